@@ -0,0 +1,704 @@
+use std::{env, fs, str::FromStr};
+
+use dotenvy::dotenv;
+use serde::Deserialize;
+
+use crate::service::{
+    login_throttle::LoginThrottling,
+    mailer::{MailTransport, SmtpSettings},
+    notifier::NotifierSink,
+    password_hasher::{HashAlgorithm, PasswordHashing},
+    password_policy::{BCRYPT_MAX_BYTES, PasswordPolicy},
+    token_keys::{SUPPORTED_ALGORITHMS, TokenSigning},
+};
+
+/// Only acceptable for local development; production refuses to boot with it.
+pub const DEFAULT_TOKEN_SECRET: &str = "secret";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Development,
+    Production,
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "development" => Ok(Environment::Development),
+            "production" => Ok(Environment::Production),
+            other => Err(format!("unknown environment `{other}`")),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("could not read config file {path}: {reason}")]
+    File { path: String, reason: String },
+    #[error("invalid value for `{key}`: {reason}")]
+    Invalid { key: &'static str, reason: String },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub environment: Environment,
+    pub db_str: String,
+    pub max_connections: u32,
+    pub host: String,
+    pub port: u16,
+    pub token_secret: String,
+    pub token_signing: TokenSigning,
+    pub auto_migrate: bool,
+    /// Shown next to the account name in authenticator apps.
+    pub mfa_issuer: String,
+    pub password_policy: PasswordPolicy,
+    pub password_hashing: PasswordHashing,
+    pub login_throttling: LoginThrottling,
+    /// Delivers password reset tokens.
+    pub notifier: NotifierSink,
+    /// Sends email verification links.
+    pub mailer: MailTransport,
+    /// Sender of outbound mail, e.g. `API <noreply@example.com>`.
+    pub mail_from: String,
+    /// Where the API is reachable from outside, for links in emails.
+    pub public_url: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            environment: Environment::Development,
+            db_str: String::new(),
+            max_connections: 5,
+            host: String::from("127.0.0.1"),
+            port: 8080,
+            token_secret: String::from(DEFAULT_TOKEN_SECRET),
+            token_signing: TokenSigning::default(),
+            auto_migrate: false,
+            mfa_issuer: String::from("api"),
+            password_policy: PasswordPolicy::default(),
+            password_hashing: PasswordHashing::default(),
+            login_throttling: LoginThrottling::default(),
+            notifier: NotifierSink::default(),
+            mailer: MailTransport::default(),
+            mail_from: String::from("api@localhost"),
+            public_url: String::from("http://127.0.0.1:8080"),
+        }
+    }
+}
+
+fn invalid(key: &'static str, reason: impl ToString) -> ConfigError {
+    ConfigError::Invalid {
+        key,
+        reason: reason.to_string(),
+    }
+}
+
+fn env_value<T: FromStr>(key: &'static str) -> Result<Option<T>, ConfigError>
+where
+    T::Err: ToString,
+{
+    match env::var(key) {
+        Ok(value) => value.parse().map(Some).map_err(|err| invalid(key, err)),
+        Err(_) => Ok(None),
+    }
+}
+
+impl Config {
+    /// Defaults, then the TOML file named by `config_file` (if any), then
+    /// environment variables (including `.env`).
+    pub fn load() -> Result<Self, ConfigError> {
+        dotenv().ok();
+
+        let mut config = match env::var("config_file") {
+            Ok(path) => Self::from_file(&path)?,
+            Err(_) => Self::default(),
+        };
+
+        config.apply_env()?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let file_error = |reason: String| ConfigError::File {
+            path: String::from(path),
+            reason,
+        };
+
+        let content = fs::read_to_string(path).map_err(|err| file_error(err.to_string()))?;
+        toml::from_str(&content).map_err(|err| file_error(err.to_string()))
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Some(environment) = env_value("environment")? {
+            self.environment = environment;
+        }
+        if let Some(db_str) = env_value("db_str")? {
+            self.db_str = db_str;
+        }
+        if let Some(max_connections) = env_value("max_connections")? {
+            self.max_connections = max_connections;
+        }
+        if let Some(host) = env_value("host")? {
+            self.host = host;
+        }
+        if let Some(port) = env_value("port")? {
+            self.port = port;
+        }
+        // `token_sercret` is the name older deployments were set up with.
+        if let Some(secret) = env_value("token_secret")?.or(env_value("token_sercret")?) {
+            self.token_secret = secret;
+        }
+        // The keys themselves can only be listed in the config file.
+        if let Some(kid) = env_value("token_signing_kid")? {
+            self.token_signing.signing_kid = Some(kid);
+        }
+        if let Some(auto_migrate) = env_value("auto_migrate")? {
+            self.auto_migrate = auto_migrate;
+        }
+        if let Some(issuer) = env_value("mfa_issuer")? {
+            self.mfa_issuer = issuer;
+        }
+
+        let policy = &mut self.password_policy;
+        if let Some(min_length) = env_value("password_min_length")? {
+            policy.min_length = min_length;
+        }
+        if let Some(max_length) = env_value("password_max_length")? {
+            policy.max_length = max_length;
+        }
+        if let Some(require) = env_value("password_require_lowercase")? {
+            policy.require_lowercase = require;
+        }
+        if let Some(require) = env_value("password_require_uppercase")? {
+            policy.require_uppercase = require;
+        }
+        if let Some(require) = env_value("password_require_digit")? {
+            policy.require_digit = require;
+        }
+        if let Some(require) = env_value("password_require_symbol")? {
+            policy.require_symbol = require;
+        }
+        if let Some(reject) = env_value("password_reject_common")? {
+            policy.reject_common = reject;
+        }
+
+        let hashing = &mut self.password_hashing;
+        if let Some(algorithm) = env_value("password_hash_algorithm")? {
+            hashing.algorithm = algorithm;
+        }
+        if let Some(memory) = env_value("argon2_memory_kib")? {
+            hashing.argon2_memory_kib = memory;
+        }
+        if let Some(iterations) = env_value("argon2_iterations")? {
+            hashing.argon2_iterations = iterations;
+        }
+        if let Some(parallelism) = env_value("argon2_parallelism")? {
+            hashing.argon2_parallelism = parallelism;
+        }
+        if let Some(cost) = env_value("bcrypt_cost")? {
+            hashing.bcrypt_cost = cost;
+        }
+
+        let throttling = &mut self.login_throttling;
+        if let Some(free) = env_value("login_account_free_attempts")? {
+            throttling.account_free_attempts = free;
+        }
+        if let Some(free) = env_value("login_ip_free_attempts")? {
+            throttling.ip_free_attempts = free;
+        }
+        if let Some(delay) = env_value("login_base_delay_secs")? {
+            throttling.base_delay_secs = delay;
+        }
+        if let Some(lockout) = env_value("login_max_lockout_secs")? {
+            throttling.max_lockout_secs = lockout;
+        }
+        if let Some(window) = env_value("login_window_secs")? {
+            throttling.window_secs = window;
+        }
+
+        if let Some(sink) = env_value("notifier")? {
+            self.notifier = sink;
+        }
+        if let Some(path) = env_value("notifier_file")? {
+            self.notifier = NotifierSink::File { path };
+        }
+
+        if let Some(from) = env_value("mail_from")? {
+            self.mail_from = from;
+        }
+        if let Some(url) = env_value("public_url")? {
+            self.public_url = url;
+        }
+        if let Some(path) = env_value("mail_file")? {
+            self.mailer = MailTransport::File { path };
+        }
+        if let Some(host) = env_value("smtp_host")? {
+            let mut smtp = match &self.mailer {
+                MailTransport::Smtp(smtp) => smtp.clone(),
+                _ => SmtpSettings::default(),
+            };
+            smtp.host = host;
+            self.mailer = MailTransport::Smtp(smtp);
+        }
+        if let MailTransport::Smtp(smtp) = &mut self.mailer {
+            if let Some(port) = env_value("smtp_port")? {
+                smtp.port = port;
+            }
+            if let Some(security) = env_value("smtp_security")? {
+                smtp.security = security;
+            }
+            if let Some(username) = env_value("smtp_username")? {
+                smtp.username = Some(username);
+            }
+            if let Some(password) = env_value("smtp_password")? {
+                smtp.password = Some(password);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.db_str.is_empty() {
+            return Err(invalid("db_str", "must be set"));
+        }
+        if self.max_connections == 0 {
+            return Err(invalid("max_connections", "must be greater than zero"));
+        }
+        // With signing keys the secret only verifies older tokens, and the
+        // default one is then ignored rather than refused.
+        let signs_with_keys = self.token_signing.signing_kid.is_some();
+        if !signs_with_keys && self.token_secret.is_empty() {
+            return Err(invalid("token_secret", "must not be empty"));
+        }
+        if !signs_with_keys
+            && self.environment == Environment::Production
+            && self.token_secret == DEFAULT_TOKEN_SECRET
+        {
+            return Err(invalid(
+                "token_secret",
+                "the default secret is not allowed in production",
+            ));
+        }
+        self.validate_token_signing()?;
+        if self.mfa_issuer.is_empty() || self.mfa_issuer.contains(':') {
+            return Err(invalid(
+                "mfa_issuer",
+                "must be non-empty and contain no `:`",
+            ));
+        }
+        if self.password_policy.min_length == 0 {
+            return Err(invalid("password_min_length", "must be greater than zero"));
+        }
+        // Only bcrypt ignores what lies past its limit.
+        if self.password_hashing.algorithm == HashAlgorithm::Bcrypt
+            && self.password_policy.max_length > BCRYPT_MAX_BYTES
+        {
+            return Err(invalid(
+                "password_max_length",
+                format!("must not exceed {BCRYPT_MAX_BYTES} bytes"),
+            ));
+        }
+        if self.password_policy.min_length > self.password_policy.max_length {
+            return Err(invalid(
+                "password_min_length",
+                "must not exceed password_max_length",
+            ));
+        }
+        if let Err(reason) = self.password_hashing.argon2_params() {
+            return Err(invalid("password_hashing", reason));
+        }
+        if !(4..=31).contains(&self.password_hashing.bcrypt_cost) {
+            return Err(invalid("bcrypt_cost", "must be between 4 and 31"));
+        }
+        if self.login_throttling.account_free_attempts < 1
+            || self.login_throttling.ip_free_attempts < 1
+        {
+            return Err(invalid(
+                "login_throttling",
+                "free attempts must be at least 1",
+            ));
+        }
+        if self.login_throttling.window_secs == 0 {
+            return Err(invalid("login_window_secs", "must be greater than zero"));
+        }
+        match &self.notifier {
+            NotifierSink::File { path } if path.is_empty() => {
+                return Err(invalid("notifier", "file sink needs a path"));
+            }
+            NotifierSink::Log | NotifierSink::File { .. }
+                if self.environment == Environment::Production =>
+            {
+                return Err(invalid(
+                    "notifier",
+                    "only the mail sink is allowed in production",
+                ));
+            }
+            _ => {}
+        }
+        match &self.mailer {
+            MailTransport::File { path } if path.is_empty() => {
+                return Err(invalid("mailer", "file transport needs a path"));
+            }
+            MailTransport::Smtp(smtp) if smtp.host.is_empty() => {
+                return Err(invalid("mailer", "smtp transport needs a host"));
+            }
+            MailTransport::Memory | MailTransport::File { .. }
+                if self.environment == Environment::Production =>
+            {
+                return Err(invalid("mailer", "production needs the smtp transport"));
+            }
+            _ => {}
+        }
+        if !self.public_url.starts_with("http://") && !self.public_url.starts_with("https://") {
+            return Err(invalid("public_url", "must be an http(s) URL"));
+        }
+
+        Ok(())
+    }
+
+    /// The secret HS256 tokens are verified with, unless it can't be trusted:
+    /// empty, or the default one in production.
+    pub fn trusted_token_secret(&self) -> Option<&str> {
+        let trusted = !self.token_secret.is_empty()
+            && (self.environment != Environment::Production
+                || self.token_secret != DEFAULT_TOKEN_SECRET);
+
+        trusted.then_some(self.token_secret.as_str())
+    }
+
+    fn validate_token_signing(&self) -> Result<(), ConfigError> {
+        let signing = &self.token_signing;
+
+        for (i, key) in signing.keys.iter().enumerate() {
+            if key.kid.is_empty() || signing.keys[..i].iter().any(|k| k.kid == key.kid) {
+                return Err(invalid("token_signing", "every key needs a unique kid"));
+            }
+            if !SUPPORTED_ALGORITHMS.contains(&key.algorithm) {
+                return Err(invalid(
+                    "token_signing",
+                    format!("key `{}` must use RS256 or EdDSA", key.kid),
+                ));
+            }
+        }
+
+        match &signing.signing_kid {
+            None if !signing.keys.is_empty() => Err(invalid(
+                "token_signing_kid",
+                "must name the key new tokens are signed with",
+            )),
+            Some(kid)
+                if !signing
+                    .keys
+                    .iter()
+                    .any(|k| &k.kid == kid && k.private_key.is_some()) =>
+            {
+                Err(invalid(
+                    "token_signing_kid",
+                    format!("no key `{kid}` with a private_key"),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_config() -> Config {
+        Config {
+            db_str: String::from("postgresql://localhost/test"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid_in_development() {
+        assert!(valid_config().validate().is_ok());
+    }
+
+    #[test]
+    fn missing_db_str_is_invalid() {
+        assert!(matches!(
+            Config::default().validate(),
+            Err(ConfigError::Invalid { key: "db_str", .. })
+        ));
+    }
+
+    #[test]
+    fn production_rejects_default_secret() {
+        let config = Config {
+            environment: Environment::Production,
+            ..valid_config()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid {
+                key: "token_secret",
+                ..
+            })
+        ));
+
+        let config = Config {
+            token_secret: String::from("a-real-secret"),
+            notifier: NotifierSink::Mail,
+            mailer: MailTransport::Smtp(SmtpSettings::default()),
+            ..config
+        };
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn production_needs_smtp() {
+        let config = Config {
+            environment: Environment::Production,
+            token_secret: String::from("a-real-secret"),
+            notifier: NotifierSink::Mail,
+            ..valid_config()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid { key: "mailer", .. })
+        ));
+    }
+
+    #[test]
+    fn toml_overrides_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            environment = "production"
+            port = 9090
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.environment, Environment::Production);
+        assert_eq!(config.port, 9090);
+        assert_eq!(config.host, "127.0.0.1");
+    }
+
+    #[test]
+    fn password_policy_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [password_policy]
+            min_length = 12
+            require_digit = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.password_policy.min_length, 12);
+        assert!(config.password_policy.require_digit);
+        assert_eq!(config.password_policy.max_length, BCRYPT_MAX_BYTES);
+    }
+
+    #[test]
+    fn invalid_argon2_params_are_rejected() {
+        let config = Config {
+            password_hashing: PasswordHashing {
+                argon2_iterations: 0,
+                ..Default::default()
+            },
+            ..valid_config()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid {
+                key: "password_hashing",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn password_max_length_is_capped_for_bcrypt() {
+        let config = Config {
+            password_policy: PasswordPolicy {
+                max_length: BCRYPT_MAX_BYTES + 1,
+                ..Default::default()
+            },
+            ..valid_config()
+        };
+
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            password_hashing: PasswordHashing {
+                algorithm: HashAlgorithm::Bcrypt,
+                ..Default::default()
+            },
+            ..config
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid {
+                key: "password_max_length",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn login_throttling_needs_free_attempts() {
+        let config = Config {
+            login_throttling: LoginThrottling {
+                account_free_attempts: 0,
+                ..Default::default()
+            },
+            ..valid_config()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid {
+                key: "login_throttling",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn notifier_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            [notifier]
+            sink = "file"
+            path = "notifications.log"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.notifier,
+            NotifierSink::File {
+                path: String::from("notifications.log")
+            }
+        );
+        assert_eq!(Config::default().notifier, NotifierSink::Log);
+        assert_eq!("mail".parse(), Ok(NotifierSink::Mail));
+        assert!("file".parse::<NotifierSink>().is_err());
+    }
+
+    #[test]
+    fn production_only_mails_notifications() {
+        let config = Config {
+            environment: Environment::Production,
+            token_secret: String::from("a-real-secret"),
+            notifier: NotifierSink::Log,
+            mailer: MailTransport::Smtp(SmtpSettings::default()),
+            ..valid_config()
+        };
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid {
+                key: "notifier",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn mailer_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            db_str = "memory://"
+
+            [mailer]
+            transport = "smtp"
+            host = "smtp.example.com"
+            security = "tls"
+            port = 465
+            "#,
+        )
+        .unwrap();
+
+        let MailTransport::Smtp(smtp) = &config.mailer else {
+            panic!("expected smtp");
+        };
+        assert_eq!(smtp.port, 465);
+        assert!(smtp.username.is_none());
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            public_url: String::from("api.example.com"),
+            ..config
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::Invalid {
+                key: "public_url",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn token_signing_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+            db_str = "memory://"
+
+            [token_signing]
+            signing_kid = "new"
+
+            [[token_signing.keys]]
+            kid = "new"
+            algorithm = "EdDSA"
+            public_key = "new.pub.pem"
+            private_key = "new.pem"
+
+            [[token_signing.keys]]
+            kid = "old"
+            algorithm = "RS256"
+            public_key = "old.pub.pem"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.token_signing.keys.len(), 2);
+        assert!(config.validate().is_ok());
+
+        // Signing keys make the secret optional, even in production.
+        let production = Config {
+            environment: Environment::Production,
+            notifier: NotifierSink::Mail,
+            mailer: MailTransport::Smtp(SmtpSettings::default()),
+            ..config.clone()
+        };
+        assert!(production.validate().is_ok());
+        assert_eq!(production.trusted_token_secret(), None);
+
+        let retired_only = Config {
+            token_signing: TokenSigning {
+                signing_kid: Some(String::from("old")),
+                ..config.token_signing.clone()
+            },
+            ..config
+        };
+
+        assert!(matches!(
+            retired_only.validate(),
+            Err(ConfigError::Invalid {
+                key: "token_signing_kid",
+                ..
+            })
+        ));
+    }
+}
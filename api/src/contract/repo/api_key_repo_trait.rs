@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+
+use crate::{
+    entity::api_key_entity::{ApiKeyCreate, ApiKeyFetched},
+    error::AppError,
+};
+
+#[async_trait]
+pub trait IApiKeyRepo {
+    async fn create(&self, key: &ApiKeyCreate) -> Result<ApiKeyFetched, AppError>;
+    async fn list(&self, user_id: i32) -> Result<Vec<ApiKeyFetched>, AppError>;
+    async fn fetch_by_hash(&self, key_hash: &str) -> Result<Option<ApiKeyFetched>, AppError>;
+    async fn touch(&self, id: i32, used_at: i64) -> Result<(), AppError>;
+    /// False if the user has no such key.
+    async fn delete(&self, user_id: i32, id: i32) -> Result<bool, AppError>;
+}
+
+#[async_trait]
+impl<T: IApiKeyRepo + Send + Sync + ?Sized> IApiKeyRepo for Box<T> {
+    async fn create(&self, key: &ApiKeyCreate) -> Result<ApiKeyFetched, AppError> {
+        (**self).create(key).await
+    }
+
+    async fn list(&self, user_id: i32) -> Result<Vec<ApiKeyFetched>, AppError> {
+        (**self).list(user_id).await
+    }
+
+    async fn fetch_by_hash(&self, key_hash: &str) -> Result<Option<ApiKeyFetched>, AppError> {
+        (**self).fetch_by_hash(key_hash).await
+    }
+
+    async fn touch(&self, id: i32, used_at: i64) -> Result<(), AppError> {
+        (**self).touch(id, used_at).await
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> Result<bool, AppError> {
+        (**self).delete(user_id, id).await
+    }
+}
@@ -1,8 +1,38 @@
 use async_trait::async_trait;
 
-use crate::entity::item_entity::ItemCreate;
+use crate::{
+    entity::item_entity::{ItemCreate, ItemFetched, ItemUpdate},
+    error::AppError,
+};
 
 #[async_trait]
 pub trait IItemRepo {
-    async fn register(&self, item: &ItemCreate);
+    async fn register(&self, item: &ItemCreate) -> Result<ItemFetched, AppError>;
+    async fn fetch_by_id(&self, id: i32) -> Result<Option<ItemFetched>, AppError>;
+    async fn fetch_by_user(&self, user_id: i32) -> Result<Vec<ItemFetched>, AppError>;
+    async fn update(&self, id: i32, item: &ItemUpdate) -> Result<Option<ItemFetched>, AppError>;
+    async fn delete(&self, id: i32) -> Result<bool, AppError>;
+}
+
+#[async_trait]
+impl<T: IItemRepo + Send + Sync + ?Sized> IItemRepo for Box<T> {
+    async fn register(&self, item: &ItemCreate) -> Result<ItemFetched, AppError> {
+        (**self).register(item).await
+    }
+
+    async fn fetch_by_id(&self, id: i32) -> Result<Option<ItemFetched>, AppError> {
+        (**self).fetch_by_id(id).await
+    }
+
+    async fn fetch_by_user(&self, user_id: i32) -> Result<Vec<ItemFetched>, AppError> {
+        (**self).fetch_by_user(user_id).await
+    }
+
+    async fn update(&self, id: i32, item: &ItemUpdate) -> Result<Option<ItemFetched>, AppError> {
+        (**self).update(id, item).await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, AppError> {
+        (**self).delete(id).await
+    }
 }
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+
+use crate::{entity::login_attempt_entity::LoginAttemptFetched, error::AppError};
+
+#[async_trait]
+pub trait ILoginAttemptRepo {
+    async fn fetch(&self, key: &str) -> Result<Option<LoginAttemptFetched>, AppError>;
+    /// Counts a failed login and returns the new total. Failures from before
+    /// `reset_before` are forgotten first.
+    async fn record_failure(&self, key: &str, now: i64, reset_before: i64)
+    -> Result<i32, AppError>;
+    async fn lock(&self, key: &str, until: i64) -> Result<(), AppError>;
+    async fn clear(&self, key: &str) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl<T: ILoginAttemptRepo + Send + Sync + ?Sized> ILoginAttemptRepo for Box<T> {
+    async fn fetch(&self, key: &str) -> Result<Option<LoginAttemptFetched>, AppError> {
+        (**self).fetch(key).await
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        now: i64,
+        reset_before: i64,
+    ) -> Result<i32, AppError> {
+        (**self).record_failure(key, now, reset_before).await
+    }
+
+    async fn lock(&self, key: &str, until: i64) -> Result<(), AppError> {
+        (**self).lock(key, until).await
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        (**self).clear(key).await
+    }
+}
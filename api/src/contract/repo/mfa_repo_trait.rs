@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+
+use crate::{entity::mfa_entity::MfaFetched, error::AppError};
+
+#[async_trait]
+pub trait IMfaRepo {
+    async fn fetch(&self, user_id: i32) -> Result<Option<MfaFetched>, AppError>;
+    /// Starts enrollment over with a new secret that is not enabled yet.
+    async fn set_pending(&self, user_id: i32, secret: &str) -> Result<(), AppError>;
+    /// Turns 2FA on and replaces any earlier recovery codes.
+    async fn enable(&self, user_id: i32, recovery_hashes: &[String]) -> Result<(), AppError>;
+    /// Returns false if `step` or a later one was already used.
+    async fn use_step(&self, user_id: i32, step: i64) -> Result<bool, AppError>;
+    /// Returns false if the code is unknown or was already used.
+    async fn use_recovery_code(&self, user_id: i32, code_hash: &str) -> Result<bool, AppError>;
+}
+
+#[async_trait]
+impl<T: IMfaRepo + Send + Sync + ?Sized> IMfaRepo for Box<T> {
+    async fn fetch(&self, user_id: i32) -> Result<Option<MfaFetched>, AppError> {
+        (**self).fetch(user_id).await
+    }
+
+    async fn set_pending(&self, user_id: i32, secret: &str) -> Result<(), AppError> {
+        (**self).set_pending(user_id, secret).await
+    }
+
+    async fn enable(&self, user_id: i32, recovery_hashes: &[String]) -> Result<(), AppError> {
+        (**self).enable(user_id, recovery_hashes).await
+    }
+
+    async fn use_step(&self, user_id: i32, step: i64) -> Result<bool, AppError> {
+        (**self).use_step(user_id, step).await
+    }
+
+    async fn use_recovery_code(&self, user_id: i32, code_hash: &str) -> Result<bool, AppError> {
+        (**self).use_recovery_code(user_id, code_hash).await
+    }
+}
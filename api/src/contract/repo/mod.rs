@@ -1,2 +1,10 @@
+pub mod api_key_repo_trait;
 pub mod item_repo_trait;
+pub mod login_attempt_repo_trait;
+pub mod mfa_repo_trait;
+pub mod password_reset_repo_trait;
+pub mod refresh_token_repo_trait;
+pub mod revocation_repo_trait;
+pub mod session_repo_trait;
+pub mod unit_of_work_trait;
 pub mod user_repo_trait;
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+
+use crate::{entity::password_reset_entity::PasswordResetCreate, error::AppError};
+
+#[async_trait]
+pub trait IPasswordResetRepo {
+    async fn create(&self, reset: &PasswordResetCreate) -> Result<(), AppError>;
+    /// Marks an unused token that hasn't expired by `now` as used and returns
+    /// its user, so a token can only ever be redeemed once.
+    async fn consume(&self, token_hash: &str, now: i64) -> Result<Option<i32>, AppError>;
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl<T: IPasswordResetRepo + Send + Sync + ?Sized> IPasswordResetRepo for Box<T> {
+    async fn create(&self, reset: &PasswordResetCreate) -> Result<(), AppError> {
+        (**self).create(reset).await
+    }
+
+    async fn consume(&self, token_hash: &str, now: i64) -> Result<Option<i32>, AppError> {
+        (**self).consume(token_hash, now).await
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError> {
+        (**self).delete_user(user_id).await
+    }
+}
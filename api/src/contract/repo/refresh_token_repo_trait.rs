@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+
+use crate::{
+    entity::refresh_token_entity::{RefreshTokenCreate, RefreshTokenFetched},
+    error::AppError,
+};
+
+#[async_trait]
+pub trait IRefreshTokenRepo {
+    async fn register(&self, token: &RefreshTokenCreate) -> Result<(), AppError>;
+    async fn fetch_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenFetched>, AppError>;
+    async fn mark_used(&self, id: i32) -> Result<bool, AppError>;
+    async fn revoke_family(&self, family: &str) -> Result<(), AppError>;
+    async fn revoke_user(&self, user_id: i32) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl<T: IRefreshTokenRepo + Send + Sync + ?Sized> IRefreshTokenRepo for Box<T> {
+    async fn register(&self, token: &RefreshTokenCreate) -> Result<(), AppError> {
+        (**self).register(token).await
+    }
+
+    async fn fetch_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenFetched>, AppError> {
+        (**self).fetch_by_hash(token_hash).await
+    }
+
+    async fn mark_used(&self, id: i32) -> Result<bool, AppError> {
+        (**self).mark_used(id).await
+    }
+
+    async fn revoke_family(&self, family: &str) -> Result<(), AppError> {
+        (**self).revoke_family(family).await
+    }
+
+    async fn revoke_user(&self, user_id: i32) -> Result<(), AppError> {
+        (**self).revoke_user(user_id).await
+    }
+}
@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+
+use crate::error::AppError;
+
+#[async_trait]
+pub trait IRevocationRepo {
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError>;
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError>;
+    async fn token_version(&self, user_id: i32) -> Result<i32, AppError>;
+    async fn bump_token_version(&self, user_id: i32) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl<T: IRevocationRepo + Send + Sync + ?Sized> IRevocationRepo for Box<T> {
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        (**self).revoke(jti, expires_at).await
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError> {
+        (**self).is_revoked(jti).await
+    }
+
+    async fn token_version(&self, user_id: i32) -> Result<i32, AppError> {
+        (**self).token_version(user_id).await
+    }
+
+    async fn bump_token_version(&self, user_id: i32) -> Result<(), AppError> {
+        (**self).bump_token_version(user_id).await
+    }
+}
@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+
+use crate::{
+    entity::session_entity::{SessionCreate, SessionFetched},
+    error::AppError,
+};
+
+#[async_trait]
+pub trait ISessionRepo {
+    async fn create(&self, session: &SessionCreate) -> Result<SessionFetched, AppError>;
+    async fn fetch(&self, id: i32) -> Result<Option<SessionFetched>, AppError>;
+    async fn fetch_by_family(&self, family: &str) -> Result<Option<SessionFetched>, AppError>;
+    /// Sessions seen at or after `seen_since`, most recent first.
+    async fn list(&self, user_id: i32, seen_since: i64) -> Result<Vec<SessionFetched>, AppError>;
+    async fn touch(&self, id: i32, seen_at: i64) -> Result<(), AppError>;
+    async fn delete(&self, id: i32) -> Result<(), AppError>;
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl<T: ISessionRepo + Send + Sync + ?Sized> ISessionRepo for Box<T> {
+    async fn create(&self, session: &SessionCreate) -> Result<SessionFetched, AppError> {
+        (**self).create(session).await
+    }
+
+    async fn fetch(&self, id: i32) -> Result<Option<SessionFetched>, AppError> {
+        (**self).fetch(id).await
+    }
+
+    async fn fetch_by_family(&self, family: &str) -> Result<Option<SessionFetched>, AppError> {
+        (**self).fetch_by_family(family).await
+    }
+
+    async fn list(&self, user_id: i32, seen_since: i64) -> Result<Vec<SessionFetched>, AppError> {
+        (**self).list(user_id, seen_since).await
+    }
+
+    async fn touch(&self, id: i32, seen_at: i64) -> Result<(), AppError> {
+        (**self).touch(id, seen_at).await
+    }
+
+    async fn delete(&self, id: i32) -> Result<(), AppError> {
+        (**self).delete(id).await
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError> {
+        (**self).delete_user(user_id).await
+    }
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::{
+        item_repo_trait::IItemRepo, login_attempt_repo_trait::ILoginAttemptRepo,
+        user_repo_trait::IUserRepo,
+    },
+    error::AppError,
+};
+
+/// Repos bound to a single transaction. Dropping it without `commit` rolls
+/// everything back.
+#[async_trait]
+pub trait IUnitOfWork: Send + Sync {
+    fn users(&self) -> &(dyn IUserRepo + Send + Sync);
+    fn items(&self) -> &(dyn IItemRepo + Send + Sync);
+    fn login_attempts(&self) -> &(dyn ILoginAttemptRepo + Send + Sync);
+    async fn commit(self: Box<Self>) -> Result<(), AppError>;
+    async fn rollback(self: Box<Self>) -> Result<(), AppError>;
+}
+
+pub type DynUnitOfWork = Box<dyn IUnitOfWork>;
+
+#[async_trait]
+pub trait IUnitOfWorkFactory {
+    async fn begin(&self) -> Result<DynUnitOfWork, AppError>;
+}
@@ -1,12 +1,111 @@
-use crate::entity::user_entity::{UserFetched, UserRegister};
+use crate::{
+    entity::{
+        role_entity::{UserAccess, UserSummary},
+        user_entity::{UserEmail, UserFetched, UserRegister},
+    },
+    error::AppError,
+};
 
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait IUserRepo {
-    async fn exists(&self, name: &str) -> bool;
-    async fn password_hash(&self, password: &str) -> String;
-    async fn register(&self, dto: &UserRegister);
-    async fn fetch_by_name(&self, name: &str) -> UserFetched;
-    async fn fetch_by_id(&self, id: i32) -> UserFetched;
+    async fn exists(&self, name: &str) -> Result<bool, AppError>;
+    /// Also grants the default `user` role.
+    async fn register(&self, dto: &UserRegister) -> Result<(), AppError>;
+    async fn fetch_by_name(&self, name: &str) -> Result<UserFetched, AppError>;
+    async fn fetch_by_id(&self, id: i32) -> Result<UserFetched, AppError>;
+    async fn update_password(&self, id: i32, password: &str) -> Result<(), AppError>;
+    /// `false` if there is no such user.
+    async fn rename(&self, id: i32, name: &str) -> Result<bool, AppError>;
+    /// Also deletes everything the user owns. `false` if there is no such
+    /// user.
+    async fn delete(&self, id: i32) -> Result<bool, AppError>;
+    async fn access(&self, id: i32) -> Result<UserAccess, AppError>;
+    /// Ordered by id.
+    async fn list(&self, limit: i64, offset: i64) -> Result<Vec<UserSummary>, AppError>;
+    /// Replaces all of the user's roles.
+    async fn set_roles(&self, id: i32, roles: &[String]) -> Result<(), AppError>;
+    async fn email(&self, id: i32) -> Result<UserEmail, AppError>;
+    /// Replaces the address and marks it unverified. Any number of users may
+    /// hold the same unverified address.
+    async fn set_email(&self, id: i32, email: Option<&str>) -> Result<(), AppError>;
+    /// Verifies `email` only if it is still the user's address, so a link
+    /// sent to an old address can't vouch for a new one. Fails with
+    /// `email_taken` if another user has already verified it.
+    async fn verify_email(&self, id: i32, email: &str, at: i64) -> Result<bool, AppError>;
+}
+
+/// What `register` and `rename` return when the name is already taken.
+pub fn user_exists() -> AppError {
+    AppError::conflict("user_exists", "A user with this name already exists.")
+}
+
+/// What `verify_email` returns when another user has verified the address.
+pub fn email_taken() -> AppError {
+    AppError::conflict(
+        "email_taken",
+        "Another user already has this email address.",
+    )
+}
+
+/// What `set_roles` returns for a role that doesn't exist.
+pub fn unknown_role(name: &str) -> AppError {
+    AppError::validation("unknown_role", &format!("Unknown role `{name}`."))
+}
+
+/// Lets services hold a boxed repo chosen at runtime.
+#[async_trait]
+impl<T: IUserRepo + Send + Sync + ?Sized> IUserRepo for Box<T> {
+    async fn exists(&self, name: &str) -> Result<bool, AppError> {
+        (**self).exists(name).await
+    }
+
+    async fn register(&self, dto: &UserRegister) -> Result<(), AppError> {
+        (**self).register(dto).await
+    }
+
+    async fn fetch_by_name(&self, name: &str) -> Result<UserFetched, AppError> {
+        (**self).fetch_by_name(name).await
+    }
+
+    async fn fetch_by_id(&self, id: i32) -> Result<UserFetched, AppError> {
+        (**self).fetch_by_id(id).await
+    }
+
+    async fn update_password(&self, id: i32, password: &str) -> Result<(), AppError> {
+        (**self).update_password(id, password).await
+    }
+
+    async fn rename(&self, id: i32, name: &str) -> Result<bool, AppError> {
+        (**self).rename(id, name).await
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, AppError> {
+        (**self).delete(id).await
+    }
+
+    async fn access(&self, id: i32) -> Result<UserAccess, AppError> {
+        (**self).access(id).await
+    }
+
+    async fn list(&self, limit: i64, offset: i64) -> Result<Vec<UserSummary>, AppError> {
+        (**self).list(limit, offset).await
+    }
+
+    async fn set_roles(&self, id: i32, roles: &[String]) -> Result<(), AppError> {
+        (**self).set_roles(id, roles).await
+    }
+
+    async fn email(&self, id: i32) -> Result<UserEmail, AppError> {
+        (**self).email(id).await
+    }
+
+    async fn set_email(&self, id: i32, email: Option<&str>) -> Result<(), AppError> {
+        (**self).set_email(id, email).await
+    }
+
+    async fn verify_email(&self, id: i32, email: &str, at: i64) -> Result<bool, AppError> {
+        (**self).verify_email(id, email, at).await
+    }
 }
@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::{entity::role_entity::UserSummary, error::AppError};
+
+#[async_trait]
+pub trait IAdminService {
+    async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<UserSummary>, AppError>;
+    /// `actor_id` is the admin making the change. The user's current tokens
+    /// stop working, so the new roles apply from their next refresh.
+    async fn set_roles(
+        &self,
+        actor_id: i32,
+        user_id: i32,
+        roles: &[String],
+    ) -> Result<UserSummary, AppError>;
+}
@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+
+use crate::{
+    dto::api_key_dto::ApiKeyDto,
+    entity::api_key_entity::{ApiKeyCreated, ApiKeyView},
+    error::AppError,
+};
+
+#[async_trait]
+pub trait IApiKeyService {
+    /// The returned key is the only time it is ever shown.
+    async fn create(&self, user_id: i32, dto: &ApiKeyDto) -> Result<ApiKeyCreated, AppError>;
+    async fn list(&self, user_id: i32) -> Result<Vec<ApiKeyView>, AppError>;
+    async fn revoke(&self, user_id: i32, id: i32) -> Result<(), AppError>;
+}
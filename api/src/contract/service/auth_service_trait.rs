@@ -1,13 +1,36 @@
-use actix_web::{HttpRequest, http::StatusCode};
 use async_trait::async_trait;
+use jsonwebtoken::jwk::JwkSet;
 
-use crate::entity::{
-    auth_entity::{AuthResponse, UserAuth},
-    user_entity::UserFetched,
+use crate::{
+    entity::{
+        auth_entity::{AuthOutcome, AuthResponse, Caller, ClientInfo, UserAuth},
+        session_entity::SessionView,
+    },
+    error::AppError,
 };
 
-#[async_trait(?Send)]
+#[async_trait]
 pub trait IAuthService {
-    async fn auth(&self, user: &UserAuth) -> Result<AuthResponse, String>;
-    async fn user(&self, req: HttpRequest) -> Result<UserFetched, StatusCode>;
+    /// The client's address throttles failed attempts; a successful login
+    /// opens a session recording both.
+    async fn auth(&self, user: &UserAuth, client: &ClientInfo) -> Result<AuthOutcome, AppError>;
+    /// Trades the challenge from `auth` and a second-factor code for tokens.
+    async fn verify_mfa(
+        &self,
+        challenge: &str,
+        code: &str,
+        client: &ClientInfo,
+    ) -> Result<AuthResponse, AppError>;
+    async fn refresh(&self, refresh: &str) -> Result<AuthResponse, AppError>;
+    async fn user(&self, token: &str) -> Result<Caller, AppError>;
+    /// Like `user`, for a personal API key instead of an access token.
+    async fn api_key_user(&self, key: &str) -> Result<Caller, AppError>;
+    async fn logout(&self, token: &str, refresh: Option<&str>) -> Result<(), AppError>;
+    async fn logout_all(&self, token: &str) -> Result<(), AppError>;
+    /// The logins of the token's owner that are still active.
+    async fn sessions(&self, token: &str) -> Result<Vec<SessionView>, AppError>;
+    /// Signs the token's owner out of one of their logins.
+    async fn revoke_session(&self, token: &str, id: i32) -> Result<(), AppError>;
+    /// Public keys other services can verify our tokens with.
+    fn jwks(&self) -> JwkSet;
 }
@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::{entity::user_entity::UserEmail, error::AppError};
+
+#[async_trait]
+pub trait IEmailService {
+    async fn email(&self, user_id: i32) -> Result<UserEmail, AppError>;
+    /// Replaces the user's address and mails a verification link to it.
+    /// Succeeds even if someone else has the address, so it can't be used to
+    /// probe for accounts; only verifying claims it.
+    async fn set_email(&self, user_id: i32, email: &str) -> Result<(), AppError>;
+    /// Mails a fresh link to the user's unverified address.
+    async fn resend(&self, user_id: i32) -> Result<(), AppError>;
+    /// Redeems a link sent by `set_email` or `resend`.
+    async fn verify(&self, token: &str) -> Result<(), AppError>;
+}
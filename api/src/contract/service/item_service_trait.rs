@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+
+use crate::{
+    entity::item_entity::{ItemCreate, ItemFetched, ItemUpdate},
+    error::AppError,
+};
+
+#[async_trait]
+pub trait IItemService {
+    async fn create(&self, item: &ItemCreate) -> Result<ItemFetched, AppError>;
+    async fn fetch(&self, id: i32) -> Result<ItemFetched, AppError>;
+    async fn list(&self, user_id: i32) -> Result<Vec<ItemFetched>, AppError>;
+    async fn update(
+        &self,
+        user_id: i32,
+        id: i32,
+        item: &ItemUpdate,
+    ) -> Result<ItemFetched, AppError>;
+    async fn delete(&self, user_id: i32, id: i32) -> Result<(), AppError>;
+}
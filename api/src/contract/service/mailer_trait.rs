@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{entity::mail_entity::Mail, error::AppError};
+
+#[async_trait]
+pub trait IMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl<T: IMailer + Send + Sync + ?Sized> IMailer for Box<T> {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        (**self).send(mail).await
+    }
+}
+
+#[async_trait]
+impl<T: IMailer + Send + Sync + ?Sized> IMailer for Arc<T> {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        (**self).send(mail).await
+    }
+}
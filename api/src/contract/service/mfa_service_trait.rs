@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+
+use crate::{
+    entity::{
+        mfa_entity::{MfaEnrollment, RecoveryCodes},
+        user_entity::UserFetched,
+    },
+    error::AppError,
+};
+
+#[async_trait]
+pub trait IMfaService {
+    async fn enroll(&self, user: &UserFetched) -> Result<MfaEnrollment, AppError>;
+    /// Enables 2FA once the user proves their authenticator works.
+    async fn confirm(&self, user_id: i32, code: &str) -> Result<RecoveryCodes, AppError>;
+}
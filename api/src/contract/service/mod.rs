@@ -1,2 +1,11 @@
+pub mod admin_service_trait;
+pub mod api_key_service_trait;
 pub mod auth_service_trait;
+pub mod email_service_trait;
+pub mod item_service_trait;
+pub mod mailer_trait;
+pub mod mfa_service_trait;
+pub mod notifier_trait;
+pub mod password_hasher_trait;
+pub mod password_service_trait;
 pub mod user_service_trait;
@@ -0,0 +1,8 @@
+use async_trait::async_trait;
+
+use crate::{entity::notification_entity::Notification, error::AppError};
+
+#[async_trait]
+pub trait INotifier {
+    async fn notify(&self, notification: &Notification) -> Result<(), AppError>;
+}
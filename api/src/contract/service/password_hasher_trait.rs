@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCheck {
+    Mismatch,
+    Valid,
+    /// The password is right but the stored hash uses an algorithm or
+    /// parameters that are no longer current.
+    NeedsRehash,
+}
+
+#[async_trait]
+pub trait IPasswordHasher {
+    async fn hash(&self, password: &str) -> Result<String, AppError>;
+    async fn verify(&self, password: &str, hash: &str) -> Result<PasswordCheck, AppError>;
+}
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+
+use crate::{
+    dto::password_dto::{PasswordChangeDto, PasswordResetDto},
+    entity::auth_entity::ClientInfo,
+    error::AppError,
+};
+
+#[async_trait]
+pub trait IPasswordService {
+    /// Signs the user out of every session except `keep_session`. Wrong
+    /// current passwords count towards the login lockout.
+    async fn change(
+        &self,
+        user_id: i32,
+        keep_session: Option<i32>,
+        dto: &PasswordChangeDto,
+        client: &ClientInfo,
+    ) -> Result<(), AppError>;
+    /// Counts a reset request against the name and the caller's IP, refusing
+    /// it while either is locked out. Doesn't look the name up.
+    async fn forgot(&self, name: &str, client: &ClientInfo) -> Result<(), AppError>;
+    /// Sends a reset token to the named user. Succeeds silently for unknown
+    /// names so the endpoint can't be used to probe for accounts; callers run
+    /// it off the request path so the response time doesn't tell either.
+    async fn send_reset(&self, name: &str) -> Result<(), AppError>;
+    /// Signs the user out everywhere.
+    async fn reset(&self, dto: &PasswordResetDto) -> Result<(), AppError>;
+}
@@ -1,8 +1,16 @@
-use crate::entity::user_entity::UserRegister;
+use crate::{
+    dto::user_dto::UserPatchDto,
+    entity::user_entity::{UserProfile, UserRegister},
+    error::AppError,
+};
 
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait IUserService {
-    async fn register(&self, dto: &UserRegister) -> Result<(), String>;
+    async fn register(&self, dto: &UserRegister) -> Result<(), AppError>;
+    async fn profile(&self, id: i32) -> Result<UserProfile, AppError>;
+    async fn update(&self, id: i32, dto: &UserPatchDto) -> Result<UserProfile, AppError>;
+    /// Deletes the account and everything it owns.
+    async fn delete(&self, id: i32) -> Result<(), AppError>;
 }
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiKeyDto {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Unix seconds; the key never expires without one.
+    #[serde(default)]
+    pub expires_at: Option<i64>,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmailDto {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct EmailTokenQuery {
+    pub token: String,
+}
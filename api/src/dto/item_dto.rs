@@ -2,6 +2,12 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ItemDto {
-    name: String,
-    price: f64,
+    pub name: String,
+    pub price: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ItemPatchDto {
+    pub name: Option<String>,
+    pub price: Option<f64>,
 }
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MfaCodeDto {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MfaChallengeDto {
+    pub challenge_token: String,
+    pub code: String,
+}
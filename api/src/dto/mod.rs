@@ -1,2 +1,8 @@
+pub mod admin_dto;
+pub mod api_key_dto;
+pub mod auth_dto;
+pub mod email_dto;
 pub mod item_dto;
+pub mod mfa_dto;
+pub mod password_dto;
 pub mod user_dto;
@@ -17,3 +17,8 @@ pub struct UserDto {
     pub name: String,
     pub password: String,
 }
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UserPatchDto {
+    pub name: Option<String>,
+}
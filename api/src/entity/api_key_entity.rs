@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+use crate::entity::role_entity::{PERMISSION_MANAGE_USERS, PERMISSION_READ_USERS};
+
+pub const SCOPE_ITEMS_READ: &str = "items:read";
+pub const SCOPE_ITEMS_WRITE: &str = "items:write";
+
+/// Scopes a key may be given. The `users:*` ones only take effect while the
+/// owner still holds the matching permission.
+pub const SCOPES: &[&str] = &[
+    SCOPE_ITEMS_READ,
+    SCOPE_ITEMS_WRITE,
+    PERMISSION_READ_USERS,
+    PERMISSION_MANAGE_USERS,
+];
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiKeyCreate {
+    pub user_id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct ApiKeyFetched {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    /// The start of the key, so its owner can tell keys apart.
+    pub prefix: String,
+    pub key_hash: String,
+    /// Space separated, like OAuth scopes.
+    pub scopes: String,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+}
+
+impl ApiKeyFetched {
+    pub fn scope_list(&self) -> Vec<String> {
+        self.scopes.split_whitespace().map(String::from).collect()
+    }
+}
+
+/// What the owner sees of a key; the key itself is only shown once.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ApiKeyView {
+    pub id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: i64,
+    pub expires_at: Option<i64>,
+    pub last_used_at: Option<i64>,
+}
+
+impl From<&ApiKeyFetched> for ApiKeyView {
+    fn from(key: &ApiKeyFetched) -> Self {
+        Self {
+            id: key.id,
+            name: key.name.clone(),
+            prefix: key.prefix.clone(),
+            scopes: key.scope_list(),
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ApiKeyCreated {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKeyView,
+}
@@ -1,26 +1,79 @@
 use serde::{Deserialize, Serialize};
 
+use crate::entity::user_entity::UserFetched;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub user_id: i32,
     pub exp: u64,
+    pub jti: String,
+    pub ver: i32,
+    /// Granted when the token was issued; changing them bumps `ver`.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub perms: Vec<String>,
+    /// The session the token belongs to. Tokens issued before sessions
+    /// existed have none.
+    #[serde(default)]
+    pub sid: Option<i32>,
+}
+
+/// Where a login comes from, recorded on its session.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// The user behind a valid access token, with what the token grants.
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub user: UserFetched,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    /// Set when the caller used an API key, which can do only this much.
+    pub scopes: Option<Vec<String>>,
+    /// The login the access token was issued to, if any.
+    pub session_id: Option<i32>,
+}
+
+/// Claims of the short-lived token that stands between a correct password
+/// and the second factor. It grants nothing on its own.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaClaims {
+    pub user_id: i32,
+    pub exp: u64,
+    pub purpose: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AuthResponse {
     pub token: String,
     pub refresh: String,
+    pub expires_in: u64,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct UserDto {
-    pub name: String,
-    pub password: String,
+pub struct MfaChallenge {
+    pub mfa_required: bool,
+    pub challenge_token: String,
+    pub expires_in: u64,
 }
 
+/// What a correct password gets you: tokens, or a challenge when the
+/// account has two-factor authentication on.
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct AuthMsg {
-    pub msg: String,
+#[serde(untagged)]
+pub enum AuthOutcome {
+    Tokens(AuthResponse),
+    MfaRequired(MfaChallenge),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UserDto {
+    pub name: String,
+    pub password: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -40,4 +93,5 @@ pub struct LoggedUser {
 pub struct AuthMe {
     pub id: i32,
     pub name: String,
+    pub roles: Vec<String>,
 }
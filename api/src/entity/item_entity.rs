@@ -6,3 +6,17 @@ pub struct ItemCreate {
     pub price: f64,
     pub user_id: i32,
 }
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct ItemFetched {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub price: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct ItemUpdate {
+    pub name: Option<String>,
+    pub price: Option<f64>,
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct LoginAttemptFetched {
+    pub key: String,
+    pub failures: i32,
+    pub last_failure: i64,
+    pub locked_until: i64,
+}
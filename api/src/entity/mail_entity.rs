@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// A plain-text message to one recipient; the sender is configured on the
+/// mailer.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
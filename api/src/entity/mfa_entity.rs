@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct MfaFetched {
+    pub user_id: i32,
+    pub secret: String,
+    /// False until the user confirms enrollment with a first code.
+    pub enabled: bool,
+    /// The last TOTP time step accepted, so a code can't be replayed.
+    pub last_step: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MfaEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
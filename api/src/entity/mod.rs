@@ -1,3 +1,12 @@
+pub mod api_key_entity;
 pub mod auth_entity;
 pub mod item_entity;
+pub mod login_attempt_entity;
+pub mod mail_entity;
+pub mod mfa_entity;
+pub mod notification_entity;
+pub mod password_reset_entity;
+pub mod refresh_token_entity;
+pub mod role_entity;
+pub mod session_entity;
 pub mod user_entity;
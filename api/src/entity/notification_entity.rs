@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Something a user has to be told outside of an API response.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub enum Notification {
+    PasswordReset {
+        name: String,
+        /// The user's verified address, if they have one.
+        email: Option<String>,
+        token: String,
+        expires_at: i64,
+    },
+}
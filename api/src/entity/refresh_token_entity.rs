@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RefreshTokenCreate {
+    pub user_id: i32,
+    pub token_hash: String,
+    pub family: String,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, sqlx::FromRow)]
+pub struct RefreshTokenFetched {
+    pub id: i32,
+    pub user_id: i32,
+    pub token_hash: String,
+    pub family: String,
+    pub expires_at: i64,
+    pub used: bool,
+    pub revoked: bool,
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Every new user gets this role.
+pub const ROLE_USER: &str = "user";
+pub const ROLE_ADMIN: &str = "admin";
+
+pub const PERMISSION_READ_USERS: &str = "users:read";
+pub const PERMISSION_MANAGE_USERS: &str = "users:manage";
+
+/// A user's roles and everything they grant.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct UserAccess {
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct UserSummary {
+    pub id: i32,
+    pub name: String,
+    pub roles: Vec<String>,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct SessionCreate {
+    pub user_id: i32,
+    /// The refresh token family issued to this login.
+    pub family: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct SessionFetched {
+    pub id: i32,
+    pub user_id: i32,
+    pub family: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: i64,
+    pub last_seen_at: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SessionView {
+    pub id: i32,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: i64,
+    pub last_seen_at: i64,
+    /// Whether this is the session the request was made from.
+    pub current: bool,
+}
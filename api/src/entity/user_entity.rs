@@ -12,6 +12,35 @@ pub struct UserFetched {
     pub password: String,
 }
 
+/// What anyone may see about a user.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct UserProfile {
+    pub id: i32,
+    pub name: String,
+}
+
+/// A user's contact address. Changing it clears `verified_at`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct UserEmail {
+    pub email: Option<String>,
+    pub verified_at: Option<i64>,
+}
+
+impl UserEmail {
+    pub fn is_verified(&self) -> bool {
+        self.email.is_some() && self.verified_at.is_some()
+    }
+}
+
+/// Claims of a signed email verification link.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailClaims {
+    pub user_id: i32,
+    pub email: String,
+    pub exp: u64,
+    pub purpose: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct UserRegister {
     pub name: String,
@@ -0,0 +1,281 @@
+use actix_web::{
+    HttpRequest, HttpResponse, ResponseError,
+    error::{JsonPayloadError, PathError, QueryPayloadError},
+    http::{StatusCode, header::RETRY_AFTER},
+};
+use serde::{Deserialize, Serialize};
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Every variant carries a machine-readable `code` that clients can branch on
+/// and a human-readable `detail`.
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error("{detail}")]
+    NotFound { code: &'static str, detail: String },
+    #[error("{detail}")]
+    Conflict { code: &'static str, detail: String },
+    #[error("{detail}")]
+    Unauthorized { code: &'static str, detail: String },
+    #[error("{detail}")]
+    Forbidden { code: &'static str, detail: String },
+    #[error("{detail}")]
+    Validation {
+        code: &'static str,
+        detail: String,
+        errors: Vec<Violation>,
+    },
+    #[error("{detail}")]
+    TooManyRequests {
+        code: &'static str,
+        detail: String,
+        retry_after: u64,
+    },
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn not_found(code: &'static str, detail: &str) -> Self {
+        AppError::NotFound {
+            code,
+            detail: String::from(detail),
+        }
+    }
+
+    pub fn conflict(code: &'static str, detail: &str) -> Self {
+        AppError::Conflict {
+            code,
+            detail: String::from(detail),
+        }
+    }
+
+    pub fn unauthorized(code: &'static str, detail: &str) -> Self {
+        AppError::Unauthorized {
+            code,
+            detail: String::from(detail),
+        }
+    }
+
+    pub fn forbidden(code: &'static str, detail: &str) -> Self {
+        AppError::Forbidden {
+            code,
+            detail: String::from(detail),
+        }
+    }
+
+    pub fn validation(code: &'static str, detail: &str) -> Self {
+        Self::violations(code, detail, Vec::new())
+    }
+
+    /// A validation error that lists every rule the input broke.
+    pub fn violations(code: &'static str, detail: &str, errors: Vec<Violation>) -> Self {
+        AppError::Validation {
+            code,
+            detail: String::from(detail),
+            errors,
+        }
+    }
+
+    /// `retry_after` is in seconds and is sent back as the `Retry-After` header.
+    pub fn too_many_requests(code: &'static str, detail: &str, retry_after: u64) -> Self {
+        AppError::TooManyRequests {
+            code,
+            detail: String::from(detail),
+            retry_after,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { code, .. }
+            | AppError::Conflict { code, .. }
+            | AppError::Unauthorized { code, .. }
+            | AppError::Forbidden { code, .. }
+            | AppError::Validation { code, .. }
+            | AppError::TooManyRequests { code, .. } => code,
+            AppError::Database(_) | AppError::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn problem(&self) -> ProblemDetails {
+        let status = self.status_code();
+
+        // Never leak driver or library details to the client.
+        let detail = match self {
+            AppError::Database(_) | AppError::Internal(_) => String::from("Internal server error."),
+            err => err.to_string(),
+        };
+
+        let errors = match self {
+            AppError::Validation { errors, .. } => errors.clone(),
+            _ => Vec::new(),
+        };
+
+        ProblemDetails {
+            problem_type: format!("/problems/{}", self.code()),
+            title: String::from(status.canonical_reason().unwrap_or("Error")),
+            status: status.as_u16(),
+            detail,
+            code: String::from(self.code()),
+            errors,
+        }
+    }
+}
+
+/// One broken rule inside a validation problem.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Violation {
+    pub code: String,
+    pub detail: String,
+}
+
+impl Violation {
+    pub fn new(code: &str, detail: &str) -> Self {
+        Self {
+            code: String::from(code),
+            detail: String::from(detail),
+        }
+    }
+}
+
+/// RFC 7807 body returned for every error response.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<Violation>,
+}
+
+impl ResponseError for AppError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } => StatusCode::CONFLICT,
+            AppError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden { .. } => StatusCode::FORBIDDEN,
+            AppError::Validation { .. } => StatusCode::BAD_REQUEST,
+            AppError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Database(_) | AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut res = HttpResponse::build(self.status_code());
+
+        if let AppError::TooManyRequests { retry_after, .. } = self {
+            res.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+
+        res.content_type(PROBLEM_CONTENT_TYPE).json(self.problem())
+    }
+}
+
+/// Extractor error handlers, so malformed requests also get a problem body.
+pub fn json_error_handler(err: JsonPayloadError, _: &HttpRequest) -> actix_web::Error {
+    AppError::validation("invalid_body", &err.to_string()).into()
+}
+
+pub fn path_error_handler(err: PathError, _: &HttpRequest) -> actix_web::Error {
+    AppError::validation("invalid_path", &err.to_string()).into()
+}
+
+pub fn query_error_handler(err: QueryPayloadError, _: &HttpRequest) -> actix_web::Error {
+    AppError::validation("invalid_query", &err.to_string()).into()
+}
+
+pub async fn route_not_found() -> Result<HttpResponse, AppError> {
+    Err(AppError::not_found("route_not_found", "Route not found."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_codes_are_stable() {
+        assert_eq!(
+            AppError::not_found("code", "").status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            AppError::conflict("code", "").status_code(),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            AppError::unauthorized("code", "").status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            AppError::forbidden("code", "").status_code(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            AppError::validation("code", "").status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            AppError::too_many_requests("code", "", 1).status_code(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            AppError::from(sqlx::Error::PoolTimedOut).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn problem_details_body() {
+        let problem = AppError::not_found("item_not_found", "Item not found.").problem();
+
+        assert_eq!(problem.problem_type, "/problems/item_not_found");
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.status, 404);
+        assert_eq!(problem.detail, "Item not found.");
+        assert_eq!(problem.code, "item_not_found");
+        assert!(problem.errors.is_empty());
+    }
+
+    #[test]
+    fn problem_details_list_violations() {
+        let violation = Violation::new("password_too_short", "Too short.");
+        let problem =
+            AppError::violations("weak_password", "Weak.", vec![violation.clone()]).problem();
+
+        assert_eq!(problem.status, 400);
+        assert_eq!(problem.errors, vec![violation]);
+    }
+
+    #[test]
+    fn problem_details_hide_internal_errors() {
+        let problem = AppError::from(sqlx::Error::PoolTimedOut).problem();
+
+        assert_eq!(problem.status, 500);
+        assert_eq!(problem.detail, "Internal server error.");
+        assert_eq!(problem.code, "internal_error");
+    }
+
+    #[actix_web::test]
+    async fn problem_content_type() {
+        let res = AppError::forbidden("item_forbidden", "").error_response();
+
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            PROBLEM_CONTENT_TYPE
+        );
+    }
+
+    #[actix_web::test]
+    async fn too_many_requests_sets_retry_after() {
+        let res = AppError::too_many_requests("login_locked", "", 30).error_response();
+
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "30");
+    }
+}
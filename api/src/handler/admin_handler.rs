@@ -0,0 +1,38 @@
+use actix_web::{
+    HttpResponse, get, put,
+    web::{self, Json, Path, Query},
+};
+
+use crate::{
+    dto::admin_dto::{PageQuery, RolesDto},
+    error::AppError,
+    handler::guard::{ManageUsers, ReadUsers, RequirePermission},
+    state::AppState,
+};
+
+#[get("/admin/users")]
+pub async fn list_users(
+    state: web::Data<AppState>,
+    page: Query<PageQuery>,
+    _: RequirePermission<ReadUsers>,
+) -> Result<HttpResponse, AppError> {
+    let users = state
+        .admin_service
+        .list_users(page.limit, page.offset)
+        .await?;
+    Ok(HttpResponse::Ok().json(users))
+}
+
+#[put("/admin/users/{id}/roles")]
+pub async fn set_roles(
+    state: web::Data<AppState>,
+    id: Path<i32>,
+    body: Json<RolesDto>,
+    caller: RequirePermission<ManageUsers>,
+) -> Result<HttpResponse, AppError> {
+    let user = state
+        .admin_service
+        .set_roles(caller.user.id, *id, &body.roles)
+        .await?;
+    Ok(HttpResponse::Ok().json(user))
+}
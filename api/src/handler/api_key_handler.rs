@@ -0,0 +1,40 @@
+use actix_web::{
+    HttpResponse, delete, get, post,
+    web::{self, Json, Path},
+};
+
+use crate::{
+    dto::api_key_dto::ApiKeyDto,
+    error::AppError,
+    handler::guard::{RequireSession, RequireVerifiedEmail},
+    state::AppState,
+};
+
+#[post("/user/api-keys")]
+pub async fn create(
+    state: web::Data<AppState>,
+    caller: RequireVerifiedEmail,
+    body: Json<ApiKeyDto>,
+) -> Result<HttpResponse, AppError> {
+    let created = state.api_key_service.create(caller.user.id, &body).await?;
+    Ok(HttpResponse::Created().json(created))
+}
+
+#[get("/user/api-keys")]
+pub async fn list(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+) -> Result<HttpResponse, AppError> {
+    let keys = state.api_key_service.list(caller.user.id).await?;
+    Ok(HttpResponse::Ok().json(keys))
+}
+
+#[delete("/user/api-keys/{id}")]
+pub async fn revoke(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+    id: Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    state.api_key_service.revoke(caller.user.id, *id).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
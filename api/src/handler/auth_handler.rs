@@ -1,51 +1,172 @@
 use actix_web::{
-    HttpRequest, HttpResponse, Responder, get, post,
-    web::{self, Json},
+    HttpRequest, HttpResponse, delete, get,
+    http::header::USER_AGENT,
+    post,
+    web::{self, Json, Path},
 };
-use sqlx::{Pool, Postgres};
 
 use crate::{
-    contract::service::auth_service_trait::IAuthService,
-    dto::user_dto::UserDto,
+    dto::{
+        auth_dto::RefreshDto,
+        mfa_dto::MfaChallengeDto,
+        password_dto::{ForgotPasswordDto, PasswordResetDto},
+        user_dto::UserDto,
+    },
     entity::{
-        auth_entity::{AuthMe, AuthMsg, UserAuth},
+        auth_entity::{AuthMe, ClientInfo, UserAuth},
         user_entity::UserFetched,
     },
-    repo::user_repo::UserRepo,
-    service::auth_service::AuthService,
+    error::AppError,
+    handler::{auth_user::AuthUser, guard::RequireSession},
+    state::AppState,
 };
 
-fn new_auth_service<'a>(pool: &'a web::Data<Pool<Postgres>>) -> AuthService<UserRepo<'a>> {
-    let user_repo = UserRepo::new(pool);
-    AuthService::new(user_repo)
+/// Longest user agent kept on a session.
+const USER_AGENT_MAX_CHARS: usize = 256;
+
+pub(crate) fn client_info(req: &HttpRequest) -> ClientInfo {
+    ClientInfo {
+        ip: req.peer_addr().map(|addr| addr.ip().to_string()),
+        user_agent: req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|agent| agent.chars().take(USER_AGENT_MAX_CHARS).collect()),
+    }
 }
 
 #[post("/auth")]
-pub async fn auth(pool: web::Data<Pool<Postgres>>, body: Json<UserDto>) -> impl Responder {
-    let auth_service = new_auth_service(&pool);
-
+pub async fn auth(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: Json<UserDto>,
+) -> Result<HttpResponse, AppError> {
     let user_auth = UserAuth {
         name: body.name.clone(),
         password: body.password.clone(),
     };
 
-    match auth_service.auth(&user_auth).await {
-        Ok(resp) => HttpResponse::Ok().json(resp),
-        Err(err) => HttpResponse::Unauthorized().json(AuthMsg { msg: err }),
-    }
+    let resp = state
+        .auth_service
+        .auth(&user_auth, &client_info(&req))
+        .await?;
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+#[post("/auth/mfa")]
+pub async fn mfa(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: Json<MfaChallengeDto>,
+) -> Result<HttpResponse, AppError> {
+    let resp = state
+        .auth_service
+        .verify_mfa(&body.challenge_token, &body.code, &client_info(&req))
+        .await?;
+    Ok(HttpResponse::Ok().json(resp))
 }
 
-fn user_fetched_to_auth_me(fetched: &UserFetched) -> AuthMe {
+#[post("/auth/forgot")]
+pub async fn forgot(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: Json<ForgotPasswordDto>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .password_service
+        .forgot(&body.name, &client_info(&req))
+        .await?;
+
+    // Answered before the lookup, so known and unknown names take as long.
+    let service = state.password_service.clone();
+    let name = body.into_inner().name;
+    actix_web::rt::spawn(async move {
+        // Swallowed on purpose: the caller already has their answer, and
+        // reporting a failure would tell them the name exists.
+        let _ = service.send_reset(&name).await;
+    });
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[post("/auth/reset")]
+pub async fn reset(
+    state: web::Data<AppState>,
+    body: Json<PasswordResetDto>,
+) -> Result<HttpResponse, AppError> {
+    state.password_service.reset(&body).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/auth/refresh")]
+pub async fn refresh(
+    state: web::Data<AppState>,
+    body: Json<RefreshDto>,
+) -> Result<HttpResponse, AppError> {
+    let resp = state.auth_service.refresh(&body.refresh).await?;
+    Ok(HttpResponse::Ok().json(resp))
+}
+
+fn user_fetched_to_auth_me(fetched: &UserFetched, roles: &[String]) -> AuthMe {
     AuthMe {
         id: fetched.id,
         name: fetched.name.clone(),
+        roles: roles.to_vec(),
     }
 }
 
 #[get("/auth/me")]
-pub async fn me(pool: web::Data<Pool<Postgres>>, req: HttpRequest) -> impl Responder {
-    match new_auth_service(&pool).user(req).await {
-        Ok(fetched) => HttpResponse::Ok().json(user_fetched_to_auth_me(&fetched)),
-        Err(_) => HttpResponse::Unauthorized().finish(),
-    }
+pub async fn me(caller: AuthUser) -> Result<HttpResponse, AppError> {
+    Ok(HttpResponse::Ok().json(user_fetched_to_auth_me(&caller.user, &caller.roles)))
+}
+
+#[post("/auth/logout")]
+pub async fn logout(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+    body: Option<Json<RefreshDto>>,
+) -> Result<HttpResponse, AppError> {
+    let refresh_token = body.as_ref().map(|b| b.refresh.as_str());
+
+    state
+        .auth_service
+        .logout(&caller.token, refresh_token)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[post("/auth/logout-all")]
+pub async fn logout_all(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+) -> Result<HttpResponse, AppError> {
+    state.auth_service.logout_all(&caller.token).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/auth/sessions")]
+pub async fn sessions(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+) -> Result<HttpResponse, AppError> {
+    let sessions = state.auth_service.sessions(&caller.token).await?;
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+#[delete("/auth/sessions/{id}")]
+pub async fn revoke_session(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+    id: Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .auth_service
+        .revoke_session(&caller.token, *id)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/.well-known/jwks.json")]
+pub async fn jwks(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(state.auth_service.jwks())
 }
@@ -0,0 +1,149 @@
+use std::{future::Future, pin::Pin};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, http::header::AUTHORIZATION, web};
+
+use crate::{entity::user_entity::UserFetched, error::AppError, state::AppState};
+
+pub const API_KEY_HEADER: &str = "X-Api-Key";
+
+/// The caller behind a valid bearer token or API key.
+pub struct AuthUser {
+    pub user: UserFetched,
+    /// The bearer token or API key presented.
+    pub token: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    /// `None` unless the caller used an API key.
+    pub scopes: Option<Vec<String>>,
+    /// `None` for API keys and for tokens issued before sessions existed.
+    pub session_id: Option<i32>,
+}
+
+impl AuthUser {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    /// Access tokens have every scope.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.iter().any(|s| s == scope))
+    }
+}
+
+fn authorization(req: &HttpRequest, scheme: &str) -> Option<String> {
+    req.headers()
+        .get(AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix(scheme))
+        .map(String::from)
+}
+
+fn bearer_token(req: &HttpRequest) -> Option<String> {
+    authorization(req, "Bearer ")
+}
+
+fn api_key(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(API_KEY_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(String::from)
+        .or_else(|| authorization(req, "ApiKey "))
+}
+
+enum Credential {
+    Bearer(String),
+    ApiKey(String),
+}
+
+fn credential(req: &HttpRequest) -> Option<Credential> {
+    bearer_token(req)
+        .map(Credential::Bearer)
+        .or_else(|| api_key(req).map(Credential::ApiKey))
+}
+
+impl FromRequest for AuthUser {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let credential = credential(req);
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+
+        Box::pin(async move {
+            let credential = credential.ok_or_else(|| {
+                AppError::unauthorized("invalid_token", "Missing or invalid bearer token.")
+            })?;
+            let state = state
+                .ok_or_else(|| AppError::Internal(String::from("App data not configured.")))?;
+
+            let (caller, token) = match credential {
+                Credential::Bearer(token) => (state.auth_service.user(&token).await?, token),
+                Credential::ApiKey(key) => (state.auth_service.api_key_user(&key).await?, key),
+            };
+
+            Ok(AuthUser {
+                user: caller.user,
+                token,
+                roles: caller.roles,
+                permissions: caller.permissions,
+                scopes: caller.scopes,
+                session_id: caller.session_id,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::header, test::TestRequest};
+
+    #[test]
+    fn bearer_token_from_header() {
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer abc"))
+            .to_http_request();
+
+        assert_eq!(bearer_token(&req).as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn bearer_token_missing_or_malformed() {
+        let req = TestRequest::default().to_http_request();
+        assert!(bearer_token(&req).is_none());
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "abc"))
+            .to_http_request();
+        assert!(bearer_token(&req).is_none());
+    }
+
+    #[test]
+    fn api_key_from_either_header() {
+        let req = TestRequest::default()
+            .insert_header((API_KEY_HEADER, "ak_1"))
+            .to_http_request();
+        assert_eq!(api_key(&req).as_deref(), Some("ak_1"));
+
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "ApiKey ak_2"))
+            .to_http_request();
+        assert_eq!(api_key(&req).as_deref(), Some("ak_2"));
+        assert!(bearer_token(&req).is_none());
+    }
+
+    #[actix_web::test]
+    async fn missing_token_is_unauthorized() {
+        let (req, mut payload) = TestRequest::default().to_http_parts();
+
+        let res = AuthUser::from_request(&req, &mut payload).await;
+
+        assert!(matches!(res, Err(AppError::Unauthorized { .. })));
+    }
+}
@@ -0,0 +1,272 @@
+use std::{future::Future, marker::PhantomData, ops::Deref, pin::Pin};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+
+use crate::{
+    entity::{
+        api_key_entity::{SCOPE_ITEMS_READ, SCOPE_ITEMS_WRITE},
+        role_entity::{PERMISSION_MANAGE_USERS, PERMISSION_READ_USERS, ROLE_ADMIN},
+    },
+    error::AppError,
+    handler::auth_user::AuthUser,
+    state::AppState,
+};
+
+pub trait Role: 'static {
+    const NAME: &'static str;
+}
+
+pub trait Permission: 'static {
+    const NAME: &'static str;
+}
+
+pub trait Scope: 'static {
+    const NAME: &'static str;
+}
+
+pub struct Admin;
+
+impl Role for Admin {
+    const NAME: &'static str = ROLE_ADMIN;
+}
+
+pub struct ReadUsers;
+
+impl Permission for ReadUsers {
+    const NAME: &'static str = PERMISSION_READ_USERS;
+}
+
+pub struct ManageUsers;
+
+impl Permission for ManageUsers {
+    const NAME: &'static str = PERMISSION_MANAGE_USERS;
+}
+
+pub struct ReadItems;
+
+impl Scope for ReadItems {
+    const NAME: &'static str = SCOPE_ITEMS_READ;
+}
+
+pub struct WriteItems;
+
+impl Scope for WriteItems {
+    const NAME: &'static str = SCOPE_ITEMS_WRITE;
+}
+
+/// An [`AuthUser`] holding role `R`; anyone else gets a 403.
+///
+/// ```ignore
+/// async fn handler(caller: RequireRole<Admin>) -> ...
+/// ```
+pub struct RequireRole<R: Role> {
+    pub caller: AuthUser,
+    role: PhantomData<R>,
+}
+
+/// An [`AuthUser`] granted permission `P` by one of their roles.
+pub struct RequirePermission<P: Permission> {
+    pub caller: AuthUser,
+    permission: PhantomData<P>,
+}
+
+/// An [`AuthUser`] whose API key, if they used one, has scope `S`.
+pub struct RequireScope<S: Scope> {
+    pub caller: AuthUser,
+    scope: PhantomData<S>,
+}
+
+/// An [`AuthUser`] who signed in rather than using an API key, for actions a
+/// leaked key must not be able to take.
+pub struct RequireSession {
+    pub caller: AuthUser,
+}
+
+/// A [`RequireSession`] caller whose email address has been verified.
+pub struct RequireVerifiedEmail {
+    pub caller: AuthUser,
+}
+
+impl<R: Role> Deref for RequireRole<R> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.caller
+    }
+}
+
+impl<P: Permission> Deref for RequirePermission<P> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.caller
+    }
+}
+
+impl<S: Scope> Deref for RequireScope<S> {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.caller
+    }
+}
+
+impl Deref for RequireSession {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.caller
+    }
+}
+
+impl Deref for RequireVerifiedEmail {
+    type Target = AuthUser;
+
+    fn deref(&self) -> &AuthUser {
+        &self.caller
+    }
+}
+
+impl<R: Role> FromRequest for RequireRole<R> {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let caller = AuthUser::from_request(req, payload);
+
+        Box::pin(async move {
+            let caller = caller.await?;
+
+            if !caller.has_role(R::NAME) {
+                return Err(AppError::forbidden(
+                    "missing_role",
+                    &format!("This requires the `{}` role.", R::NAME),
+                ));
+            }
+
+            Ok(RequireRole {
+                caller,
+                role: PhantomData,
+            })
+        })
+    }
+}
+
+impl<P: Permission> FromRequest for RequirePermission<P> {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let caller = AuthUser::from_request(req, payload);
+
+        Box::pin(async move {
+            let caller = caller.await?;
+
+            if !caller.has_permission(P::NAME) {
+                return Err(AppError::forbidden(
+                    "missing_permission",
+                    &format!("This requires the `{}` permission.", P::NAME),
+                ));
+            }
+
+            Ok(RequirePermission {
+                caller,
+                permission: PhantomData,
+            })
+        })
+    }
+}
+
+impl<S: Scope> FromRequest for RequireScope<S> {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let caller = AuthUser::from_request(req, payload);
+
+        Box::pin(async move {
+            let caller = caller.await?;
+
+            if !caller.has_scope(S::NAME) {
+                return Err(AppError::forbidden(
+                    "missing_scope",
+                    &format!("This API key lacks the `{}` scope.", S::NAME),
+                ));
+            }
+
+            Ok(RequireScope {
+                caller,
+                scope: PhantomData,
+            })
+        })
+    }
+}
+
+impl FromRequest for RequireSession {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let caller = AuthUser::from_request(req, payload);
+
+        Box::pin(async move {
+            let caller = caller.await?;
+
+            if caller.scopes.is_some() {
+                return Err(AppError::forbidden(
+                    "session_required",
+                    "API keys cannot be used for this.",
+                ));
+            }
+
+            Ok(RequireSession { caller })
+        })
+    }
+}
+
+impl FromRequest for RequireVerifiedEmail {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let session = RequireSession::from_request(req, payload);
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+
+        Box::pin(async move {
+            let caller = session.await?.caller;
+            let state = state
+                .ok_or_else(|| AppError::Internal(String::from("App data not configured.")))?;
+
+            if !state
+                .email_service
+                .email(caller.user.id)
+                .await?
+                .is_verified()
+            {
+                return Err(AppError::forbidden(
+                    "email_unverified",
+                    "Verify your email address first.",
+                ));
+            }
+
+            Ok(RequireVerifiedEmail { caller })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[actix_web::test]
+    async fn missing_token_is_unauthorized_not_forbidden() {
+        let (req, mut payload) = TestRequest::default().to_http_parts();
+
+        let res = RequireRole::<Admin>::from_request(&req, &mut payload).await;
+        assert!(matches!(res, Err(AppError::Unauthorized { .. })));
+
+        let res = RequirePermission::<ManageUsers>::from_request(&req, &mut payload).await;
+        assert!(matches!(res, Err(AppError::Unauthorized { .. })));
+    }
+}
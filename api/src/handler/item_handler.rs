@@ -1,27 +1,97 @@
 use actix_web::{
-    HttpRequest, HttpResponse, Responder, post,
-    web::{self, Json},
+    HttpResponse, delete, get, patch, post, put,
+    web::{self, Json, Path},
 };
-use sqlx::{Pool, Postgres};
 
-use crate::dto::item_dto::ItemDto;
+use crate::{
+    dto::item_dto::{ItemDto, ItemPatchDto},
+    entity::item_entity::{ItemCreate, ItemUpdate},
+    error::AppError,
+    handler::guard::{ReadItems, RequireScope, WriteItems},
+    state::AppState,
+};
 
-#[post("/auth")]
+#[post("/items")]
 pub async fn create(
-    pool: web::Data<Pool<Postgres>>,
+    state: web::Data<AppState>,
     body: Json<ItemDto>,
-    req: HttpRequest,
-) -> impl Responder {
-    HttpResponse::Unauthorized().finish()
-    // let auth_service = new_auth_service(&pool);
-    //
-    // let user_auth = UserAuth {
-    //     name: body.name.clone(),
-    //     password: body.password.clone(),
-    // };
-    //
-    // match auth_service.auth(&user_auth).await {
-    //     Ok(resp) => HttpResponse::Ok().json(resp),
-    //     Err(err) => HttpResponse::Unauthorized().json(AuthMsg { msg: err }),
-    // }
+    caller: RequireScope<WriteItems>,
+) -> Result<HttpResponse, AppError> {
+    let item = ItemCreate {
+        name: body.name.clone(),
+        price: body.price,
+        user_id: caller.user.id,
+    };
+
+    let item = state.item_service.create(&item).await?;
+    Ok(HttpResponse::Created().json(item))
+}
+
+#[get("/items/{id}")]
+pub async fn fetch(
+    state: web::Data<AppState>,
+    id: Path<i32>,
+    _: RequireScope<ReadItems>,
+) -> Result<HttpResponse, AppError> {
+    let item = state.item_service.fetch(*id).await?;
+    Ok(HttpResponse::Ok().json(item))
+}
+
+#[get("/items")]
+pub async fn list(
+    state: web::Data<AppState>,
+    caller: RequireScope<ReadItems>,
+) -> Result<HttpResponse, AppError> {
+    let items = state.item_service.list(caller.user.id).await?;
+    Ok(HttpResponse::Ok().json(items))
+}
+
+async fn update_item(
+    state: web::Data<AppState>,
+    id: i32,
+    item: ItemUpdate,
+    user_id: i32,
+) -> Result<HttpResponse, AppError> {
+    let item = state.item_service.update(user_id, id, &item).await?;
+    Ok(HttpResponse::Ok().json(item))
+}
+
+#[put("/items/{id}")]
+pub async fn replace(
+    state: web::Data<AppState>,
+    id: Path<i32>,
+    body: Json<ItemDto>,
+    caller: RequireScope<WriteItems>,
+) -> Result<HttpResponse, AppError> {
+    let item = ItemUpdate {
+        name: Some(body.name.clone()),
+        price: Some(body.price),
+    };
+
+    update_item(state, *id, item, caller.user.id).await
+}
+
+#[patch("/items/{id}")]
+pub async fn update(
+    state: web::Data<AppState>,
+    id: Path<i32>,
+    body: Json<ItemPatchDto>,
+    caller: RequireScope<WriteItems>,
+) -> Result<HttpResponse, AppError> {
+    let item = ItemUpdate {
+        name: body.name.clone(),
+        price: body.price,
+    };
+
+    update_item(state, *id, item, caller.user.id).await
+}
+
+#[delete("/items/{id}")]
+pub async fn remove(
+    state: web::Data<AppState>,
+    id: Path<i32>,
+    caller: RequireScope<WriteItems>,
+) -> Result<HttpResponse, AppError> {
+    state.item_service.delete(caller.user.id, *id).await?;
+    Ok(HttpResponse::NoContent().finish())
 }
@@ -0,0 +1,31 @@
+use actix_web::{
+    HttpResponse, post,
+    web::{self, Json},
+};
+
+use crate::{
+    dto::mfa_dto::MfaCodeDto, error::AppError, handler::guard::RequireSession, state::AppState,
+};
+
+/// Starts (or restarts) enrollment; 2FA stays off until `confirm`.
+#[post("/user/mfa")]
+pub async fn enroll(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+) -> Result<HttpResponse, AppError> {
+    let enrollment = state.mfa_service.enroll(&caller.user).await?;
+    Ok(HttpResponse::Created().json(enrollment))
+}
+
+#[post("/user/mfa/confirm")]
+pub async fn confirm(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+    body: Json<MfaCodeDto>,
+) -> Result<HttpResponse, AppError> {
+    let recovery = state
+        .mfa_service
+        .confirm(caller.user.id, &body.code)
+        .await?;
+    Ok(HttpResponse::Ok().json(recovery))
+}
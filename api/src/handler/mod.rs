@@ -1,3 +1,8 @@
+pub mod admin_handler;
+pub mod api_key_handler;
 pub mod auth_handler;
+pub mod auth_user;
+pub mod guard;
 pub mod item_handler;
+pub mod mfa_handler;
 pub mod user_handler;
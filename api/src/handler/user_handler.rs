@@ -1,30 +1,116 @@
-use actix_web::{HttpResponse, Responder, post, web};
-use sqlx::{Pool, Postgres};
+use actix_web::{
+    HttpRequest, HttpResponse, delete, get, patch, post, put,
+    web::{self, Path},
+};
 
 use crate::{
-    contract::service::user_service_trait::IUserService,
-    dto::user_dto::{UserDto, UserRespose},
+    dto::{
+        email_dto::{EmailDto, EmailTokenQuery},
+        password_dto::PasswordChangeDto,
+        user_dto::{UserDto, UserPatchDto, UserRespose},
+    },
     entity::user_entity::UserRegister,
-    repo::user_repo::UserRepo,
-    service::user_service::UserService,
+    error::AppError,
+    handler::{auth_handler::client_info, guard::RequireSession},
+    state::AppState,
 };
 
 #[post("/user")]
-pub async fn create(pool: web::Data<Pool<Postgres>>, body: web::Json<UserDto>) -> impl Responder {
-    let repo = UserRepo::new(&pool);
-    let service = UserService::new(repo);
-
+pub async fn create(
+    state: web::Data<AppState>,
+    body: web::Json<UserDto>,
+) -> Result<HttpResponse, AppError> {
     let data_in = UserRegister {
         password: body.password.clone(),
         name: body.name.clone(),
     };
 
-    let res = service.register(&data_in).await;
+    state.user_service.register(&data_in).await?;
+
+    Ok(HttpResponse::Created().json(UserRespose {
+        msg: String::from("user created"),
+    }))
+}
+
+#[put("/user/password")]
+pub async fn change_password(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    caller: RequireSession,
+    body: web::Json<PasswordChangeDto>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .password_service
+        .change(caller.user.id, caller.session_id, &body, &client_info(&req))
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/user/email")]
+pub async fn email(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+) -> Result<HttpResponse, AppError> {
+    let email = state.email_service.email(caller.user.id).await?;
+    Ok(HttpResponse::Ok().json(email))
+}
+
+#[put("/user/email")]
+pub async fn set_email(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+    body: web::Json<EmailDto>,
+) -> Result<HttpResponse, AppError> {
+    state
+        .email_service
+        .set_email(caller.user.id, &body.email)
+        .await?;
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[post("/user/email/verification")]
+pub async fn resend_verification(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+) -> Result<HttpResponse, AppError> {
+    state.email_service.resend(caller.user.id).await?;
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// The target of the link in verification emails.
+#[get("/user/email/verify")]
+pub async fn verify_email(
+    state: web::Data<AppState>,
+    query: web::Query<EmailTokenQuery>,
+) -> Result<HttpResponse, AppError> {
+    state.email_service.verify(&query.token).await?;
+
+    Ok(HttpResponse::Ok().json(UserRespose {
+        msg: String::from("email verified"),
+    }))
+}
+
+#[get(r"/user/{id:\d+}")]
+pub async fn fetch(state: web::Data<AppState>, id: Path<i32>) -> Result<HttpResponse, AppError> {
+    let profile = state.user_service.profile(*id).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+#[patch("/user/me")]
+pub async fn update_me(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+    body: web::Json<UserPatchDto>,
+) -> Result<HttpResponse, AppError> {
+    let profile = state.user_service.update(caller.user.id, &body).await?;
+    Ok(HttpResponse::Ok().json(profile))
+}
 
-    match res {
-        Ok(_) => HttpResponse::Created().json(UserRespose {
-            msg: String::from("user created"),
-        }),
-        Err(msg) => HttpResponse::BadRequest().json(UserRespose { msg }),
-    }
+#[delete("/user/me")]
+pub async fn delete_me(
+    state: web::Data<AppState>,
+    caller: RequireSession,
+) -> Result<HttpResponse, AppError> {
+    state.user_service.delete(caller.user.id).await?;
+    Ok(HttpResponse::NoContent().finish())
 }
@@ -1,6 +1,10 @@
+pub mod config;
 pub mod contract;
 pub mod dto;
 pub mod entity;
+pub mod error;
 pub mod handler;
 pub mod repo;
+pub mod routes;
 pub mod service;
+pub mod state;
@@ -1,45 +1,81 @@
 use std::env;
 
-use actix_web::{App, HttpResponse, HttpServer, Responder, get, web};
-use api::handler::{auth_handler, user_handler};
-use dotenvy::dotenv;
-use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
-
-#[get("/")]
-async fn hello() -> impl Responder {
-    HttpResponse::Ok().body("Hello world!")
-}
+use actix_web::{App, HttpServer, web};
+use api::{
+    config::Config,
+    contract::repo::{revocation_repo_trait::IRevocationRepo, user_repo_trait::IUserRepo},
+    entity::role_entity::ROLE_ADMIN,
+    repo::storage::Storage,
+    routes,
+    state::AppState,
+};
+use migration::runner::{self, Migrator};
 
-async fn manual_hello() -> impl Responder {
-    HttpResponse::Ok().body("Hey there!")
-}
+async fn migrate(db_str: &str) {
+    let mut conn = runner::connect(db_str).await.unwrap();
+
+    let applied = Migrator::for_connection(&conn)
+        .unwrap()
+        .up_locked(&mut conn)
+        .await
+        .expect("failed to apply migrations");
 
-async fn load_pool() -> Pool<Postgres> {
-    let db_str = env::var("db_str").unwrap();
+    for migration in applied {
+        println!("applied {}", migration.name);
+    }
+}
 
-    PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&db_str)
+/// Gives an existing user the admin role, for bootstrapping the first admin.
+async fn grant_admin(storage: &Storage, name: &str) {
+    let users = storage.user_repo();
+    let user = users
+        .fetch_by_name(name)
         .await
-        .unwrap()
+        .unwrap_or_else(|err| panic!("cannot grant admin to `{name}`: {err}"));
+
+    let mut roles = users.access(user.id).await.unwrap().roles;
+    if !roles.iter().any(|role| role == ROLE_ADMIN) {
+        roles.push(String::from(ROLE_ADMIN));
+        users.set_roles(user.id, &roles).await.unwrap();
+        storage
+            .revocation_repo()
+            .bump_token_version(user.id)
+            .await
+            .unwrap();
+    }
+
+    println!("`{name}` is an admin");
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    dotenv().ok();
+    let mut config = Config::load().unwrap_or_else(|err| panic!("invalid configuration: {err}"));
+    config.auto_migrate |= env::args().any(|arg| arg == "--migrate");
+
+    let storage = Storage::connect(&config).await.unwrap();
+
+    if config.auto_migrate && !matches!(storage, Storage::Memory(_)) {
+        migrate(&config.db_str).await;
+    }
+
+    if let Some(name) =
+        env::args().find_map(|arg| arg.strip_prefix("--grant-admin=").map(String::from))
+    {
+        grant_admin(&storage, &name).await;
+        return Ok(());
+    }
 
-    let pool = load_pool().await;
+    let bind = (config.host.clone(), config.port);
+    let state = AppState::new(&storage, &config)
+        .unwrap_or_else(|err| panic!("invalid configuration: {err}"));
+    let state = web::Data::new(state);
 
     HttpServer::new(move || {
         App::new()
-            .app_data(web::Data::new(pool.clone()))
-            .service(hello)
-            .service(user_handler::create)
-            .service(auth_handler::auth)
-            .service(auth_handler::me)
-            .route("/hey", web::get().to(manual_hello))
+            .app_data(state.clone())
+            .configure(routes::configure)
     })
-    .bind(("127.0.0.1", 8080))?
+    .bind(bind)?
     .run()
     .await
 }
@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    contract::repo::api_key_repo_trait::IApiKeyRepo,
+    entity::api_key_entity::{ApiKeyCreate, ApiKeyFetched},
+    error::AppError,
+};
+
+pub struct ApiKeyRepo {
+    pool: Pool<Postgres>,
+}
+
+impl ApiKeyRepo {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IApiKeyRepo for ApiKeyRepo {
+    async fn create(&self, key: &ApiKeyCreate) -> Result<ApiKeyFetched, AppError> {
+        Ok(sqlx::query_as::<_, ApiKeyFetched>(
+            r#"
+            INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, user_id, name, prefix, key_hash, scopes, created_at, expires_at, last_used_at
+        "#,
+        )
+        .bind(key.user_id)
+        .bind(&key.name)
+        .bind(&key.prefix)
+        .bind(&key.key_hash)
+        .bind(key.scopes.join(" "))
+        .bind(key.created_at)
+        .bind(key.expires_at)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn list(&self, user_id: i32) -> Result<Vec<ApiKeyFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ApiKeyFetched>(
+            r#"
+            SELECT id, user_id, name, prefix, key_hash, scopes, created_at, expires_at, last_used_at
+            FROM api_keys
+            WHERE user_id = $1
+            ORDER BY id
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn fetch_by_hash(&self, key_hash: &str) -> Result<Option<ApiKeyFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ApiKeyFetched>(
+            r#"
+            SELECT id, user_id, name, prefix, key_hash, scopes, created_at, expires_at, last_used_at
+            FROM api_keys
+            WHERE key_hash = $1
+        "#,
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn touch(&self, id: i32, used_at: i64) -> Result<(), AppError> {
+        sqlx::query("UPDATE api_keys SET last_used_at = $2 WHERE id = $1")
+            .bind(id)
+            .bind(used_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> Result<bool, AppError> {
+        let res = sqlx::query("DELETE FROM api_keys WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config, contract::repo::user_repo_trait::IUserRepo,
+        entity::user_entity::UserRegister, repo::user_repo::UserRepo,
+    };
+    use sqlx::postgres::PgPoolOptions;
+
+    async fn load_pool() -> Pool<Postgres> {
+        let config = Config::load().unwrap();
+
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn api_key_repo_lifecycle() {
+        let pool = load_pool().await;
+
+        let user_repo = UserRepo::new(pool.clone());
+        user_repo
+            .register(&UserRegister {
+                name: String::from("api_key_user"),
+                password: String::from("api_key_user_password"),
+            })
+            .await
+            .unwrap();
+        let user = user_repo.fetch_by_name("api_key_user").await.unwrap();
+
+        let repo = ApiKeyRepo::new(pool.clone());
+        let created = repo
+            .create(&ApiKeyCreate {
+                user_id: user.id,
+                name: String::from("ci"),
+                prefix: String::from("ak_1234"),
+                key_hash: String::from("api_key_user_hash"),
+                scopes: vec![String::from("items:read"), String::from("items:write")],
+                created_at: 100,
+                expires_at: Some(200),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(created.scope_list(), ["items:read", "items:write"]);
+        assert_eq!(created.last_used_at, None);
+
+        repo.touch(created.id, 150).await.unwrap();
+        let fetched = repo
+            .fetch_by_hash("api_key_user_hash")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fetched.last_used_at, Some(150));
+        assert_eq!(repo.list(user.id).await.unwrap(), [fetched]);
+
+        assert!(!repo.delete(user.id + 1, created.id).await.unwrap());
+        assert!(repo.delete(user.id, created.id).await.unwrap());
+        assert!(
+            repo.fetch_by_hash("api_key_user_hash")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use sqlx::{Database, Pool, Transaction, pool::PoolConnection};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::error::AppError;
+
+pub type SharedTransaction<DB> = Arc<Mutex<Transaction<'static, DB>>>;
+
+/// Where a SQL repo sends its queries: straight to the pool, or into a
+/// transaction it shares with the other repos of a unit of work.
+pub enum Db<DB: Database> {
+    Pool(Pool<DB>),
+    Tx(SharedTransaction<DB>),
+}
+
+impl<DB: Database> Db<DB> {
+    pub async fn conn(&self) -> Result<DbConn<DB>, sqlx::Error> {
+        Ok(match self {
+            Db::Pool(pool) => DbConn::Pooled(pool.acquire().await?),
+            Db::Tx(tx) => DbConn::Tx(tx.clone().lock_owned().await),
+        })
+    }
+}
+
+impl<DB: Database> From<Pool<DB>> for Db<DB> {
+    fn from(pool: Pool<DB>) -> Self {
+        Db::Pool(pool)
+    }
+}
+
+impl<DB: Database> From<SharedTransaction<DB>> for Db<DB> {
+    fn from(tx: SharedTransaction<DB>) -> Self {
+        Db::Tx(tx)
+    }
+}
+
+/// A connection borrowed for one query; `&mut *conn` is an executor.
+pub enum DbConn<DB: Database> {
+    Pooled(PoolConnection<DB>),
+    Tx(OwnedMutexGuard<Transaction<'static, DB>>),
+}
+
+impl<DB: Database> Deref for DbConn<DB> {
+    type Target = DB::Connection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            DbConn::Pooled(conn) => conn,
+            DbConn::Tx(tx) => tx,
+        }
+    }
+}
+
+impl<DB: Database> DerefMut for DbConn<DB> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            DbConn::Pooled(conn) => conn,
+            DbConn::Tx(tx) => tx,
+        }
+    }
+}
+
+/// Takes the transaction back once every repo sharing it has been dropped.
+pub fn into_transaction<DB: Database>(
+    tx: SharedTransaction<DB>,
+) -> Result<Transaction<'static, DB>, AppError> {
+    Arc::try_unwrap(tx)
+        .map(Mutex::into_inner)
+        .map_err(|_| AppError::Internal(String::from("transaction is still in use")))
+}
@@ -1,50 +1,111 @@
 use async_trait::async_trait;
-use sqlx::{Pool, Postgres};
+use sqlx::Postgres;
 
 use crate::{
-    contract::repo::{item_repo_trait::IItemRepo, user_repo_trait::IUserRepo},
-    entity::item_entity::ItemCreate,
+    contract::repo::item_repo_trait::IItemRepo,
+    entity::item_entity::{ItemCreate, ItemFetched, ItemUpdate},
+    error::AppError,
+    repo::db::Db,
 };
 
-pub struct ItemRepo<'a> {
-    pool: &'a Pool<Postgres>,
+pub struct ItemRepo {
+    db: Db<Postgres>,
 }
 
-impl<'a> ItemRepo<'a> {
-    pub fn new(pool: &'a Pool<Postgres>) -> Self {
-        Self { pool }
+impl ItemRepo {
+    pub fn new(db: impl Into<Db<Postgres>>) -> Self {
+        Self { db: db.into() }
     }
 }
 
 #[async_trait]
-impl IItemRepo for ItemRepo<'_> {
-    async fn register(&self, item: &ItemCreate) {
-        sqlx::query("INSERT INTO items (name, price, user_id) VALUES ($1, $2, $3)")
-            .bind(&item.name)
-            .bind(item.price)
-            .bind(item.user_id)
-            .execute(self.pool)
-            .await
-            .unwrap();
+impl IItemRepo for ItemRepo {
+    async fn register(&self, item: &ItemCreate) -> Result<ItemFetched, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            INSERT INTO items (name, price, user_id)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, name, price
+        "#,
+        )
+        .bind(&item.name)
+        .bind(item.price)
+        .bind(item.user_id)
+        .fetch_one(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn fetch_by_id(&self, id: i32) -> Result<Option<ItemFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            SELECT id, user_id, name, price
+            FROM items
+            WHERE id = $1
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn fetch_by_user(&self, user_id: i32) -> Result<Vec<ItemFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            SELECT id, user_id, name, price
+            FROM items
+            WHERE user_id = $1
+            ORDER BY id
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn update(&self, id: i32, item: &ItemUpdate) -> Result<Option<ItemFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            UPDATE items
+            SET name = COALESCE($2, name),
+                price = COALESCE($3, price)
+            WHERE id = $1
+            RETURNING id, user_id, name, price
+        "#,
+        )
+        .bind(id)
+        .bind(&item.name)
+        .bind(item.price)
+        .fetch_optional(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, AppError> {
+        let res = sqlx::query("DELETE FROM items WHERE id = $1")
+            .bind(id)
+            .execute(&mut *self.db.conn().await?)
+            .await?;
+
+        Ok(res.rows_affected() == 1)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{entity::user_entity::UserRegister, repo::user_repo::UserRepo};
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo, entity::user_entity::UserRegister,
+        repo::user_repo::UserRepo,
+    };
 
     use super::*;
-    use dotenvy::dotenv;
-    use sqlx::postgres::PgPoolOptions;
-    use std::env;
+    use crate::config::Config;
+    use sqlx::{Pool, postgres::PgPoolOptions};
 
     async fn load_pool() -> Pool<Postgres> {
-        dotenv().ok();
-        let db_str = env::var("db_str").unwrap();
+        let config = Config::load().unwrap();
 
         PgPoolOptions::new()
-            .max_connections(5)
-            .connect(&db_str)
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
             .await
             .unwrap()
     }
@@ -54,17 +115,17 @@ mod tests {
     async fn item_repo_exists() {
         let pool = load_pool().await;
 
-        let item_repo = ItemRepo::new(&pool);
+        let item_repo = ItemRepo::new(pool.clone());
 
-        let user_respo = UserRepo::new(&pool);
+        let user_respo = UserRepo::new(pool.clone());
 
         let user = UserRegister {
             name: String::from("my_name"),
             password: String::from("my_password"),
         };
 
-        user_respo.register(&user).await;
-        let fetched_user = user_respo.fetch_by_name("my_name").await;
+        user_respo.register(&user).await.unwrap();
+        let fetched_user = user_respo.fetch_by_name("my_name").await.unwrap();
 
         let item = ItemCreate {
             name: String::from("item - a"),
@@ -72,7 +133,7 @@ mod tests {
             user_id: fetched_user.id,
         };
 
-        item_repo.register(&item).await;
+        item_repo.register(&item).await.unwrap();
 
         sqlx::query("DELETE FROM items WHERE name = $1")
             .bind(item.name)
@@ -80,4 +141,64 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn item_repo_crud() {
+        let pool = load_pool().await;
+
+        let item_repo = ItemRepo::new(pool.clone());
+
+        let user_respo = UserRepo::new(pool.clone());
+
+        let user = UserRegister {
+            name: String::from("item_owner"),
+            password: String::from("my_password"),
+        };
+
+        user_respo.register(&user).await.unwrap();
+        let fetched_user = user_respo.fetch_by_name("item_owner").await.unwrap();
+
+        let item = ItemCreate {
+            name: String::from("item - b"),
+            price: 2.5,
+            user_id: fetched_user.id,
+        };
+
+        let created = item_repo.register(&item).await.unwrap();
+
+        assert_eq!(
+            item_repo.fetch_by_id(created.id).await.unwrap(),
+            Some(created.clone())
+        );
+        assert_eq!(
+            item_repo.fetch_by_user(fetched_user.id).await.unwrap(),
+            vec![created.clone()]
+        );
+
+        let patch = ItemUpdate {
+            name: None,
+            price: Some(3.0),
+        };
+
+        let updated = item_repo.update(created.id, &patch).await.unwrap().unwrap();
+
+        assert_eq!(updated.name, "item - b");
+        assert_eq!(updated.price, 3.0);
+
+        assert!(item_repo.delete(created.id).await.unwrap());
+        assert!(!item_repo.delete(created.id).await.unwrap());
+        assert!(item_repo.fetch_by_id(created.id).await.unwrap().is_none());
+
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(fetched_user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(fetched_user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
 }
@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use sqlx::Postgres;
+
+use crate::{
+    contract::repo::login_attempt_repo_trait::ILoginAttemptRepo,
+    entity::login_attempt_entity::LoginAttemptFetched, error::AppError, repo::db::Db,
+};
+
+pub struct LoginAttemptRepo {
+    db: Db<Postgres>,
+}
+
+impl LoginAttemptRepo {
+    pub fn new(db: impl Into<Db<Postgres>>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl ILoginAttemptRepo for LoginAttemptRepo {
+    async fn fetch(&self, key: &str) -> Result<Option<LoginAttemptFetched>, AppError> {
+        Ok(sqlx::query_as::<_, LoginAttemptFetched>(
+            r#"
+            SELECT key, failures, last_failure, locked_until
+            FROM login_attempts
+            WHERE key = $1
+        "#,
+        )
+        .bind(key)
+        .fetch_optional(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        now: i64,
+        reset_before: i64,
+    ) -> Result<i32, AppError> {
+        let failures: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO login_attempts (key, failures, last_failure)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (key) DO UPDATE
+            SET failures = CASE
+                    WHEN login_attempts.last_failure < $3 THEN 1
+                    ELSE login_attempts.failures + 1
+                END,
+                last_failure = $2
+            RETURNING failures
+        "#,
+        )
+        .bind(key)
+        .bind(now)
+        .bind(reset_before)
+        .fetch_one(&mut *self.db.conn().await?)
+        .await?;
+
+        Ok(failures.0)
+    }
+
+    async fn lock(&self, key: &str, until: i64) -> Result<(), AppError> {
+        sqlx::query("UPDATE login_attempts SET locked_until = $2 WHERE key = $1")
+            .bind(key)
+            .bind(until)
+            .execute(&mut *self.db.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM login_attempts WHERE key = $1")
+            .bind(key)
+            .execute(&mut *self.db.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use sqlx::{Pool, postgres::PgPoolOptions};
+
+    async fn load_pool() -> Pool<Postgres> {
+        let config = Config::load().unwrap();
+
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn login_attempt_repo_counts_and_resets() {
+        let repo = LoginAttemptRepo::new(load_pool().await);
+        let key = "account:login_attempt_test";
+
+        repo.clear(key).await.unwrap();
+
+        assert_eq!(repo.record_failure(key, 100, 0).await.unwrap(), 1);
+        assert_eq!(repo.record_failure(key, 110, 0).await.unwrap(), 2);
+        repo.lock(key, 200).await.unwrap();
+        assert_eq!(repo.fetch(key).await.unwrap().unwrap().locked_until, 200);
+
+        assert_eq!(repo.record_failure(key, 500, 400).await.unwrap(), 1);
+
+        repo.clear(key).await.unwrap();
+        assert!(repo.fetch(key).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::api_key_repo_trait::IApiKeyRepo,
+    entity::api_key_entity::{ApiKeyCreate, ApiKeyFetched},
+    error::AppError,
+    repo::memory::MemoryStore,
+};
+
+pub struct InMemoryApiKeyRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryApiKeyRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl IApiKeyRepo for InMemoryApiKeyRepo {
+    async fn create(&self, key: &ApiKeyCreate) -> Result<ApiKeyFetched, AppError> {
+        let mut tables = self.store.tables().await;
+        tables.check_user("api_keys", key.user_id)?;
+
+        if tables.api_keys.values().any(|k| k.key_hash == key.key_hash) {
+            return Err(AppError::Internal(String::from("duplicate api key hash")));
+        }
+
+        let id = tables.next_id();
+        let fetched = ApiKeyFetched {
+            id,
+            user_id: key.user_id,
+            name: key.name.clone(),
+            prefix: key.prefix.clone(),
+            key_hash: key.key_hash.clone(),
+            scopes: key.scopes.join(" "),
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            last_used_at: None,
+        };
+        tables.api_keys.insert(id, fetched.clone());
+
+        Ok(fetched)
+    }
+
+    async fn list(&self, user_id: i32) -> Result<Vec<ApiKeyFetched>, AppError> {
+        Ok(self
+            .store
+            .tables()
+            .await
+            .api_keys
+            .values()
+            .filter(|k| k.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn fetch_by_hash(&self, key_hash: &str) -> Result<Option<ApiKeyFetched>, AppError> {
+        Ok(self
+            .store
+            .tables()
+            .await
+            .api_keys
+            .values()
+            .find(|k| k.key_hash == key_hash)
+            .cloned())
+    }
+
+    async fn touch(&self, id: i32, used_at: i64) -> Result<(), AppError> {
+        if let Some(key) = self.store.tables().await.api_keys.get_mut(&id) {
+            key.last_used_at = Some(used_at);
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> Result<bool, AppError> {
+        let mut tables = self.store.tables().await;
+
+        if tables
+            .api_keys
+            .get(&id)
+            .is_some_and(|k| k.user_id == user_id)
+        {
+            tables.api_keys.remove(&id);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::item_repo_trait::IItemRepo,
+    entity::item_entity::{ItemCreate, ItemFetched, ItemUpdate},
+    error::AppError,
+    repo::memory::MemoryStore,
+};
+
+pub struct InMemoryItemRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryItemRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl IItemRepo for InMemoryItemRepo {
+    async fn register(&self, item: &ItemCreate) -> Result<ItemFetched, AppError> {
+        let mut tables = self.store.tables().await;
+        tables.check_user("items", item.user_id)?;
+
+        let fetched = ItemFetched {
+            id: tables.next_id(),
+            user_id: item.user_id,
+            name: item.name.clone(),
+            price: item.price,
+        };
+        tables.items.insert(fetched.id, fetched.clone());
+
+        Ok(fetched)
+    }
+
+    async fn fetch_by_id(&self, id: i32) -> Result<Option<ItemFetched>, AppError> {
+        Ok(self.store.tables().await.items.get(&id).cloned())
+    }
+
+    async fn fetch_by_user(&self, user_id: i32) -> Result<Vec<ItemFetched>, AppError> {
+        Ok(self
+            .store
+            .tables()
+            .await
+            .items
+            .values()
+            .filter(|i| i.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn update(&self, id: i32, item: &ItemUpdate) -> Result<Option<ItemFetched>, AppError> {
+        let mut tables = self.store.tables().await;
+
+        Ok(tables.items.get_mut(&id).map(|stored| {
+            if let Some(name) = &item.name {
+                stored.name = name.clone();
+            }
+            if let Some(price) = item.price {
+                stored.price = price;
+            }
+            stored.clone()
+        }))
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, AppError> {
+        Ok(self.store.tables().await.items.remove(&id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo, entity::user_entity::UserRegister,
+        repo::memory::user_repo::InMemoryUserRepo,
+    };
+
+    #[tokio::test]
+    async fn memory_item_repo_crud() {
+        let store = MemoryStore::new();
+
+        let user_repo = InMemoryUserRepo::new(store.clone());
+        user_repo
+            .register(&UserRegister {
+                name: String::from("item_owner"),
+                password: String::from("password"),
+            })
+            .await
+            .unwrap();
+        let owner = user_repo.fetch_by_name("item_owner").await.unwrap();
+
+        let repo = InMemoryItemRepo::new(store.clone());
+
+        let item = repo
+            .register(&ItemCreate {
+                name: String::from("item"),
+                price: 1.5,
+                user_id: owner.id,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(repo.fetch_by_id(item.id).await.unwrap(), Some(item.clone()));
+        assert_eq!(
+            repo.fetch_by_user(owner.id).await.unwrap(),
+            vec![item.clone()]
+        );
+
+        let patch = ItemUpdate {
+            name: None,
+            price: Some(2.0),
+        };
+        let updated = repo.update(item.id, &patch).await.unwrap().unwrap();
+        assert_eq!(updated.name, "item");
+        assert_eq!(updated.price, 2.0);
+
+        assert!(repo.delete(item.id).await.unwrap());
+        assert!(!repo.delete(item.id).await.unwrap());
+        assert!(repo.update(item.id, &patch).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn memory_item_repo_requires_owner() {
+        let store = MemoryStore::new();
+        let repo = InMemoryItemRepo::new(store.clone());
+
+        let res = repo
+            .register(&ItemCreate {
+                name: String::from("orphan"),
+                price: 1.0,
+                user_id: 42,
+            })
+            .await;
+
+        assert!(res.is_err());
+    }
+}
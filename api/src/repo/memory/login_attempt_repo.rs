@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::login_attempt_repo_trait::ILoginAttemptRepo,
+    entity::login_attempt_entity::LoginAttemptFetched, error::AppError, repo::memory::MemoryStore,
+};
+
+pub struct InMemoryLoginAttemptRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryLoginAttemptRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl ILoginAttemptRepo for InMemoryLoginAttemptRepo {
+    async fn fetch(&self, key: &str) -> Result<Option<LoginAttemptFetched>, AppError> {
+        Ok(self.store.tables().await.login_attempts.get(key).cloned())
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        now: i64,
+        reset_before: i64,
+    ) -> Result<i32, AppError> {
+        let mut tables = self.store.tables().await;
+        let attempt = tables
+            .login_attempts
+            .entry(String::from(key))
+            .or_insert_with(|| LoginAttemptFetched {
+                key: String::from(key),
+                failures: 0,
+                last_failure: now,
+                locked_until: 0,
+            });
+
+        if attempt.last_failure < reset_before {
+            attempt.failures = 0;
+        }
+        attempt.failures += 1;
+        attempt.last_failure = now;
+
+        Ok(attempt.failures)
+    }
+
+    async fn lock(&self, key: &str, until: i64) -> Result<(), AppError> {
+        if let Some(attempt) = self.store.tables().await.login_attempts.get_mut(key) {
+            attempt.locked_until = until;
+        }
+
+        Ok(())
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        self.store.tables().await.login_attempts.remove(key);
+
+        Ok(())
+    }
+}
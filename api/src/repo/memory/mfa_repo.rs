@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::mfa_repo_trait::IMfaRepo,
+    entity::mfa_entity::MfaFetched,
+    error::AppError,
+    repo::memory::{MemoryStore, RecoveryCodeRow},
+};
+
+pub struct InMemoryMfaRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryMfaRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl IMfaRepo for InMemoryMfaRepo {
+    async fn fetch(&self, user_id: i32) -> Result<Option<MfaFetched>, AppError> {
+        Ok(self.store.tables().await.user_mfa.get(&user_id).cloned())
+    }
+
+    async fn set_pending(&self, user_id: i32, secret: &str) -> Result<(), AppError> {
+        let mut tables = self.store.tables().await;
+        tables.check_user("user_mfa", user_id)?;
+
+        tables.user_mfa.insert(
+            user_id,
+            MfaFetched {
+                user_id,
+                secret: String::from(secret),
+                enabled: false,
+                last_step: 0,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn enable(&self, user_id: i32, recovery_hashes: &[String]) -> Result<(), AppError> {
+        let mut tables = self.store.tables().await;
+
+        if let Some(mfa) = tables.user_mfa.get_mut(&user_id) {
+            mfa.enabled = true;
+        }
+
+        tables.mfa_recovery_codes.retain(|c| c.user_id != user_id);
+        tables
+            .mfa_recovery_codes
+            .extend(recovery_hashes.iter().map(|code_hash| RecoveryCodeRow {
+                user_id,
+                code_hash: code_hash.clone(),
+                used: false,
+            }));
+
+        Ok(())
+    }
+
+    async fn use_step(&self, user_id: i32, step: i64) -> Result<bool, AppError> {
+        let mut tables = self.store.tables().await;
+
+        Ok(match tables.user_mfa.get_mut(&user_id) {
+            Some(mfa) if mfa.last_step < step => {
+                mfa.last_step = step;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    async fn use_recovery_code(&self, user_id: i32, code_hash: &str) -> Result<bool, AppError> {
+        let mut tables = self.store.tables().await;
+
+        Ok(
+            match tables
+                .mfa_recovery_codes
+                .iter_mut()
+                .find(|c| c.user_id == user_id && c.code_hash == code_hash && !c.used)
+            {
+                Some(code) => {
+                    code.used = true;
+                    true
+                }
+                None => false,
+            },
+        )
+    }
+}
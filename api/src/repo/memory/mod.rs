@@ -0,0 +1,167 @@
+pub mod api_key_repo;
+pub mod item_repo;
+pub mod login_attempt_repo;
+pub mod mfa_repo;
+pub mod password_reset_repo;
+pub mod refresh_token_repo;
+pub mod revocation_repo;
+pub mod session_repo;
+pub mod unit_of_work;
+pub mod user_repo;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use tokio::sync::{Mutex, MutexGuard, OwnedMutexGuard};
+
+use crate::{
+    entity::{
+        api_key_entity::ApiKeyFetched, item_entity::ItemFetched,
+        login_attempt_entity::LoginAttemptFetched, mfa_entity::MfaFetched,
+        password_reset_entity::PasswordResetFetched, refresh_token_entity::RefreshTokenFetched,
+        session_entity::SessionFetched,
+    },
+    error::AppError,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct UserRow {
+    pub id: i32,
+    pub name: String,
+    pub password: String,
+    pub token_version: i32,
+    pub roles: Vec<String>,
+    pub email: Option<String>,
+    pub email_verified_at: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct RecoveryCodeRow {
+    pub user_id: i32,
+    pub code_hash: String,
+    pub used: bool,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Tables {
+    pub users: BTreeMap<i32, UserRow>,
+    pub items: BTreeMap<i32, ItemFetched>,
+    pub refresh_tokens: BTreeMap<i32, RefreshTokenFetched>,
+    pub revoked_tokens: HashMap<String, i64>,
+    pub login_attempts: HashMap<String, LoginAttemptFetched>,
+    pub user_mfa: BTreeMap<i32, MfaFetched>,
+    pub mfa_recovery_codes: Vec<RecoveryCodeRow>,
+    pub api_keys: BTreeMap<i32, ApiKeyFetched>,
+    pub sessions: BTreeMap<i32, SessionFetched>,
+    pub password_resets: BTreeMap<i32, PasswordResetFetched>,
+    next_id: i32,
+}
+
+impl Tables {
+    /// Ids are unique across tables, which is stricter than SERIAL but
+    /// indistinguishable to callers.
+    pub fn next_id(&mut self) -> i32 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Mirrors the `REFERENCES users (id)` constraints of the SQL schema.
+    pub fn check_user(&self, table: &str, user_id: i32) -> Result<(), AppError> {
+        if self.users.contains_key(&user_id) {
+            Ok(())
+        } else {
+            Err(AppError::Internal(format!(
+                "insert on {table} violates foreign key to users ({user_id})"
+            )))
+        }
+    }
+}
+
+/// Process-local storage shared by the in-memory repos, so the server can run
+/// with no database at all. Clones share the same tables, like a pool.
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    handle: Handle,
+}
+
+#[derive(Clone)]
+enum Handle {
+    Shared(Arc<Mutex<Tables>>),
+    /// Holds the shared lock for the whole unit of work, which is how the
+    /// memory backend isolates it from everything else.
+    Tx(Arc<Mutex<OwnedMutexGuard<Tables>>>),
+}
+
+impl Default for Handle {
+    fn default() -> Self {
+        Handle::Shared(Arc::default())
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) async fn tables(&self) -> TablesGuard<'_> {
+        match &self.handle {
+            Handle::Shared(tables) => TablesGuard::Shared(tables.lock().await),
+            Handle::Tx(tx) => TablesGuard::Tx(tx.lock().await),
+        }
+    }
+
+    /// Locks the tables until the returned store is dropped. Anything else
+    /// touching this store waits until then, so don't use it from inside the
+    /// unit of work.
+    pub(crate) async fn begin(&self) -> Result<MemoryStore, AppError> {
+        let Handle::Shared(tables) = &self.handle else {
+            return Err(AppError::Internal(String::from(
+                "nested units of work are not supported",
+            )));
+        };
+
+        let guard = tables.clone().lock_owned().await;
+
+        Ok(MemoryStore {
+            handle: Handle::Tx(Arc::new(Mutex::new(guard))),
+        })
+    }
+
+    /// Puts back the tables a unit of work started from. Only called on drop,
+    /// when none of its repos can still be holding the lock.
+    fn restore(&self, tables: Tables) {
+        if let Handle::Tx(tx) = &self.handle
+            && let Ok(mut guard) = tx.try_lock()
+        {
+            **guard = tables;
+        }
+    }
+}
+
+pub(crate) enum TablesGuard<'a> {
+    Shared(MutexGuard<'a, Tables>),
+    Tx(MutexGuard<'a, OwnedMutexGuard<Tables>>),
+}
+
+impl Deref for TablesGuard<'_> {
+    type Target = Tables;
+
+    fn deref(&self) -> &Tables {
+        match self {
+            TablesGuard::Shared(tables) => tables,
+            TablesGuard::Tx(tables) => tables,
+        }
+    }
+}
+
+impl DerefMut for TablesGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Tables {
+        match self {
+            TablesGuard::Shared(tables) => tables,
+            TablesGuard::Tx(tables) => tables,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::password_reset_repo_trait::IPasswordResetRepo,
+    entity::password_reset_entity::{PasswordResetCreate, PasswordResetFetched},
+    error::AppError,
+    repo::memory::MemoryStore,
+};
+
+pub struct InMemoryPasswordResetRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryPasswordResetRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl IPasswordResetRepo for InMemoryPasswordResetRepo {
+    async fn create(&self, reset: &PasswordResetCreate) -> Result<(), AppError> {
+        let mut tables = self.store.tables().await;
+        tables.check_user("password_resets", reset.user_id)?;
+
+        if tables
+            .password_resets
+            .values()
+            .any(|r| r.token_hash == reset.token_hash)
+        {
+            return Err(AppError::Internal(String::from(
+                "duplicate password reset token",
+            )));
+        }
+
+        let id = tables.next_id();
+        tables.password_resets.insert(
+            id,
+            PasswordResetFetched {
+                id,
+                user_id: reset.user_id,
+                token_hash: reset.token_hash.clone(),
+                expires_at: reset.expires_at,
+                used: false,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn consume(&self, token_hash: &str, now: i64) -> Result<Option<i32>, AppError> {
+        let mut tables = self.store.tables().await;
+
+        Ok(tables
+            .password_resets
+            .values_mut()
+            .find(|r| r.token_hash == token_hash && !r.used && r.expires_at > now)
+            .map(|reset| {
+                reset.used = true;
+                reset.user_id
+            }))
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError> {
+        self.store
+            .tables()
+            .await
+            .password_resets
+            .retain(|_, r| r.user_id != user_id);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::refresh_token_repo_trait::IRefreshTokenRepo,
+    entity::refresh_token_entity::{RefreshTokenCreate, RefreshTokenFetched},
+    error::AppError,
+    repo::memory::MemoryStore,
+};
+
+pub struct InMemoryRefreshTokenRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryRefreshTokenRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl IRefreshTokenRepo for InMemoryRefreshTokenRepo {
+    async fn register(&self, token: &RefreshTokenCreate) -> Result<(), AppError> {
+        let mut tables = self.store.tables().await;
+        tables.check_user("refresh_tokens", token.user_id)?;
+
+        if tables
+            .refresh_tokens
+            .values()
+            .any(|t| t.token_hash == token.token_hash)
+        {
+            return Err(AppError::Internal(String::from(
+                "duplicate refresh token hash",
+            )));
+        }
+
+        let id = tables.next_id();
+        tables.refresh_tokens.insert(
+            id,
+            RefreshTokenFetched {
+                id,
+                user_id: token.user_id,
+                token_hash: token.token_hash.clone(),
+                family: token.family.clone(),
+                expires_at: token.expires_at,
+                used: false,
+                revoked: false,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn fetch_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenFetched>, AppError> {
+        Ok(self
+            .store
+            .tables()
+            .await
+            .refresh_tokens
+            .values()
+            .find(|t| t.token_hash == token_hash)
+            .cloned())
+    }
+
+    async fn mark_used(&self, id: i32) -> Result<bool, AppError> {
+        let mut tables = self.store.tables().await;
+
+        Ok(match tables.refresh_tokens.get_mut(&id) {
+            Some(token) if !token.used => {
+                token.used = true;
+                true
+            }
+            _ => false,
+        })
+    }
+
+    async fn revoke_family(&self, family: &str) -> Result<(), AppError> {
+        self.store
+            .tables()
+            .await
+            .refresh_tokens
+            .values_mut()
+            .filter(|t| t.family == family)
+            .for_each(|t| t.revoked = true);
+
+        Ok(())
+    }
+
+    async fn revoke_user(&self, user_id: i32) -> Result<(), AppError> {
+        self.store
+            .tables()
+            .await
+            .refresh_tokens
+            .values_mut()
+            .filter(|t| t.user_id == user_id)
+            .for_each(|t| t.revoked = true);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::revocation_repo_trait::IRevocationRepo, error::AppError,
+    repo::memory::MemoryStore,
+};
+
+pub struct InMemoryRevocationRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryRevocationRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl IRevocationRepo for InMemoryRevocationRepo {
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let mut tables = self.store.tables().await;
+        tables.revoked_tokens.retain(|_, exp| *exp >= now);
+        tables
+            .revoked_tokens
+            .entry(String::from(jti))
+            .or_insert(expires_at);
+
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError> {
+        Ok(self.store.tables().await.revoked_tokens.contains_key(jti))
+    }
+
+    async fn token_version(&self, user_id: i32) -> Result<i32, AppError> {
+        self.store
+            .tables()
+            .await
+            .users
+            .get(&user_id)
+            .map(|u| u.token_version)
+            .ok_or_else(|| AppError::not_found("user_not_found", "User not found."))
+    }
+
+    async fn bump_token_version(&self, user_id: i32) -> Result<(), AppError> {
+        if let Some(user) = self.store.tables().await.users.get_mut(&user_id) {
+            user.token_version += 1;
+        }
+
+        Ok(())
+    }
+}
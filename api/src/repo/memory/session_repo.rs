@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::session_repo_trait::ISessionRepo,
+    entity::session_entity::{SessionCreate, SessionFetched},
+    error::AppError,
+    repo::memory::MemoryStore,
+};
+
+pub struct InMemorySessionRepo {
+    store: MemoryStore,
+}
+
+impl InMemorySessionRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl ISessionRepo for InMemorySessionRepo {
+    async fn create(&self, session: &SessionCreate) -> Result<SessionFetched, AppError> {
+        let mut tables = self.store.tables().await;
+        tables.check_user("sessions", session.user_id)?;
+
+        if tables.sessions.values().any(|s| s.family == session.family) {
+            return Err(AppError::Internal(String::from("duplicate session family")));
+        }
+
+        let id = tables.next_id();
+        let fetched = SessionFetched {
+            id,
+            user_id: session.user_id,
+            family: session.family.clone(),
+            user_agent: session.user_agent.clone(),
+            ip: session.ip.clone(),
+            created_at: session.created_at,
+            last_seen_at: session.created_at,
+        };
+        tables.sessions.insert(id, fetched.clone());
+
+        Ok(fetched)
+    }
+
+    async fn fetch(&self, id: i32) -> Result<Option<SessionFetched>, AppError> {
+        Ok(self.store.tables().await.sessions.get(&id).cloned())
+    }
+
+    async fn fetch_by_family(&self, family: &str) -> Result<Option<SessionFetched>, AppError> {
+        Ok(self
+            .store
+            .tables()
+            .await
+            .sessions
+            .values()
+            .find(|s| s.family == family)
+            .cloned())
+    }
+
+    async fn list(&self, user_id: i32, seen_since: i64) -> Result<Vec<SessionFetched>, AppError> {
+        let mut sessions: Vec<_> = self
+            .store
+            .tables()
+            .await
+            .sessions
+            .values()
+            .filter(|s| s.user_id == user_id && s.last_seen_at >= seen_since)
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse((s.last_seen_at, s.id)));
+
+        Ok(sessions)
+    }
+
+    async fn touch(&self, id: i32, seen_at: i64) -> Result<(), AppError> {
+        if let Some(session) = self.store.tables().await.sessions.get_mut(&id) {
+            session.last_seen_at = seen_at;
+        }
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: i32) -> Result<(), AppError> {
+        self.store.tables().await.sessions.remove(&id);
+        Ok(())
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError> {
+        self.store
+            .tables()
+            .await
+            .sessions
+            .retain(|_, s| s.user_id != user_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::{
+        item_repo_trait::IItemRepo, login_attempt_repo_trait::ILoginAttemptRepo,
+        unit_of_work_trait::IUnitOfWork, user_repo_trait::IUserRepo,
+    },
+    error::AppError,
+    repo::memory::{
+        MemoryStore, Tables, item_repo::InMemoryItemRepo,
+        login_attempt_repo::InMemoryLoginAttemptRepo, user_repo::InMemoryUserRepo,
+    },
+};
+
+/// Writes go straight to the locked tables; rolling back restores the copy
+/// taken when the unit of work began.
+pub struct InMemoryUnitOfWork {
+    store: MemoryStore,
+    snapshot: Option<Tables>,
+    users: InMemoryUserRepo,
+    items: InMemoryItemRepo,
+    login_attempts: InMemoryLoginAttemptRepo,
+}
+
+impl InMemoryUnitOfWork {
+    pub async fn begin(store: &MemoryStore) -> Result<Self, AppError> {
+        let store = store.begin().await?;
+        let snapshot = store.tables().await.clone();
+
+        Ok(Self {
+            users: InMemoryUserRepo::new(store.clone()),
+            items: InMemoryItemRepo::new(store.clone()),
+            login_attempts: InMemoryLoginAttemptRepo::new(store.clone()),
+            snapshot: Some(snapshot),
+            store,
+        })
+    }
+}
+
+impl Drop for InMemoryUnitOfWork {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            self.store.restore(snapshot);
+        }
+    }
+}
+
+#[async_trait]
+impl IUnitOfWork for InMemoryUnitOfWork {
+    fn users(&self) -> &(dyn IUserRepo + Send + Sync) {
+        &self.users
+    }
+
+    fn items(&self) -> &(dyn IItemRepo + Send + Sync) {
+        &self.items
+    }
+
+    fn login_attempts(&self) -> &(dyn ILoginAttemptRepo + Send + Sync) {
+        &self.login_attempts
+    }
+
+    async fn commit(mut self: Box<Self>) -> Result<(), AppError> {
+        self.snapshot = None;
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract::repo::unit_of_work_trait::IUnitOfWork,
+        entity::{item_entity::ItemCreate, user_entity::UserRegister},
+    };
+
+    async fn register_with_item(uow: &dyn IUnitOfWork, name: &str) {
+        uow.users()
+            .register(&UserRegister {
+                name: String::from(name),
+                password: String::from("password"),
+            })
+            .await
+            .unwrap();
+        let user = uow.users().fetch_by_name(name).await.unwrap();
+
+        uow.items()
+            .register(&ItemCreate {
+                name: String::from("default"),
+                price: 0.0,
+                user_id: user.id,
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn memory_unit_of_work_commit_and_rollback() {
+        let store = MemoryStore::new();
+        let users = InMemoryUserRepo::new(store.clone());
+
+        let uow = Box::new(InMemoryUnitOfWork::begin(&store).await.unwrap());
+        register_with_item(uow.as_ref(), "kept").await;
+        uow.commit().await.unwrap();
+
+        let uow = Box::new(InMemoryUnitOfWork::begin(&store).await.unwrap());
+        register_with_item(uow.as_ref(), "rolled_back").await;
+        uow.rollback().await.unwrap();
+
+        let uow = InMemoryUnitOfWork::begin(&store).await.unwrap();
+        register_with_item(&uow, "dropped").await;
+        drop(uow);
+
+        assert!(users.exists("kept").await.unwrap());
+        assert!(!users.exists("rolled_back").await.unwrap());
+        assert!(!users.exists("dropped").await.unwrap());
+        assert_eq!(store.tables().await.items.len(), 1);
+    }
+}
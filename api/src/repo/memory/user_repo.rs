@@ -0,0 +1,331 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::repo::user_repo_trait::{IUserRepo, email_taken, unknown_role, user_exists},
+    entity::{
+        role_entity::{
+            PERMISSION_MANAGE_USERS, PERMISSION_READ_USERS, ROLE_ADMIN, ROLE_USER, UserAccess,
+            UserSummary,
+        },
+        user_entity::{UserEmail, UserFetched, UserRegister},
+    },
+    error::AppError,
+    repo::memory::{MemoryStore, UserRow},
+};
+
+pub struct InMemoryUserRepo {
+    store: MemoryStore,
+}
+
+impl InMemoryUserRepo {
+    pub fn new(store: MemoryStore) -> Self {
+        Self { store }
+    }
+}
+
+/// Mirrors the `role_permissions` rows seeded by the roles migration.
+const ROLE_PERMISSIONS: &[(&str, &[&str])] = &[
+    (ROLE_USER, &[]),
+    (
+        ROLE_ADMIN,
+        &[PERMISSION_READ_USERS, PERMISSION_MANAGE_USERS],
+    ),
+];
+
+fn role_permissions(role: &str) -> Option<&'static [&'static str]> {
+    ROLE_PERMISSIONS
+        .iter()
+        .find(|(name, _)| *name == role)
+        .map(|(_, permissions)| *permissions)
+}
+
+fn user_fetched(row: &UserRow) -> UserFetched {
+    UserFetched {
+        id: row.id,
+        name: row.name.clone(),
+        password: row.password.clone(),
+    }
+}
+
+fn not_found() -> AppError {
+    AppError::not_found("user_not_found", "User not found.")
+}
+
+#[async_trait]
+impl IUserRepo for InMemoryUserRepo {
+    async fn exists(&self, name: &str) -> Result<bool, AppError> {
+        Ok(self
+            .store
+            .tables()
+            .await
+            .users
+            .values()
+            .any(|u| u.name == name))
+    }
+
+    async fn register(&self, dto: &UserRegister) -> Result<(), AppError> {
+        let mut tables = self.store.tables().await;
+
+        if tables.users.values().any(|u| u.name == dto.name) {
+            return Err(user_exists());
+        }
+
+        let id = tables.next_id();
+        tables.users.insert(
+            id,
+            UserRow {
+                id,
+                name: dto.name.clone(),
+                password: dto.password.clone(),
+                token_version: 0,
+                roles: vec![String::from(ROLE_USER)],
+                email: None,
+                email_verified_at: None,
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn fetch_by_name(&self, name: &str) -> Result<UserFetched, AppError> {
+        self.store
+            .tables()
+            .await
+            .users
+            .values()
+            .find(|u| u.name == name)
+            .map(user_fetched)
+            .ok_or_else(not_found)
+    }
+
+    async fn fetch_by_id(&self, id: i32) -> Result<UserFetched, AppError> {
+        self.store
+            .tables()
+            .await
+            .users
+            .get(&id)
+            .map(user_fetched)
+            .ok_or_else(not_found)
+    }
+
+    async fn update_password(&self, id: i32, password: &str) -> Result<(), AppError> {
+        if let Some(user) = self.store.tables().await.users.get_mut(&id) {
+            user.password = String::from(password);
+        }
+
+        Ok(())
+    }
+
+    async fn rename(&self, id: i32, name: &str) -> Result<bool, AppError> {
+        let mut tables = self.store.tables().await;
+
+        if tables.users.values().any(|u| u.id != id && u.name == name) {
+            return Err(user_exists());
+        }
+
+        Ok(match tables.users.get_mut(&id) {
+            Some(user) => {
+                user.name = String::from(name);
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, AppError> {
+        let mut tables = self.store.tables().await;
+
+        if tables.users.remove(&id).is_none() {
+            return Ok(false);
+        }
+
+        // Mirrors the ON DELETE CASCADE foreign keys.
+        tables.items.retain(|_, row| row.user_id != id);
+        tables.refresh_tokens.retain(|_, row| row.user_id != id);
+        tables.user_mfa.remove(&id);
+        tables.mfa_recovery_codes.retain(|row| row.user_id != id);
+        tables.api_keys.retain(|_, row| row.user_id != id);
+        tables.sessions.retain(|_, row| row.user_id != id);
+        tables.password_resets.retain(|_, row| row.user_id != id);
+
+        Ok(true)
+    }
+
+    async fn access(&self, id: i32) -> Result<UserAccess, AppError> {
+        let tables = self.store.tables().await;
+        let roles = tables
+            .users
+            .get(&id)
+            .map(|u| u.roles.clone())
+            .unwrap_or_default();
+
+        let mut permissions: Vec<String> = roles
+            .iter()
+            .filter_map(|role| role_permissions(role))
+            .flatten()
+            .map(|permission| String::from(*permission))
+            .collect();
+        permissions.sort();
+        permissions.dedup();
+
+        Ok(UserAccess { roles, permissions })
+    }
+
+    async fn list(&self, limit: i64, offset: i64) -> Result<Vec<UserSummary>, AppError> {
+        Ok(self
+            .store
+            .tables()
+            .await
+            .users
+            .values()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .map(|u| UserSummary {
+                id: u.id,
+                name: u.name.clone(),
+                roles: u.roles.clone(),
+            })
+            .collect())
+    }
+
+    async fn set_roles(&self, id: i32, roles: &[String]) -> Result<(), AppError> {
+        if let Some(role) = roles.iter().find(|role| role_permissions(role).is_none()) {
+            return Err(unknown_role(role));
+        }
+
+        let mut tables = self.store.tables().await;
+        tables.check_user("user_roles", id)?;
+
+        // Kept sorted, like the SQL repos return them.
+        let mut roles = roles.to_vec();
+        roles.sort();
+        roles.dedup();
+        tables.users.get_mut(&id).unwrap().roles = roles;
+
+        Ok(())
+    }
+
+    async fn email(&self, id: i32) -> Result<UserEmail, AppError> {
+        let tables = self.store.tables().await;
+        let user = tables.users.get(&id).ok_or_else(not_found)?;
+
+        Ok(UserEmail {
+            email: user.email.clone(),
+            verified_at: user.email_verified_at,
+        })
+    }
+
+    async fn set_email(&self, id: i32, email: Option<&str>) -> Result<(), AppError> {
+        let mut tables = self.store.tables().await;
+
+        if let Some(user) = tables.users.get_mut(&id) {
+            user.email = email.map(String::from);
+            user.email_verified_at = None;
+        }
+
+        Ok(())
+    }
+
+    async fn verify_email(&self, id: i32, email: &str, at: i64) -> Result<bool, AppError> {
+        let mut tables = self.store.tables().await;
+
+        // Mirrors the unique index on verified addresses.
+        if tables.users.values().any(|u| {
+            u.id != id && u.email.as_deref() == Some(email) && u.email_verified_at.is_some()
+        }) {
+            return Err(email_taken());
+        }
+
+        match tables.users.get_mut(&id) {
+            Some(user) if user.email.as_deref() == Some(email) => {
+                user.email_verified_at.get_or_insert(at);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn memory_user_repo_register_and_fetch() {
+        let store = MemoryStore::new();
+        let repo = InMemoryUserRepo::new(store.clone());
+
+        let user = UserRegister {
+            name: String::from("memory_user"),
+            password: String::from("memory_password"),
+        };
+
+        assert!(!repo.exists(&user.name).await.unwrap());
+
+        repo.register(&user).await.unwrap();
+
+        assert!(repo.exists(&user.name).await.unwrap());
+
+        let fetched = repo.fetch_by_name(&user.name).await.unwrap();
+        assert_eq!(repo.fetch_by_id(fetched.id).await.unwrap().name, user.name);
+
+        repo.update_password(fetched.id, "rehashed").await.unwrap();
+        assert_eq!(
+            repo.fetch_by_id(fetched.id).await.unwrap().password,
+            "rehashed"
+        );
+
+        assert!(matches!(
+            repo.register(&user).await,
+            Err(AppError::Conflict { .. })
+        ));
+        assert!(matches!(
+            repo.fetch_by_name("missing").await,
+            Err(AppError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn memory_user_repo_roles() {
+        let repo = InMemoryUserRepo::new(MemoryStore::new());
+
+        let user = UserRegister {
+            name: String::from("memory_admin"),
+            password: String::from("memory_admin_password"),
+        };
+        repo.register(&user).await.unwrap();
+        let id = repo.fetch_by_name(&user.name).await.unwrap().id;
+
+        assert_eq!(
+            repo.access(id).await.unwrap(),
+            UserAccess {
+                roles: vec![String::from(ROLE_USER)],
+                permissions: Vec::new(),
+            }
+        );
+
+        let roles = vec![String::from(ROLE_USER), String::from(ROLE_ADMIN)];
+        repo.set_roles(id, &roles).await.unwrap();
+
+        let access = repo.access(id).await.unwrap();
+        assert_eq!(access.roles, [ROLE_ADMIN, ROLE_USER]);
+        assert_eq!(
+            access.permissions,
+            [PERMISSION_MANAGE_USERS, PERMISSION_READ_USERS]
+        );
+
+        assert!(matches!(
+            repo.set_roles(id, &[String::from("wizard")]).await,
+            Err(AppError::Validation { .. })
+        ));
+        assert_eq!(
+            repo.access(id).await.unwrap().roles,
+            [ROLE_ADMIN, ROLE_USER]
+        );
+
+        let listed = repo.list(100, 0).await.unwrap();
+        let summary = listed.iter().find(|u| u.id == id).unwrap();
+        assert_eq!(summary.name, user.name);
+        assert_eq!(summary.roles, [ROLE_ADMIN, ROLE_USER]);
+    }
+}
@@ -1,2 +1,15 @@
+pub mod api_key_repo;
+pub mod db;
 pub mod item_repo;
+pub mod login_attempt_repo;
+pub mod memory;
+pub mod mfa_repo;
+pub mod password_reset_repo;
+pub mod refresh_token_repo;
+pub mod revocation_repo;
+pub mod session_repo;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod storage;
+pub mod unit_of_work;
 pub mod user_repo;
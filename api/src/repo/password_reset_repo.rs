@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    contract::repo::password_reset_repo_trait::IPasswordResetRepo,
+    entity::password_reset_entity::PasswordResetCreate, error::AppError,
+};
+
+pub struct PasswordResetRepo {
+    pool: Pool<Postgres>,
+}
+
+impl PasswordResetRepo {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IPasswordResetRepo for PasswordResetRepo {
+    async fn create(&self, reset: &PasswordResetCreate) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO password_resets (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+        )
+        .bind(reset.user_id)
+        .bind(&reset.token_hash)
+        .bind(reset.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume(&self, token_hash: &str, now: i64) -> Result<Option<i32>, AppError> {
+        let user_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            UPDATE password_resets SET used = TRUE
+            WHERE token_hash = $1 AND used = FALSE AND expires_at > $2
+            RETURNING user_id
+        "#,
+        )
+        .bind(token_hash)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id.map(|row| row.0))
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM password_resets WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config, contract::repo::user_repo_trait::IUserRepo,
+        entity::user_entity::UserRegister, repo::user_repo::UserRepo,
+    };
+    use sqlx::postgres::PgPoolOptions;
+
+    async fn load_pool() -> Pool<Postgres> {
+        let config = Config::load().unwrap();
+
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn password_reset_is_single_use() {
+        let pool = load_pool().await;
+
+        let user_repo = UserRepo::new(pool.clone());
+        user_repo
+            .register(&UserRegister {
+                name: String::from("reset_user"),
+                password: String::from("reset_user_password"),
+            })
+            .await
+            .unwrap();
+        let user = user_repo.fetch_by_name("reset_user").await.unwrap();
+
+        let repo = PasswordResetRepo::new(pool.clone());
+        for (token_hash, expires_at) in [("reset_user_live", 200), ("reset_user_expired", 100)] {
+            repo.create(&PasswordResetCreate {
+                user_id: user.id,
+                token_hash: String::from(token_hash),
+                expires_at,
+            })
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(repo.consume("reset_user_expired", 150).await.unwrap(), None);
+        assert_eq!(
+            repo.consume("reset_user_live", 150).await.unwrap(),
+            Some(user.id)
+        );
+        assert_eq!(repo.consume("reset_user_live", 150).await.unwrap(), None);
+
+        repo.delete_user(user.id).await.unwrap();
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
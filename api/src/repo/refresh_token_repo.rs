@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    contract::repo::refresh_token_repo_trait::IRefreshTokenRepo,
+    entity::refresh_token_entity::{RefreshTokenCreate, RefreshTokenFetched},
+    error::AppError,
+};
+
+pub struct RefreshTokenRepo {
+    pool: Pool<Postgres>,
+}
+
+impl RefreshTokenRepo {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IRefreshTokenRepo for RefreshTokenRepo {
+    async fn register(&self, token: &RefreshTokenCreate) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO refresh_tokens (user_id, token_hash, family, expires_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(token.user_id)
+        .bind(&token.token_hash)
+        .bind(&token.family)
+        .bind(token.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenFetched>, AppError> {
+        Ok(sqlx::query_as::<_, RefreshTokenFetched>(
+            r#"
+            SELECT id, user_id, token_hash, family, expires_at, used, revoked
+            FROM refresh_tokens
+            WHERE token_hash = $1
+        "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn mark_used(&self, id: i32) -> Result<bool, AppError> {
+        let res =
+            sqlx::query("UPDATE refresh_tokens SET used = TRUE WHERE id = $1 AND used = FALSE")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn revoke_family(&self, family: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE family = $1")
+            .bind(family)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_user(&self, user_id: i32) -> Result<(), AppError> {
+        sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo, entity::user_entity::UserRegister,
+        repo::user_repo::UserRepo,
+    };
+
+    use super::*;
+    use crate::config::Config;
+    use sqlx::postgres::PgPoolOptions;
+
+    async fn load_pool() -> Pool<Postgres> {
+        let config = Config::load().unwrap();
+
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn refresh_token_repo_revoke_family() {
+        let pool = load_pool().await;
+
+        let user_repo = UserRepo::new(pool.clone());
+        let user = UserRegister {
+            name: String::from("refresh_user"),
+            password: String::from("refresh_password"),
+        };
+        user_repo.register(&user).await.unwrap();
+        let fetched_user = user_repo.fetch_by_name("refresh_user").await.unwrap();
+
+        let repo = RefreshTokenRepo::new(pool.clone());
+        let token = RefreshTokenCreate {
+            user_id: fetched_user.id,
+            token_hash: String::from("refresh_hash"),
+            family: String::from("refresh_family"),
+            expires_at: 0,
+        };
+        repo.register(&token).await.unwrap();
+
+        let fetched = repo.fetch_by_hash("refresh_hash").await.unwrap().unwrap();
+        assert!(!fetched.used && !fetched.revoked);
+
+        assert!(repo.mark_used(fetched.id).await.unwrap());
+        assert!(!repo.mark_used(fetched.id).await.unwrap());
+        repo.revoke_family("refresh_family").await.unwrap();
+
+        let fetched = repo.fetch_by_hash("refresh_hash").await.unwrap().unwrap();
+        assert!(fetched.used && fetched.revoked);
+
+        sqlx::query("DELETE FROM refresh_tokens WHERE user_id = $1")
+            .bind(fetched_user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(fetched_user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(fetched_user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use crate::{contract::repo::revocation_repo_trait::IRevocationRepo, error::AppError};
+
+pub struct RevocationRepo {
+    pool: Pool<Postgres>,
+}
+
+impl RevocationRepo {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IRevocationRepo for RevocationRepo {
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        // Entries only matter until the token would have expired anyway.
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO revoked_tokens (jti, expires_at) VALUES ($1, $2) ON CONFLICT (jti) DO NOTHING",
+        )
+        .bind(jti)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError> {
+        let revoked: (bool,) =
+            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)")
+                .bind(jti)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(revoked.0)
+    }
+
+    async fn token_version(&self, user_id: i32) -> Result<i32, AppError> {
+        let version: (i32,) = sqlx::query_as("SELECT token_version FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::not_found("user_not_found", "User not found."))?;
+
+        Ok(version.0)
+    }
+
+    async fn bump_token_version(&self, user_id: i32) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET token_version = token_version + 1 WHERE id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo, entity::user_entity::UserRegister,
+        repo::user_repo::UserRepo,
+    };
+
+    use super::*;
+    use crate::config::Config;
+    use sqlx::postgres::PgPoolOptions;
+
+    async fn load_pool() -> Pool<Postgres> {
+        let config = Config::load().unwrap();
+
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn revocation_repo_revoke_jti() {
+        let pool = load_pool().await;
+
+        let repo = RevocationRepo::new(pool.clone());
+
+        assert!(!repo.is_revoked("revoked_jti").await.unwrap());
+
+        repo.revoke("revoked_jti", i64::MAX).await.unwrap();
+        repo.revoke("revoked_jti", i64::MAX).await.unwrap();
+
+        assert!(repo.is_revoked("revoked_jti").await.unwrap());
+
+        sqlx::query("DELETE FROM revoked_tokens WHERE jti = $1")
+            .bind("revoked_jti")
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn revocation_repo_bump_version() {
+        let pool = load_pool().await;
+
+        let user_repo = UserRepo::new(pool.clone());
+        let user = UserRegister {
+            name: String::from("version_user"),
+            password: String::from("version_password"),
+        };
+        user_repo.register(&user).await.unwrap();
+        let fetched_user = user_repo.fetch_by_name("version_user").await.unwrap();
+
+        let repo = RevocationRepo::new(pool.clone());
+
+        assert_eq!(repo.token_version(fetched_user.id).await.unwrap(), 0);
+
+        repo.bump_token_version(fetched_user.id).await.unwrap();
+
+        assert_eq!(repo.token_version(fetched_user.id).await.unwrap(), 1);
+
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(fetched_user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(fetched_user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
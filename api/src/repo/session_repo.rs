@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    contract::repo::session_repo_trait::ISessionRepo,
+    entity::session_entity::{SessionCreate, SessionFetched},
+    error::AppError,
+};
+
+pub struct SessionRepo {
+    pool: Pool<Postgres>,
+}
+
+impl SessionRepo {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ISessionRepo for SessionRepo {
+    async fn create(&self, session: &SessionCreate) -> Result<SessionFetched, AppError> {
+        Ok(sqlx::query_as::<_, SessionFetched>(
+            r#"
+            INSERT INTO sessions (user_id, family, user_agent, ip, created_at, last_seen_at)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            RETURNING id, user_id, family, user_agent, ip, created_at, last_seen_at
+        "#,
+        )
+        .bind(session.user_id)
+        .bind(&session.family)
+        .bind(&session.user_agent)
+        .bind(&session.ip)
+        .bind(session.created_at)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    async fn fetch(&self, id: i32) -> Result<Option<SessionFetched>, AppError> {
+        Ok(sqlx::query_as::<_, SessionFetched>(
+            r#"
+            SELECT id, user_id, family, user_agent, ip, created_at, last_seen_at
+            FROM sessions
+            WHERE id = $1
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn fetch_by_family(&self, family: &str) -> Result<Option<SessionFetched>, AppError> {
+        Ok(sqlx::query_as::<_, SessionFetched>(
+            r#"
+            SELECT id, user_id, family, user_agent, ip, created_at, last_seen_at
+            FROM sessions
+            WHERE family = $1
+        "#,
+        )
+        .bind(family)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn list(&self, user_id: i32, seen_since: i64) -> Result<Vec<SessionFetched>, AppError> {
+        Ok(sqlx::query_as::<_, SessionFetched>(
+            r#"
+            SELECT id, user_id, family, user_agent, ip, created_at, last_seen_at
+            FROM sessions
+            WHERE user_id = $1 AND last_seen_at >= $2
+            ORDER BY last_seen_at DESC, id DESC
+        "#,
+        )
+        .bind(user_id)
+        .bind(seen_since)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    async fn touch(&self, id: i32, seen_at: i64) -> Result<(), AppError> {
+        sqlx::query("UPDATE sessions SET last_seen_at = $2 WHERE id = $1")
+            .bind(id)
+            .bind(seen_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: i32) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM sessions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM sessions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::Config, contract::repo::user_repo_trait::IUserRepo,
+        entity::user_entity::UserRegister, repo::user_repo::UserRepo,
+    };
+    use sqlx::postgres::PgPoolOptions;
+
+    async fn load_pool() -> Pool<Postgres> {
+        let config = Config::load().unwrap();
+
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn session_repo_lifecycle() {
+        let pool = load_pool().await;
+
+        let user_repo = UserRepo::new(pool.clone());
+        user_repo
+            .register(&UserRegister {
+                name: String::from("session_user"),
+                password: String::from("session_user_password"),
+            })
+            .await
+            .unwrap();
+        let user = user_repo.fetch_by_name("session_user").await.unwrap();
+
+        let repo = SessionRepo::new(pool.clone());
+        let mut ids = Vec::new();
+        for (family, created_at) in [("session_user_old", 100), ("session_user_new", 200)] {
+            let created = repo
+                .create(&SessionCreate {
+                    user_id: user.id,
+                    family: String::from(family),
+                    user_agent: Some(String::from("curl/8.0")),
+                    ip: None,
+                    created_at,
+                })
+                .await
+                .unwrap();
+            assert_eq!(created.last_seen_at, created_at);
+            ids.push(created.id);
+        }
+
+        repo.touch(ids[0], 300).await.unwrap();
+        let listed = repo.list(user.id, 150).await.unwrap();
+        assert_eq!(
+            listed.iter().map(|s| s.id).collect::<Vec<_>>(),
+            [ids[0], ids[1]]
+        );
+        assert!(repo.list(user.id, 250).await.unwrap().len() == 1);
+        assert_eq!(
+            repo.fetch_by_family("session_user_new").await.unwrap(),
+            repo.fetch(ids[1]).await.unwrap()
+        );
+
+        repo.delete(ids[0]).await.unwrap();
+        assert!(repo.fetch(ids[0]).await.unwrap().is_none());
+        repo.delete_user(user.id).await.unwrap();
+        assert!(repo.list(user.id, 0).await.unwrap().is_empty());
+
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use sqlx::Sqlite;
+
+use crate::{
+    contract::repo::item_repo_trait::IItemRepo,
+    entity::item_entity::{ItemCreate, ItemFetched, ItemUpdate},
+    error::AppError,
+    repo::db::Db,
+};
+
+pub struct SqliteItemRepo {
+    db: Db<Sqlite>,
+}
+
+impl SqliteItemRepo {
+    pub fn new(db: impl Into<Db<Sqlite>>) -> Self {
+        Self { db: db.into() }
+    }
+}
+
+#[async_trait]
+impl IItemRepo for SqliteItemRepo {
+    async fn register(&self, item: &ItemCreate) -> Result<ItemFetched, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            INSERT INTO items (name, price, user_id)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, name, price
+        "#,
+        )
+        .bind(&item.name)
+        .bind(item.price)
+        .bind(item.user_id)
+        .fetch_one(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn fetch_by_id(&self, id: i32) -> Result<Option<ItemFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            SELECT id, user_id, name, price
+            FROM items
+            WHERE id = $1
+        "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn fetch_by_user(&self, user_id: i32) -> Result<Vec<ItemFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            SELECT id, user_id, name, price
+            FROM items
+            WHERE user_id = $1
+            ORDER BY id
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn update(&self, id: i32, item: &ItemUpdate) -> Result<Option<ItemFetched>, AppError> {
+        Ok(sqlx::query_as::<_, ItemFetched>(
+            r#"
+            UPDATE items
+            SET name = COALESCE($2, name),
+                price = COALESCE($3, price)
+            WHERE id = $1
+            RETURNING id, user_id, name, price
+        "#,
+        )
+        .bind(id)
+        .bind(&item.name)
+        .bind(item.price)
+        .fetch_optional(&mut *self.db.conn().await?)
+        .await?)
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, AppError> {
+        let res = sqlx::query("DELETE FROM items WHERE id = $1")
+            .bind(id)
+            .execute(&mut *self.db.conn().await?)
+            .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo,
+        entity::user_entity::UserRegister,
+        repo::sqlite::{test_pool, user_repo::SqliteUserRepo},
+    };
+
+    #[tokio::test]
+    async fn sqlite_item_repo_crud() {
+        let pool = test_pool().await;
+
+        let user_repo = SqliteUserRepo::new(pool.clone());
+        user_repo
+            .register(&UserRegister {
+                name: String::from("item_owner"),
+                password: String::from("password"),
+            })
+            .await
+            .unwrap();
+        let owner = user_repo.fetch_by_name("item_owner").await.unwrap();
+
+        let repo = SqliteItemRepo::new(pool.clone());
+
+        let item = repo
+            .register(&ItemCreate {
+                name: String::from("item"),
+                price: 1.5,
+                user_id: owner.id,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(repo.fetch_by_id(item.id).await.unwrap(), Some(item.clone()));
+        assert_eq!(
+            repo.fetch_by_user(owner.id).await.unwrap(),
+            vec![item.clone()]
+        );
+
+        let patch = ItemUpdate {
+            name: None,
+            price: Some(2.0),
+        };
+        let updated = repo.update(item.id, &patch).await.unwrap().unwrap();
+        assert_eq!(updated.name, "item");
+        assert_eq!(updated.price, 2.0);
+
+        assert!(repo.delete(item.id).await.unwrap());
+        assert!(!repo.delete(item.id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn sqlite_item_repo_requires_owner() {
+        let pool = test_pool().await;
+        let repo = SqliteItemRepo::new(pool.clone());
+
+        let res = repo
+            .register(&ItemCreate {
+                name: String::from("orphan"),
+                price: 1.0,
+                user_id: 42,
+            })
+            .await;
+
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    contract::repo::mfa_repo_trait::IMfaRepo, entity::mfa_entity::MfaFetched, error::AppError,
+};
+
+pub struct SqliteMfaRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteMfaRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IMfaRepo for SqliteMfaRepo {
+    async fn fetch(&self, user_id: i32) -> Result<Option<MfaFetched>, AppError> {
+        Ok(sqlx::query_as::<_, MfaFetched>(
+            r#"
+            SELECT user_id, secret, enabled, last_step
+            FROM user_mfa
+            WHERE user_id = $1
+        "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn set_pending(&self, user_id: i32, secret: &str) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_mfa (user_id, secret)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = excluded.secret, enabled = FALSE, last_step = 0
+        "#,
+        )
+        .bind(user_id)
+        .bind(secret)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn enable(&self, user_id: i32, recovery_hashes: &[String]) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE user_mfa SET enabled = TRUE WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM mfa_recovery_codes WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for code_hash in recovery_hashes {
+            sqlx::query("INSERT INTO mfa_recovery_codes (user_id, code_hash) VALUES ($1, $2)")
+                .bind(user_id)
+                .bind(code_hash)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn use_step(&self, user_id: i32, step: i64) -> Result<bool, AppError> {
+        let res =
+            sqlx::query("UPDATE user_mfa SET last_step = $2 WHERE user_id = $1 AND last_step < $2")
+                .bind(user_id)
+                .bind(step)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn use_recovery_code(&self, user_id: i32, code_hash: &str) -> Result<bool, AppError> {
+        let res = sqlx::query(
+            "UPDATE mfa_recovery_codes SET used = TRUE WHERE user_id = $1 AND code_hash = $2 AND used = FALSE",
+        )
+        .bind(user_id)
+        .bind(code_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo,
+        entity::user_entity::UserRegister,
+        repo::sqlite::{test_pool, user_repo::SqliteUserRepo},
+    };
+
+    #[tokio::test]
+    async fn sqlite_mfa_repo_enroll_and_use_codes() {
+        let pool = test_pool().await;
+        let user_repo = SqliteUserRepo::new(pool.clone());
+        user_repo
+            .register(&UserRegister {
+                name: String::from("mfa_user"),
+                password: String::from("mfa_password"),
+            })
+            .await
+            .unwrap();
+        let user_id = user_repo.fetch_by_name("mfa_user").await.unwrap().id;
+
+        let repo = SqliteMfaRepo::new(pool);
+        repo.set_pending(user_id, "SECRET").await.unwrap();
+        repo.enable(user_id, &[String::from("code_hash")])
+            .await
+            .unwrap();
+        assert!(repo.fetch(user_id).await.unwrap().unwrap().enabled);
+
+        repo.set_pending(user_id, "OTHER").await.unwrap();
+        let pending = repo.fetch(user_id).await.unwrap().unwrap();
+        assert!(!pending.enabled && pending.secret == "OTHER");
+
+        assert!(repo.use_step(user_id, 10).await.unwrap());
+        assert!(!repo.use_step(user_id, 9).await.unwrap());
+        assert!(repo.use_recovery_code(user_id, "code_hash").await.unwrap());
+        assert!(!repo.use_recovery_code(user_id, "code_hash").await.unwrap());
+    }
+}
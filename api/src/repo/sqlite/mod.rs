@@ -0,0 +1,29 @@
+pub mod api_key_repo;
+pub mod item_repo;
+pub mod login_attempt_repo;
+pub mod mfa_repo;
+pub mod password_reset_repo;
+pub mod refresh_token_repo;
+pub mod revocation_repo;
+pub mod session_repo;
+pub mod unit_of_work;
+pub mod user_repo;
+
+#[cfg(test)]
+pub(crate) async fn test_pool() -> sqlx::Pool<sqlx::Sqlite> {
+    use migration::migrations::SQLITE_MIGRATIONS;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    // A single connection keeps every query on the same in-memory database.
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+
+    for migration in SQLITE_MIGRATIONS {
+        sqlx::raw_sql(migration.up).execute(&pool).await.unwrap();
+    }
+
+    pool
+}
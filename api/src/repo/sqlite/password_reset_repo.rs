@@ -0,0 +1,100 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    contract::repo::password_reset_repo_trait::IPasswordResetRepo,
+    entity::password_reset_entity::PasswordResetCreate, error::AppError,
+};
+
+pub struct SqlitePasswordResetRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl SqlitePasswordResetRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IPasswordResetRepo for SqlitePasswordResetRepo {
+    async fn create(&self, reset: &PasswordResetCreate) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO password_resets (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+        )
+        .bind(reset.user_id)
+        .bind(&reset.token_hash)
+        .bind(reset.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume(&self, token_hash: &str, now: i64) -> Result<Option<i32>, AppError> {
+        let user_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            UPDATE password_resets SET used = TRUE
+            WHERE token_hash = $1 AND used = FALSE AND expires_at > $2
+            RETURNING user_id
+        "#,
+        )
+        .bind(token_hash)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(user_id.map(|row| row.0))
+    }
+
+    async fn delete_user(&self, user_id: i32) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM password_resets WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo,
+        entity::user_entity::UserRegister,
+        repo::sqlite::{test_pool, user_repo::SqliteUserRepo},
+    };
+
+    #[tokio::test]
+    async fn sqlite_password_reset_is_single_use() {
+        let pool = test_pool().await;
+
+        let user_repo = SqliteUserRepo::new(pool.clone());
+        user_repo
+            .register(&UserRegister {
+                name: String::from("sqlite_reset_user"),
+                password: String::from("sqlite_reset_user_password"),
+            })
+            .await
+            .unwrap();
+        let user = user_repo.fetch_by_name("sqlite_reset_user").await.unwrap();
+
+        let repo = SqlitePasswordResetRepo::new(pool.clone());
+        for (token_hash, expires_at) in [("live", 200), ("expired", 100)] {
+            repo.create(&PasswordResetCreate {
+                user_id: user.id,
+                token_hash: String::from(token_hash),
+                expires_at,
+            })
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(repo.consume("expired", 150).await.unwrap(), None);
+        assert_eq!(repo.consume("live", 150).await.unwrap(), Some(user.id));
+        assert_eq!(repo.consume("live", 150).await.unwrap(), None);
+
+        repo.delete_user(user.id).await.unwrap();
+    }
+}
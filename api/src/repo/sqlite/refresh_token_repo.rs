@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
+
+use crate::{
+    contract::repo::refresh_token_repo_trait::IRefreshTokenRepo,
+    entity::refresh_token_entity::{RefreshTokenCreate, RefreshTokenFetched},
+    error::AppError,
+};
+
+pub struct SqliteRefreshTokenRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteRefreshTokenRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IRefreshTokenRepo for SqliteRefreshTokenRepo {
+    async fn register(&self, token: &RefreshTokenCreate) -> Result<(), AppError> {
+        sqlx::query(
+            "INSERT INTO refresh_tokens (user_id, token_hash, family, expires_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(token.user_id)
+        .bind(&token.token_hash)
+        .bind(&token.family)
+        .bind(token.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenFetched>, AppError> {
+        Ok(sqlx::query_as::<_, RefreshTokenFetched>(
+            r#"
+            SELECT id, user_id, token_hash, family, expires_at, used, revoked
+            FROM refresh_tokens
+            WHERE token_hash = $1
+        "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    async fn mark_used(&self, id: i32) -> Result<bool, AppError> {
+        let res =
+            sqlx::query("UPDATE refresh_tokens SET used = TRUE WHERE id = $1 AND used = FALSE")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+
+    async fn revoke_family(&self, family: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE family = $1")
+            .bind(family)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_user(&self, user_id: i32) -> Result<(), AppError> {
+        sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
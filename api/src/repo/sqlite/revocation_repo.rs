@@ -0,0 +1,71 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
+
+use crate::{contract::repo::revocation_repo_trait::IRevocationRepo, error::AppError};
+
+pub struct SqliteRevocationRepo {
+    pool: Pool<Sqlite>,
+}
+
+impl SqliteRevocationRepo {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl IRevocationRepo for SqliteRevocationRepo {
+    async fn revoke(&self, jti: &str, expires_at: i64) -> Result<(), AppError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        // Entries only matter until the token would have expired anyway.
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < $1")
+            .bind(now)
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO revoked_tokens (jti, expires_at) VALUES ($1, $2) ON CONFLICT (jti) DO NOTHING",
+        )
+        .bind(jti)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &str) -> Result<bool, AppError> {
+        let revoked: (bool,) =
+            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)")
+                .bind(jti)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(revoked.0)
+    }
+
+    async fn token_version(&self, user_id: i32) -> Result<i32, AppError> {
+        let version: (i32,) = sqlx::query_as("SELECT token_version FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::not_found("user_not_found", "User not found."))?;
+
+        Ok(version.0)
+    }
+
+    async fn bump_token_version(&self, user_id: i32) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET token_version = token_version + 1 WHERE id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
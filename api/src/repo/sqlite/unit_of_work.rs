@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::Mutex;
+
+use crate::{
+    contract::repo::{
+        item_repo_trait::IItemRepo, login_attempt_repo_trait::ILoginAttemptRepo,
+        unit_of_work_trait::IUnitOfWork, user_repo_trait::IUserRepo,
+    },
+    error::AppError,
+    repo::{
+        db::{SharedTransaction, into_transaction},
+        sqlite::{
+            item_repo::SqliteItemRepo, login_attempt_repo::SqliteLoginAttemptRepo,
+            user_repo::SqliteUserRepo,
+        },
+    },
+};
+
+pub struct SqliteUnitOfWork {
+    tx: SharedTransaction<Sqlite>,
+    users: SqliteUserRepo,
+    items: SqliteItemRepo,
+    login_attempts: SqliteLoginAttemptRepo,
+}
+
+impl SqliteUnitOfWork {
+    pub async fn begin(pool: &Pool<Sqlite>) -> Result<Self, AppError> {
+        let tx = Arc::new(Mutex::new(pool.begin().await?));
+
+        Ok(Self {
+            users: SqliteUserRepo::new(tx.clone()),
+            items: SqliteItemRepo::new(tx.clone()),
+            login_attempts: SqliteLoginAttemptRepo::new(tx.clone()),
+            tx,
+        })
+    }
+}
+
+#[async_trait]
+impl IUnitOfWork for SqliteUnitOfWork {
+    fn users(&self) -> &(dyn IUserRepo + Send + Sync) {
+        &self.users
+    }
+
+    fn items(&self) -> &(dyn IItemRepo + Send + Sync) {
+        &self.items
+    }
+
+    fn login_attempts(&self) -> &(dyn ILoginAttemptRepo + Send + Sync) {
+        &self.login_attempts
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), AppError> {
+        let Self {
+            tx,
+            users,
+            items,
+            login_attempts,
+        } = *self;
+        drop((users, items, login_attempts));
+
+        Ok(into_transaction(tx)?.commit().await?)
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), AppError> {
+        let Self {
+            tx,
+            users,
+            items,
+            login_attempts,
+        } = *self;
+        drop((users, items, login_attempts));
+
+        Ok(into_transaction(tx)?.rollback().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{item_entity::ItemCreate, user_entity::UserRegister},
+        repo::sqlite::test_pool,
+    };
+
+    #[tokio::test]
+    async fn sqlite_unit_of_work_commit_and_rollback() {
+        let pool = test_pool().await;
+
+        for (name, commit) in [("kept", true), ("rolled_back", false)] {
+            let uow = Box::new(SqliteUnitOfWork::begin(&pool).await.unwrap());
+
+            uow.users()
+                .register(&UserRegister {
+                    name: String::from(name),
+                    password: String::from("password"),
+                })
+                .await
+                .unwrap();
+            let user = uow.users().fetch_by_name(name).await.unwrap();
+            uow.items()
+                .register(&ItemCreate {
+                    name: String::from("default"),
+                    price: 0.0,
+                    user_id: user.id,
+                })
+                .await
+                .unwrap();
+
+            if commit {
+                uow.commit().await.unwrap();
+            } else {
+                uow.rollback().await.unwrap();
+            }
+        }
+
+        let users = SqliteUserRepo::new(pool.clone());
+        assert!(users.exists("kept").await.unwrap());
+        assert!(!users.exists("rolled_back").await.unwrap());
+
+        let items: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM items")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(items.0, 1);
+    }
+}
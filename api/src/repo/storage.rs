@@ -0,0 +1,204 @@
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
+#[cfg(feature = "sqlite")]
+use sqlx::{Sqlite, sqlite::SqlitePoolOptions};
+
+#[cfg(feature = "sqlite")]
+use crate::repo::sqlite::{
+    api_key_repo::SqliteApiKeyRepo, item_repo::SqliteItemRepo,
+    login_attempt_repo::SqliteLoginAttemptRepo, mfa_repo::SqliteMfaRepo,
+    password_reset_repo::SqlitePasswordResetRepo, refresh_token_repo::SqliteRefreshTokenRepo,
+    revocation_repo::SqliteRevocationRepo, session_repo::SqliteSessionRepo,
+    unit_of_work::SqliteUnitOfWork, user_repo::SqliteUserRepo,
+};
+use crate::{
+    config::Config,
+    contract::repo::{
+        api_key_repo_trait::IApiKeyRepo,
+        item_repo_trait::IItemRepo,
+        login_attempt_repo_trait::ILoginAttemptRepo,
+        mfa_repo_trait::IMfaRepo,
+        password_reset_repo_trait::IPasswordResetRepo,
+        refresh_token_repo_trait::IRefreshTokenRepo,
+        revocation_repo_trait::IRevocationRepo,
+        session_repo_trait::ISessionRepo,
+        unit_of_work_trait::{DynUnitOfWork, IUnitOfWorkFactory},
+        user_repo_trait::IUserRepo,
+    },
+    error::AppError,
+    repo::{
+        api_key_repo::ApiKeyRepo,
+        item_repo::ItemRepo,
+        login_attempt_repo::LoginAttemptRepo,
+        memory::{
+            MemoryStore, api_key_repo::InMemoryApiKeyRepo, item_repo::InMemoryItemRepo,
+            login_attempt_repo::InMemoryLoginAttemptRepo, mfa_repo::InMemoryMfaRepo,
+            password_reset_repo::InMemoryPasswordResetRepo,
+            refresh_token_repo::InMemoryRefreshTokenRepo, revocation_repo::InMemoryRevocationRepo,
+            session_repo::InMemorySessionRepo, unit_of_work::InMemoryUnitOfWork,
+            user_repo::InMemoryUserRepo,
+        },
+        mfa_repo::MfaRepo,
+        password_reset_repo::PasswordResetRepo,
+        refresh_token_repo::RefreshTokenRepo,
+        revocation_repo::RevocationRepo,
+        session_repo::SessionRepo,
+        unit_of_work::UnitOfWork,
+        user_repo::UserRepo,
+    },
+};
+
+pub const MEMORY_SCHEME: &str = "memory://";
+pub const SQLITE_SCHEME: &str = "sqlite:";
+
+pub type DynUserRepo = Box<dyn IUserRepo + Send + Sync>;
+pub type DynItemRepo = Box<dyn IItemRepo + Send + Sync>;
+pub type DynRefreshTokenRepo = Box<dyn IRefreshTokenRepo + Send + Sync>;
+pub type DynRevocationRepo = Box<dyn IRevocationRepo + Send + Sync>;
+pub type DynLoginAttemptRepo = Box<dyn ILoginAttemptRepo + Send + Sync>;
+pub type DynMfaRepo = Box<dyn IMfaRepo + Send + Sync>;
+pub type DynApiKeyRepo = Box<dyn IApiKeyRepo + Send + Sync>;
+pub type DynSessionRepo = Box<dyn ISessionRepo + Send + Sync>;
+pub type DynPasswordResetRepo = Box<dyn IPasswordResetRepo + Send + Sync>;
+
+/// The backend every repo is built on, picked from the `db_str` scheme.
+#[derive(Clone)]
+pub enum Storage {
+    Postgres(Pool<Postgres>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Pool<Sqlite>),
+    Memory(MemoryStore),
+}
+
+impl Storage {
+    pub async fn connect(config: &Config) -> Result<Self, sqlx::Error> {
+        if config.db_str.starts_with(MEMORY_SCHEME) {
+            return Ok(Self::memory());
+        }
+
+        if config.db_str.starts_with(SQLITE_SCHEME) {
+            return Self::connect_sqlite(config).await;
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await?;
+
+        Ok(Storage::Postgres(pool))
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn connect_sqlite(config: &Config) -> Result<Self, sqlx::Error> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await?;
+
+        Ok(Storage::Sqlite(pool))
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    async fn connect_sqlite(_: &Config) -> Result<Self, sqlx::Error> {
+        Err(sqlx::Error::Configuration(
+            "sqlite support requires building with the `sqlite` feature".into(),
+        ))
+    }
+
+    pub fn memory() -> Self {
+        Storage::Memory(MemoryStore::new())
+    }
+
+    pub fn user_repo(&self) -> DynUserRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(UserRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteUserRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryUserRepo::new(store.clone())),
+        }
+    }
+
+    pub fn item_repo(&self) -> DynItemRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(ItemRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteItemRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryItemRepo::new(store.clone())),
+        }
+    }
+
+    pub fn refresh_token_repo(&self) -> DynRefreshTokenRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(RefreshTokenRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteRefreshTokenRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryRefreshTokenRepo::new(store.clone())),
+        }
+    }
+
+    pub fn revocation_repo(&self) -> DynRevocationRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(RevocationRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteRevocationRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryRevocationRepo::new(store.clone())),
+        }
+    }
+
+    pub fn login_attempt_repo(&self) -> DynLoginAttemptRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(LoginAttemptRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteLoginAttemptRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryLoginAttemptRepo::new(store.clone())),
+        }
+    }
+
+    pub fn mfa_repo(&self) -> DynMfaRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(MfaRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteMfaRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryMfaRepo::new(store.clone())),
+        }
+    }
+
+    pub fn api_key_repo(&self) -> DynApiKeyRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(ApiKeyRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteApiKeyRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryApiKeyRepo::new(store.clone())),
+        }
+    }
+
+    pub fn session_repo(&self) -> DynSessionRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(SessionRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteSessionRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemorySessionRepo::new(store.clone())),
+        }
+    }
+
+    pub fn password_reset_repo(&self) -> DynPasswordResetRepo {
+        match self {
+            Storage::Postgres(pool) => Box::new(PasswordResetRepo::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqlitePasswordResetRepo::new(pool.clone())),
+            Storage::Memory(store) => Box::new(InMemoryPasswordResetRepo::new(store.clone())),
+        }
+    }
+}
+
+#[async_trait]
+impl IUnitOfWorkFactory for Storage {
+    async fn begin(&self) -> Result<DynUnitOfWork, AppError> {
+        Ok(match self {
+            Storage::Postgres(pool) => Box::new(UnitOfWork::begin(pool).await?),
+            #[cfg(feature = "sqlite")]
+            Storage::Sqlite(pool) => Box::new(SqliteUnitOfWork::begin(pool).await?),
+            Storage::Memory(store) => Box::new(InMemoryUnitOfWork::begin(store).await?),
+        })
+    }
+}
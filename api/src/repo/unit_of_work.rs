@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+use tokio::sync::Mutex;
+
+use crate::{
+    contract::repo::{
+        item_repo_trait::IItemRepo, login_attempt_repo_trait::ILoginAttemptRepo,
+        unit_of_work_trait::IUnitOfWork, user_repo_trait::IUserRepo,
+    },
+    error::AppError,
+    repo::{
+        db::{SharedTransaction, into_transaction},
+        item_repo::ItemRepo,
+        login_attempt_repo::LoginAttemptRepo,
+        user_repo::UserRepo,
+    },
+};
+
+pub struct UnitOfWork {
+    tx: SharedTransaction<Postgres>,
+    users: UserRepo,
+    items: ItemRepo,
+    login_attempts: LoginAttemptRepo,
+}
+
+impl UnitOfWork {
+    pub async fn begin(pool: &Pool<Postgres>) -> Result<Self, AppError> {
+        let tx = Arc::new(Mutex::new(pool.begin().await?));
+
+        Ok(Self {
+            users: UserRepo::new(tx.clone()),
+            items: ItemRepo::new(tx.clone()),
+            login_attempts: LoginAttemptRepo::new(tx.clone()),
+            tx,
+        })
+    }
+}
+
+#[async_trait]
+impl IUnitOfWork for UnitOfWork {
+    fn users(&self) -> &(dyn IUserRepo + Send + Sync) {
+        &self.users
+    }
+
+    fn items(&self) -> &(dyn IItemRepo + Send + Sync) {
+        &self.items
+    }
+
+    fn login_attempts(&self) -> &(dyn ILoginAttemptRepo + Send + Sync) {
+        &self.login_attempts
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), AppError> {
+        let Self {
+            tx,
+            users,
+            items,
+            login_attempts,
+        } = *self;
+        drop((users, items, login_attempts));
+
+        Ok(into_transaction(tx)?.commit().await?)
+    }
+
+    async fn rollback(self: Box<Self>) -> Result<(), AppError> {
+        let Self {
+            tx,
+            users,
+            items,
+            login_attempts,
+        } = *self;
+        drop((users, items, login_attempts));
+
+        Ok(into_transaction(tx)?.rollback().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::Config, entity::user_entity::UserRegister, repo::user_repo::UserRepo};
+    use sqlx::postgres::PgPoolOptions;
+
+    async fn load_pool() -> Pool<Postgres> {
+        let config = Config::load().unwrap();
+
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn unit_of_work_rollback_discards_writes() {
+        let pool = load_pool().await;
+
+        let uow = Box::new(UnitOfWork::begin(&pool).await.unwrap());
+        uow.users()
+            .register(&UserRegister {
+                name: String::from("uow_rolled_back"),
+                password: String::from("password"),
+            })
+            .await
+            .unwrap();
+        assert!(uow.users().exists("uow_rolled_back").await.unwrap());
+        uow.rollback().await.unwrap();
+
+        let users = UserRepo::new(pool.clone());
+        assert!(!users.exists("uow_rolled_back").await.unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn concurrent_duplicate_names_conflict() {
+        let pool = load_pool().await;
+
+        let user = UserRegister {
+            name: String::from("uow_duplicate"),
+            password: String::from("password"),
+        };
+
+        let first = Box::new(UnitOfWork::begin(&pool).await.unwrap());
+        let second = Box::new(UnitOfWork::begin(&pool).await.unwrap());
+
+        first.users().register(&user).await.unwrap();
+
+        // The second insert blocks on the first transaction's index entry and
+        // fails once it commits.
+        let (committed, duplicate) = tokio::join!(first.commit(), second.users().register(&user));
+
+        committed.unwrap();
+        assert!(matches!(duplicate, Err(AppError::Conflict { .. })));
+
+        sqlx::query(
+            "DELETE FROM user_roles WHERE user_id IN (SELECT id FROM users WHERE name = $1)",
+        )
+        .bind(&user.name)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM users WHERE name = $1")
+            .bind(&user.name)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
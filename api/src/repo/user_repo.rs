@@ -1,23 +1,25 @@
-use crate::contract::repo::user_repo_trait::IUserRepo;
-use crate::entity::user_entity::{UserFetched, UserRegister};
+use crate::contract::repo::user_repo_trait::{IUserRepo, email_taken, unknown_role, user_exists};
+use crate::entity::role_entity::{ROLE_USER, UserAccess, UserSummary};
+use crate::entity::user_entity::{UserEmail, UserFetched, UserRegister};
+use crate::error::AppError;
+use crate::repo::db::Db;
 use async_trait::async_trait;
-use bcrypt::{DEFAULT_COST, hash};
-use sqlx::Pool;
+use sqlx::Connection;
 use sqlx::postgres::Postgres;
 
-pub struct UserRepo<'a> {
-    pool: &'a Pool<Postgres>,
+pub struct UserRepo {
+    db: Db<Postgres>,
 }
 
-impl<'a> UserRepo<'a> {
-    pub fn new(pool: &'a Pool<Postgres>) -> Self {
-        Self { pool }
+impl UserRepo {
+    pub fn new(db: impl Into<Db<Postgres>>) -> Self {
+        Self { db: db.into() }
     }
 }
 
 #[async_trait]
-impl IUserRepo for UserRepo<'_> {
-    async fn exists(&self, name: &str) -> bool {
+impl IUserRepo for UserRepo {
+    async fn exists(&self, name: &str) -> Result<bool, AppError> {
         let exists: (bool,) = sqlx::query_as(
             r#"
         SELECT EXISTS (
@@ -28,25 +30,40 @@ impl IUserRepo for UserRepo<'_> {
         "#,
         )
         .bind(name)
-        .fetch_one(self.pool)
-        .await
-        .unwrap();
+        .fetch_one(&mut *self.db.conn().await?)
+        .await?;
 
-        exists.0
+        Ok(exists.0)
     }
-    async fn password_hash(&self, password: &str) -> String {
-        hash(password, DEFAULT_COST).unwrap()
-    }
-    async fn register(&self, dto: &UserRegister) {
-        sqlx::query("INSERT INTO users (name, password) VALUES ($1, $2)")
-            .bind(&dto.name)
-            .bind(&dto.password)
-            .execute(self.pool)
-            .await
-            .unwrap();
+    async fn register(&self, dto: &UserRegister) -> Result<(), AppError> {
+        let mut conn = self.db.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        let (id,): (i32,) =
+            sqlx::query_as("INSERT INTO users (name, password) VALUES ($1, $2) RETURNING id")
+                .bind(&dto.name)
+                .bind(&dto.password)
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|err| match err {
+                    sqlx::Error::Database(db) if db.is_unique_violation() => user_exists(),
+                    err => err.into(),
+                })?;
+
+        sqlx::query(
+            "INSERT INTO user_roles (user_id, role_id) SELECT $1, id FROM roles WHERE name = $2",
+        )
+        .bind(id)
+        .bind(ROLE_USER)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
     }
 
-    async fn fetch_by_name(&self, name: &str) -> UserFetched {
+    async fn fetch_by_name(&self, name: &str) -> Result<UserFetched, AppError> {
         sqlx::query_as::<_, UserFetched>(
             r#"
             SELECT id, name, password
@@ -55,12 +72,12 @@ impl IUserRepo for UserRepo<'_> {
         "#,
         )
         .bind(name)
-        .fetch_one(self.pool)
-        .await
-        .unwrap()
+        .fetch_optional(&mut *self.db.conn().await?)
+        .await?
+        .ok_or_else(|| AppError::not_found("user_not_found", "User not found."))
     }
 
-    async fn fetch_by_id(&self, id: i32) -> UserFetched {
+    async fn fetch_by_id(&self, id: i32) -> Result<UserFetched, AppError> {
         sqlx::query_as::<_, UserFetched>(
             r#"
             SELECT id, name, password
@@ -69,26 +86,216 @@ impl IUserRepo for UserRepo<'_> {
         "#,
         )
         .bind(id)
-        .fetch_one(self.pool)
+        .fetch_optional(&mut *self.db.conn().await?)
+        .await?
+        .ok_or_else(|| AppError::not_found("user_not_found", "User not found."))
+    }
+
+    async fn update_password(&self, id: i32, password: &str) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET password = $2 WHERE id = $1")
+            .bind(id)
+            .bind(password)
+            .execute(&mut *self.db.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn rename(&self, id: i32, name: &str) -> Result<bool, AppError> {
+        let res = sqlx::query("UPDATE users SET name = $2 WHERE id = $1")
+            .bind(id)
+            .bind(name)
+            .execute(&mut *self.db.conn().await?)
+            .await
+            .map_err(|err| match err {
+                sqlx::Error::Database(db) if db.is_unique_violation() => user_exists(),
+                err => err.into(),
+            })?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn delete(&self, id: i32) -> Result<bool, AppError> {
+        // Rows referencing the user go with it (ON DELETE CASCADE).
+        let res = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&mut *self.db.conn().await?)
+            .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    async fn access(&self, id: i32) -> Result<UserAccess, AppError> {
+        let mut conn = self.db.conn().await?;
+
+        let roles: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT roles.name
+            FROM user_roles
+            JOIN roles ON roles.id = user_roles.role_id
+            WHERE user_roles.user_id = $1
+            ORDER BY roles.name
+        "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let permissions: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT permissions.name
+            FROM user_roles
+            JOIN role_permissions ON role_permissions.role_id = user_roles.role_id
+            JOIN permissions ON permissions.id = role_permissions.permission_id
+            WHERE user_roles.user_id = $1
+            ORDER BY permissions.name
+        "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(UserAccess {
+            roles: roles.into_iter().map(|(name,)| name).collect(),
+            permissions: permissions.into_iter().map(|(name,)| name).collect(),
+        })
+    }
+
+    async fn list(&self, limit: i64, offset: i64) -> Result<Vec<UserSummary>, AppError> {
+        let mut conn = self.db.conn().await?;
+
+        let users: Vec<(i32, String)> =
+            sqlx::query_as("SELECT id, name FROM users ORDER BY id LIMIT $1 OFFSET $2")
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&mut *conn)
+                .await?;
+
+        let (Some((first, _)), Some((last, _))) = (users.first(), users.last()) else {
+            return Ok(Vec::new());
+        };
+
+        // The page is a contiguous id range, so one query covers its roles.
+        let roles: Vec<(i32, String)> = sqlx::query_as(
+            r#"
+            SELECT user_roles.user_id, roles.name
+            FROM user_roles
+            JOIN roles ON roles.id = user_roles.role_id
+            WHERE user_roles.user_id BETWEEN $1 AND $2
+            ORDER BY roles.name
+        "#,
+        )
+        .bind(first)
+        .bind(last)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(users
+            .into_iter()
+            .map(|(id, name)| UserSummary {
+                id,
+                name,
+                roles: roles
+                    .iter()
+                    .filter(|(user_id, _)| *user_id == id)
+                    .map(|(_, role)| role.clone())
+                    .collect(),
+            })
+            .collect())
+    }
+
+    async fn set_roles(&self, id: i32, roles: &[String]) -> Result<(), AppError> {
+        let mut conn = self.db.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        let mut role_ids = Vec::with_capacity(roles.len());
+        for role in roles {
+            let role_id: Option<(i32,)> = sqlx::query_as("SELECT id FROM roles WHERE name = $1")
+                .bind(role)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            role_ids.push(role_id.ok_or_else(|| unknown_role(role))?.0);
+        }
+
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        for role_id in role_ids {
+            sqlx::query(
+                "INSERT INTO user_roles (user_id, role_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            )
+            .bind(id)
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn email(&self, id: i32) -> Result<UserEmail, AppError> {
+        let (email, verified_at): (Option<String>, Option<i64>) =
+            sqlx::query_as("SELECT email, email_verified_at FROM users WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&mut *self.db.conn().await?)
+                .await?
+                .ok_or_else(|| AppError::not_found("user_not_found", "User not found."))?;
+
+        Ok(UserEmail { email, verified_at })
+    }
+
+    async fn set_email(&self, id: i32, email: Option<&str>) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET email = $2, email_verified_at = NULL WHERE id = $1")
+            .bind(id)
+            .bind(email)
+            .execute(&mut *self.db.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn verify_email(&self, id: i32, email: &str, at: i64) -> Result<bool, AppError> {
+        let res = sqlx::query(
+            r#"
+            UPDATE users SET email_verified_at = COALESCE(email_verified_at, $3)
+            WHERE id = $1 AND email = $2
+        "#,
+        )
+        .bind(id)
+        .bind(email)
+        .bind(at)
+        .execute(&mut *self.db.conn().await?)
         .await
-        .unwrap()
+        .map_err(|err| match err {
+            sqlx::Error::Database(db) if db.is_unique_violation() => email_taken(),
+            err => err.into(),
+        })?;
+
+        Ok(res.rows_affected() == 1)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use dotenvy::dotenv;
-    use sqlx::postgres::PgPoolOptions;
-    use std::env;
+    use crate::config::Config;
+    use crate::contract::repo::item_repo_trait::IItemRepo;
+    use crate::entity::item_entity::ItemCreate;
+    use crate::entity::role_entity::{PERMISSION_MANAGE_USERS, PERMISSION_READ_USERS, ROLE_ADMIN};
+    use crate::repo::item_repo::ItemRepo;
+    use sqlx::{Pool, postgres::PgPoolOptions};
 
     async fn load_pool() -> Pool<Postgres> {
-        dotenv().ok();
-        let db_str = env::var("db_str").unwrap();
+        let config = Config::load().unwrap();
 
         PgPoolOptions::new()
-            .max_connections(5)
-            .connect(&db_str)
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
             .await
             .unwrap()
     }
@@ -98,9 +305,9 @@ mod tests {
     async fn user_repo_exists() {
         let pool = load_pool().await;
 
-        let repo = UserRepo::new(&pool);
+        let repo = UserRepo::new(pool.clone());
 
-        repo.exists(&String::from("test")).await;
+        repo.exists(&String::from("test")).await.unwrap();
     }
 
     #[tokio::test]
@@ -113,18 +320,204 @@ mod tests {
             password: String::from("new_password"),
         };
 
-        let repo = UserRepo::new(&pool);
+        let repo = UserRepo::new(pool.clone());
 
-        repo.register(&new_user).await;
+        repo.register(&new_user).await.unwrap();
 
-        let exists = repo.exists(&String::from("new_user")).await;
+        let exists = repo.exists(&String::from("new_user")).await.unwrap();
 
         assert!(exists, "exists user");
 
+        sqlx::query(
+            "DELETE FROM user_roles WHERE user_id IN (SELECT id FROM users WHERE name = $1)",
+        )
+        .bind(&new_user.name)
+        .execute(&pool)
+        .await
+        .unwrap();
         sqlx::query("DELETE FROM users WHERE name = $1")
             .bind(new_user.name)
             .execute(&pool)
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn user_repo_fetch_missing_user() {
+        let pool = load_pool().await;
+
+        let repo = UserRepo::new(pool.clone());
+
+        let res = repo.fetch_by_name("missing_user").await;
+
+        assert!(matches!(res, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn user_repo_roles() {
+        let pool = load_pool().await;
+        let repo = UserRepo::new(pool.clone());
+
+        let user = UserRegister {
+            name: String::from("roles_user"),
+            password: String::from("roles_user_password"),
+        };
+        repo.register(&user).await.unwrap();
+        let id = repo.fetch_by_name(&user.name).await.unwrap().id;
+
+        assert_eq!(
+            repo.access(id).await.unwrap(),
+            UserAccess {
+                roles: vec![String::from(ROLE_USER)],
+                permissions: Vec::new(),
+            }
+        );
+
+        let roles = vec![String::from(ROLE_USER), String::from(ROLE_ADMIN)];
+        repo.set_roles(id, &roles).await.unwrap();
+
+        let access = repo.access(id).await.unwrap();
+        assert_eq!(access.roles, [ROLE_ADMIN, ROLE_USER]);
+        assert_eq!(
+            access.permissions,
+            [PERMISSION_MANAGE_USERS, PERMISSION_READ_USERS]
+        );
+
+        assert!(matches!(
+            repo.set_roles(id, &[String::from("wizard")]).await,
+            Err(AppError::Validation { .. })
+        ));
+        assert_eq!(
+            repo.access(id).await.unwrap().roles,
+            [ROLE_ADMIN, ROLE_USER]
+        );
+
+        let listed = repo.list(100, 0).await.unwrap();
+        let summary = listed.iter().find(|u| u.id == id).unwrap();
+        assert_eq!(summary.name, user.name);
+        assert_eq!(summary.roles, [ROLE_ADMIN, ROLE_USER]);
+
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn user_repo_email() {
+        let pool = load_pool().await;
+        let repo = UserRepo::new(pool.clone());
+
+        let mut ids = Vec::new();
+        for name in ["email_user", "email_rival"] {
+            repo.register(&UserRegister {
+                name: String::from(name),
+                password: String::from("email_user_password"),
+            })
+            .await
+            .unwrap();
+            ids.push(repo.fetch_by_name(name).await.unwrap().id);
+        }
+
+        assert_eq!(repo.email(ids[0]).await.unwrap(), UserEmail::default());
+
+        repo.set_email(ids[0], Some("nk@example.com"))
+            .await
+            .unwrap();
+        // Unverified addresses claim nothing.
+        repo.set_email(ids[1], Some("nk@example.com"))
+            .await
+            .unwrap();
+
+        assert!(
+            !repo
+                .verify_email(ids[0], "old@example.com", 100)
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.verify_email(ids[0], "nk@example.com", 100)
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.verify_email(ids[0], "nk@example.com", 200)
+                .await
+                .unwrap()
+        );
+        assert_eq!(repo.email(ids[0]).await.unwrap().verified_at, Some(100));
+        assert!(matches!(
+            repo.verify_email(ids[1], "nk@example.com", 300).await,
+            Err(AppError::Conflict { .. })
+        ));
+
+        repo.set_email(ids[0], Some("new@example.com"))
+            .await
+            .unwrap();
+        assert!(!repo.email(ids[0]).await.unwrap().is_verified());
+
+        for id in ids {
+            sqlx::query("DELETE FROM user_roles WHERE user_id = $1")
+                .bind(id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            sqlx::query("DELETE FROM users WHERE id = $1")
+                .bind(id)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn user_repo_rename_and_delete() {
+        let pool = load_pool().await;
+        let repo = UserRepo::new(pool.clone());
+
+        let mut ids = Vec::new();
+        for name in ["rename_user", "rename_rival"] {
+            repo.register(&UserRegister {
+                name: String::from(name),
+                password: String::from("rename_password"),
+            })
+            .await
+            .unwrap();
+            ids.push(repo.fetch_by_name(name).await.unwrap().id);
+        }
+
+        assert!(matches!(
+            repo.rename(ids[0], "rename_rival").await,
+            Err(AppError::Conflict { .. })
+        ));
+        assert!(repo.rename(ids[0], "renamed_user").await.unwrap());
+        assert_eq!(repo.fetch_by_id(ids[0]).await.unwrap().name, "renamed_user");
+
+        let items = ItemRepo::new(pool);
+        let item = items
+            .register(&ItemCreate {
+                name: String::from("owned"),
+                price: 1.0,
+                user_id: ids[0],
+            })
+            .await
+            .unwrap();
+
+        for id in &ids {
+            assert!(repo.delete(*id).await.unwrap());
+        }
+        assert!(!repo.delete(ids[0]).await.unwrap());
+        assert!(!repo.rename(ids[0], "renamed_user").await.unwrap());
+        assert!(items.fetch_by_id(item.id).await.unwrap().is_none());
+    }
 }
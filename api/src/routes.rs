@@ -0,0 +1,60 @@
+use actix_web::{HttpResponse, Responder, get, web};
+
+use crate::{
+    error,
+    handler::{
+        admin_handler, api_key_handler, auth_handler, item_handler, mfa_handler, user_handler,
+    },
+};
+
+#[get("/")]
+async fn hello() -> impl Responder {
+    HttpResponse::Ok().body("Hello world!")
+}
+
+async fn manual_hello() -> impl Responder {
+    HttpResponse::Ok().body("Hey there!")
+}
+
+/// Every route and extractor config, shared by the server and in-process tests.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default().error_handler(error::json_error_handler))
+        .app_data(web::PathConfig::default().error_handler(error::path_error_handler))
+        .app_data(web::QueryConfig::default().error_handler(error::query_error_handler))
+        .service(hello)
+        .service(user_handler::create)
+        .service(user_handler::fetch)
+        .service(user_handler::change_password)
+        .service(user_handler::email)
+        .service(user_handler::set_email)
+        .service(user_handler::resend_verification)
+        .service(user_handler::verify_email)
+        .service(user_handler::update_me)
+        .service(user_handler::delete_me)
+        .service(auth_handler::auth)
+        .service(auth_handler::mfa)
+        .service(auth_handler::forgot)
+        .service(auth_handler::reset)
+        .service(auth_handler::refresh)
+        .service(auth_handler::me)
+        .service(auth_handler::logout)
+        .service(auth_handler::logout_all)
+        .service(auth_handler::sessions)
+        .service(auth_handler::revoke_session)
+        .service(auth_handler::jwks)
+        .service(mfa_handler::enroll)
+        .service(mfa_handler::confirm)
+        .service(api_key_handler::create)
+        .service(api_key_handler::list)
+        .service(api_key_handler::revoke)
+        .service(item_handler::create)
+        .service(item_handler::list)
+        .service(item_handler::fetch)
+        .service(item_handler::replace)
+        .service(item_handler::update)
+        .service(item_handler::remove)
+        .service(admin_handler::list_users)
+        .service(admin_handler::set_roles)
+        .route("/hey", web::get().to(manual_hello))
+        .default_service(web::to(error::route_not_found));
+}
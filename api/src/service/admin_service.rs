@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::{
+        repo::{revocation_repo_trait::IRevocationRepo, user_repo_trait::IUserRepo},
+        service::admin_service_trait::IAdminService,
+    },
+    dto::admin_dto::MAX_PAGE_SIZE,
+    entity::role_entity::{ROLE_ADMIN, UserSummary},
+    error::AppError,
+};
+
+pub struct AdminService<UserRepo: IUserRepo, RevokeRepo: IRevocationRepo> {
+    user_repo: UserRepo,
+    revoke_repo: RevokeRepo,
+}
+
+impl<UserRepo: IUserRepo, RevokeRepo: IRevocationRepo> AdminService<UserRepo, RevokeRepo> {
+    pub fn new(user_repo: UserRepo, revoke_repo: RevokeRepo) -> Self {
+        Self {
+            user_repo,
+            revoke_repo,
+        }
+    }
+}
+
+#[async_trait]
+impl<R: IUserRepo + Sync, V: IRevocationRepo + Sync> IAdminService for AdminService<R, V> {
+    async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<UserSummary>, AppError> {
+        if !(1..=MAX_PAGE_SIZE).contains(&limit) || offset < 0 {
+            return Err(AppError::validation(
+                "invalid_page",
+                &format!("limit must be between 1 and {MAX_PAGE_SIZE} and offset not negative."),
+            ));
+        }
+
+        self.user_repo.list(limit, offset).await
+    }
+
+    async fn set_roles(
+        &self,
+        actor_id: i32,
+        user_id: i32,
+        roles: &[String],
+    ) -> Result<UserSummary, AppError> {
+        let user = self.user_repo.fetch_by_id(user_id).await?;
+
+        // Otherwise the last admin could lock everyone out.
+        if actor_id == user_id && !roles.iter().any(|role| role == ROLE_ADMIN) {
+            return Err(AppError::forbidden(
+                "cannot_demote_self",
+                "Admins cannot remove their own admin role.",
+            ));
+        }
+
+        let mut roles = roles.to_vec();
+        roles.sort();
+        roles.dedup();
+
+        self.user_repo.set_roles(user_id, &roles).await?;
+        self.revoke_repo.bump_token_version(user_id).await?;
+
+        Ok(UserSummary {
+            id: user.id,
+            name: user.name,
+            roles: self.user_repo.access(user_id).await?.roles,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::{role_entity::ROLE_USER, user_entity::UserRegister},
+        repo::memory::{
+            MemoryStore, revocation_repo::InMemoryRevocationRepo, user_repo::InMemoryUserRepo,
+        },
+    };
+
+    async fn service(
+        names: &[&str],
+    ) -> (
+        AdminService<InMemoryUserRepo, InMemoryRevocationRepo>,
+        Vec<i32>,
+    ) {
+        let store = MemoryStore::new();
+        let users = InMemoryUserRepo::new(store.clone());
+        let mut ids = Vec::new();
+
+        for name in names {
+            users
+                .register(&UserRegister {
+                    name: String::from(*name),
+                    password: String::from("hash"),
+                })
+                .await
+                .unwrap();
+            ids.push(users.fetch_by_name(name).await.unwrap().id);
+        }
+
+        (
+            AdminService::new(users, InMemoryRevocationRepo::new(store)),
+            ids,
+        )
+    }
+
+    fn roles(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| String::from(*name)).collect()
+    }
+
+    #[tokio::test]
+    async fn set_roles_replaces_roles_and_revokes_tokens() {
+        let (service, ids) = service(&["root", "nk"]).await;
+        let version = service.revoke_repo.token_version(ids[1]).await.unwrap();
+
+        let summary = service
+            .set_roles(ids[0], ids[1], &roles(&[ROLE_ADMIN, ROLE_USER, ROLE_ADMIN]))
+            .await
+            .unwrap();
+
+        assert_eq!(summary.roles, roles(&[ROLE_ADMIN, ROLE_USER]));
+        assert_eq!(
+            service.revoke_repo.token_version(ids[1]).await.unwrap(),
+            version + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn set_roles_rejects_bad_input() {
+        let (service, ids) = service(&["root"]).await;
+
+        assert!(matches!(
+            service
+                .set_roles(ids[0], ids[0], &roles(&[ROLE_USER]))
+                .await,
+            Err(AppError::Forbidden { .. })
+        ));
+        assert!(matches!(
+            service
+                .set_roles(ids[0], ids[0], &roles(&[ROLE_ADMIN, "wizard"]))
+                .await,
+            Err(AppError::Validation { .. })
+        ));
+        assert!(matches!(
+            service.set_roles(ids[0], 999, &roles(&[ROLE_USER])).await,
+            Err(AppError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_users_pages() {
+        let (service, _) = service(&["a", "b", "c"]).await;
+
+        let page = service.list_users(2, 1).await.unwrap();
+        let names: Vec<_> = page.iter().map(|u| u.name.as_str()).collect();
+
+        assert_eq!(names, ["b", "c"]);
+        assert_eq!(page[0].roles, roles(&[ROLE_USER]));
+        assert!(service.list_users(0, 0).await.is_err());
+        assert!(service.list_users(MAX_PAGE_SIZE + 1, 0).await.is_err());
+        assert!(service.list_users(10, -1).await.is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    contract::{
+        repo::api_key_repo_trait::IApiKeyRepo, service::api_key_service_trait::IApiKeyService,
+    },
+    dto::api_key_dto::ApiKeyDto,
+    entity::api_key_entity::{ApiKeyCreate, ApiKeyCreated, ApiKeyView, SCOPES},
+    error::AppError,
+};
+
+const KEY_PREFIX: &str = "ak_";
+/// How much of a key is stored in the clear: enough to tell keys apart,
+/// far too little to guess the rest.
+const SHOWN_PREFIX_LEN: usize = KEY_PREFIX.len() + 8;
+const MAX_NAME_LEN: usize = 100;
+
+pub struct ApiKeyService<R: IApiKeyRepo> {
+    repo: R,
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn generate_key() -> String {
+    format!("{KEY_PREFIX}{}", hex::encode(rand::random::<[u8; 32]>()))
+}
+
+/// Keys are long and random, so a plain digest is enough, like refresh tokens.
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn validate(dto: &ApiKeyDto) -> Result<Vec<String>, AppError> {
+    let name = dto.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::validation(
+            "invalid_api_key_name",
+            &format!("API key name must be 1 to {MAX_NAME_LEN} characters."),
+        ));
+    }
+
+    if let Some(scope) = dto.scopes.iter().find(|s| !SCOPES.contains(&s.as_str())) {
+        return Err(AppError::validation(
+            "unknown_scope",
+            &format!("Unknown scope `{scope}`."),
+        ));
+    }
+
+    if dto.scopes.is_empty() {
+        return Err(AppError::validation(
+            "missing_scope",
+            "An API key needs at least one scope.",
+        ));
+    }
+
+    if dto.expires_at.is_some_and(|expires_at| expires_at <= now()) {
+        return Err(AppError::validation(
+            "invalid_expiry",
+            "expires_at must be in the future.",
+        ));
+    }
+
+    let mut scopes = dto.scopes.clone();
+    scopes.sort();
+    scopes.dedup();
+
+    Ok(scopes)
+}
+
+impl<R: IApiKeyRepo> ApiKeyService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl<R: IApiKeyRepo + Sync> IApiKeyService for ApiKeyService<R> {
+    async fn create(&self, user_id: i32, dto: &ApiKeyDto) -> Result<ApiKeyCreated, AppError> {
+        let scopes = validate(dto)?;
+        let key = generate_key();
+
+        let created = self
+            .repo
+            .create(&ApiKeyCreate {
+                user_id,
+                name: String::from(dto.name.trim()),
+                prefix: String::from(&key[..SHOWN_PREFIX_LEN]),
+                key_hash: hash_api_key(&key),
+                scopes,
+                created_at: now(),
+                expires_at: dto.expires_at,
+            })
+            .await?;
+
+        Ok(ApiKeyCreated {
+            key,
+            api_key: ApiKeyView::from(&created),
+        })
+    }
+
+    async fn list(&self, user_id: i32) -> Result<Vec<ApiKeyView>, AppError> {
+        Ok(self
+            .repo
+            .list(user_id)
+            .await?
+            .iter()
+            .map(ApiKeyView::from)
+            .collect())
+    }
+
+    async fn revoke(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        if self.repo.delete(user_id, id).await? {
+            Ok(())
+        } else {
+            Err(AppError::not_found(
+                "api_key_not_found",
+                "API key not found.",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo,
+        entity::{api_key_entity::SCOPE_ITEMS_READ, user_entity::UserRegister},
+        repo::memory::{
+            MemoryStore, api_key_repo::InMemoryApiKeyRepo, user_repo::InMemoryUserRepo,
+        },
+    };
+
+    async fn service() -> (ApiKeyService<InMemoryApiKeyRepo>, i32) {
+        let store = MemoryStore::new();
+        let users = InMemoryUserRepo::new(store.clone());
+        users
+            .register(&UserRegister {
+                name: String::from("nk"),
+                password: String::from("hash"),
+            })
+            .await
+            .unwrap();
+        let user_id = users.fetch_by_name("nk").await.unwrap().id;
+
+        (ApiKeyService::new(InMemoryApiKeyRepo::new(store)), user_id)
+    }
+
+    fn dto(name: &str, scopes: &[&str], expires_at: Option<i64>) -> ApiKeyDto {
+        ApiKeyDto {
+            name: String::from(name),
+            scopes: scopes.iter().map(|s| String::from(*s)).collect(),
+            expires_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_stores_only_a_hash() {
+        let (service, user_id) = service().await;
+
+        let created = service
+            .create(
+                user_id,
+                &dto(" ci ", &[SCOPE_ITEMS_READ, SCOPE_ITEMS_READ], None),
+            )
+            .await
+            .unwrap();
+
+        assert!(created.key.starts_with(&created.api_key.prefix));
+        assert_eq!(created.api_key.name, "ci");
+        assert_eq!(created.api_key.scopes, [SCOPE_ITEMS_READ]);
+
+        let stored = service
+            .repo
+            .fetch_by_hash(&hash_api_key(&created.key))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(stored.key_hash, created.key);
+        assert_eq!(service.list(user_id).await.unwrap(), [created.api_key]);
+    }
+
+    #[tokio::test]
+    async fn create_rejects_bad_input() {
+        let (service, user_id) = service().await;
+
+        for (dto, code) in [
+            (dto("  ", &[SCOPE_ITEMS_READ], None), "invalid_api_key_name"),
+            (dto("ci", &[], None), "missing_scope"),
+            (dto("ci", &["root"], None), "unknown_scope"),
+            (
+                dto("ci", &[SCOPE_ITEMS_READ], Some(now() - 1)),
+                "invalid_expiry",
+            ),
+        ] {
+            let err = service.create(user_id, &dto).await.unwrap_err();
+            assert_eq!(err.code(), code);
+        }
+    }
+
+    #[tokio::test]
+    async fn revoke_only_own_keys() {
+        let (service, user_id) = service().await;
+        let created = service
+            .create(user_id, &dto("ci", &[SCOPE_ITEMS_READ], None))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            service.revoke(user_id + 1, created.api_key.id).await,
+            Err(AppError::NotFound { .. })
+        ));
+        service.revoke(user_id, created.api_key.id).await.unwrap();
+        assert!(service.list(user_id).await.unwrap().is_empty());
+    }
+}
@@ -1,257 +1,895 @@
-use std::{
-    env,
-    time::{SystemTime, UNIX_EPOCH},
-};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::{
-    contract::repo::user_repo_trait::IUserRepo,
-    contract::service::auth_service_trait::IAuthService,
+    contract::repo::{
+        api_key_repo_trait::IApiKeyRepo, login_attempt_repo_trait::ILoginAttemptRepo,
+        mfa_repo_trait::IMfaRepo, refresh_token_repo_trait::IRefreshTokenRepo,
+        revocation_repo_trait::IRevocationRepo, session_repo_trait::ISessionRepo,
+        user_repo_trait::IUserRepo,
+    },
+    contract::service::{
+        auth_service_trait::IAuthService,
+        password_hasher_trait::{IPasswordHasher, PasswordCheck},
+    },
     entity::{
-        auth_entity::{AuthResponse, Claims, UserAuth},
+        auth_entity::{
+            AuthOutcome, AuthResponse, Caller, Claims, ClientInfo, MfaChallenge, MfaClaims,
+            UserAuth,
+        },
+        refresh_token_entity::RefreshTokenCreate,
+        session_entity::{SessionCreate, SessionFetched, SessionView},
         user_entity::UserFetched,
     },
-};
-use actix_web::{
-    HttpRequest,
-    http::{StatusCode, header::AUTHORIZATION},
+    error::AppError,
+    service::{
+        api_key_service::hash_api_key, login_throttle::LoginThrottle, mfa_service::use_code,
+        token_keys::TokenKeys,
+    },
 };
 use async_trait::async_trait;
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::jwk::JwkSet;
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+
+const ACCESS_TOKEN_TTL: u64 = 60 * 15;
+const REFRESH_TOKEN_TTL: u64 = 60 * 60 * 24 * 30;
+const MFA_CHALLENGE_TTL: u64 = 60 * 5;
+const MFA_PURPOSE: &str = "mfa";
 
-pub struct AuthService<UserRepo: IUserRepo> {
+pub struct AuthService<
+    UserRepo: IUserRepo,
+    RefreshRepo: IRefreshTokenRepo,
+    RevokeRepo: IRevocationRepo,
+    Hasher: IPasswordHasher,
+    AttemptRepo: ILoginAttemptRepo,
+    MfaRepo: IMfaRepo,
+    ApiKeyRepo: IApiKeyRepo,
+    SessionRepo: ISessionRepo,
+> {
     user_repo: UserRepo,
+    refresh_repo: RefreshRepo,
+    revoke_repo: RevokeRepo,
+    hasher: Hasher,
+    throttle: LoginThrottle<AttemptRepo>,
+    mfa_repo: MfaRepo,
+    api_key_repo: ApiKeyRepo,
+    session_repo: SessionRepo,
+    keys: TokenKeys,
+    /// Verified against when the user does not exist, so that a missing
+    /// account costs as much as a wrong password.
+    dummy_hash: OnceCell<String>,
 }
-fn one_year_exp() -> u64 {
-    let now = SystemTime::now()
+
+fn now() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
-        .as_secs();
+        .as_secs()
+}
 
-    now + 60 * 60 * 24 * 365
+pub(crate) fn random_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    hex::encode(bytes)
+}
+
+pub(crate) fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
 }
-impl<UserRepo: IUserRepo> AuthService<UserRepo> {
-    pub fn new(user_repo: UserRepo) -> Self {
-        Self { user_repo }
-    }
 
-    async fn token_build(&self, user: &UserAuth) -> AuthResponse {
-        let fetch_user = self.user_repo.fetch_by_name(&user.name).await;
+fn invalid_challenge() -> AppError {
+    AppError::unauthorized(
+        "invalid_mfa_challenge",
+        "Invalid or expired two-factor challenge.",
+    )
+}
+
+impl<
+    UserRepo: IUserRepo,
+    RefreshRepo: IRefreshTokenRepo,
+    RevokeRepo: IRevocationRepo,
+    Hasher: IPasswordHasher,
+    AttemptRepo: ILoginAttemptRepo,
+    MfaRepo: IMfaRepo,
+    ApiKeyRepo: IApiKeyRepo,
+    SessionRepo: ISessionRepo,
+>
+    AuthService<
+        UserRepo,
+        RefreshRepo,
+        RevokeRepo,
+        Hasher,
+        AttemptRepo,
+        MfaRepo,
+        ApiKeyRepo,
+        SessionRepo,
+    >
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_repo: UserRepo,
+        refresh_repo: RefreshRepo,
+        revoke_repo: RevokeRepo,
+        hasher: Hasher,
+        throttle: LoginThrottle<AttemptRepo>,
+        mfa_repo: MfaRepo,
+        api_key_repo: ApiKeyRepo,
+        session_repo: SessionRepo,
+        keys: TokenKeys,
+    ) -> Self {
+        Self {
+            user_repo,
+            refresh_repo,
+            revoke_repo,
+            hasher,
+            throttle,
+            mfa_repo,
+            api_key_repo,
+            session_repo,
+            keys,
+            dummy_hash: OnceCell::new(),
+        }
+    }
 
+    async fn token_build(
+        &self,
+        user_id: i32,
+        family: &str,
+        session_id: i32,
+    ) -> Result<AuthResponse, AppError> {
+        let access = self.user_repo.access(user_id).await?;
         let claim = Claims {
-            user_id: fetch_user.id,
-            exp: one_year_exp(),
+            user_id,
+            exp: now() + ACCESS_TOKEN_TTL,
+            jti: random_token(),
+            ver: self.revoke_repo.token_version(user_id).await?,
+            roles: access.roles,
+            perms: access.permissions,
+            sid: Some(session_id),
         };
 
-        let token_sercret = env::var("token_sercret").unwrap_or(String::from("secret"));
+        let token = self.keys.encode(&claim)?;
 
-        let token = encode(
-            &Header::default(),
-            &claim,
-            &EncodingKey::from_secret(token_sercret.as_ref()),
-        )
-        .unwrap();
+        let refresh = random_token();
+
+        self.refresh_repo
+            .register(&RefreshTokenCreate {
+                user_id,
+                token_hash: hash_token(&refresh),
+                family: String::from(family),
+                expires_at: (now() + REFRESH_TOKEN_TTL) as i64,
+            })
+            .await?;
 
-        AuthResponse {
+        Ok(AuthResponse {
             token,
-            refresh: String::from(""),
+            refresh,
+            expires_in: ACCESS_TOKEN_TTL,
+        })
+    }
+
+    fn challenge_build(&self, user_id: i32) -> Result<MfaChallenge, AppError> {
+        let claim = MfaClaims {
+            user_id,
+            exp: now() + MFA_CHALLENGE_TTL,
+            purpose: String::from(MFA_PURPOSE),
+        };
+
+        let challenge_token = self.keys.encode(&claim)?;
+
+        Ok(MfaChallenge {
+            mfa_required: true,
+            challenge_token,
+            expires_in: MFA_CHALLENGE_TTL,
+        })
+    }
+
+    fn challenge_claims(&self, challenge: &str) -> Result<MfaClaims, AppError> {
+        match self.keys.decode::<MfaClaims>(challenge) {
+            Ok(data) if data.claims.purpose == MFA_PURPOSE => Ok(data.claims),
+            _ => Err(invalid_challenge()),
         }
     }
 
-    async fn match_password(&self, user: &UserAuth) -> bool {
-        let fetch_user = self.user_repo.fetch_by_name(&user.name).await;
-        bcrypt::verify(&user.password, &fetch_user.password).unwrap_or(false)
+    /// Upgrades the stored hash in passing when it is no longer current.
+    async fn match_password(&self, user: &UserAuth) -> Result<Option<UserFetched>, AppError> {
+        let fetch_user = match self.user_repo.fetch_by_name(&user.name).await {
+            Err(AppError::NotFound { .. }) => None,
+            fetched => Some(fetched?),
+        };
+
+        let hash = match &fetch_user {
+            Some(fetch_user) => &fetch_user.password,
+            None => {
+                self.dummy_hash
+                    .get_or_try_init(|| async { self.hasher.hash(&random_token()).await })
+                    .await?
+            }
+        };
+
+        let check = self.hasher.verify(&user.password, hash).await?;
+
+        let Some(fetch_user) = fetch_user else {
+            return Ok(None);
+        };
+
+        match check {
+            PasswordCheck::Mismatch => return Ok(None),
+            PasswordCheck::Valid => {}
+            PasswordCheck::NeedsRehash => {
+                let rehashed = self.hasher.hash(&user.password).await?;
+                self.user_repo
+                    .update_password(fetch_user.id, &rehashed)
+                    .await?;
+            }
+        }
+
+        Ok(Some(fetch_user))
     }
-}
 
-#[async_trait(?Send)]
-impl<R: IUserRepo + Sync> IAuthService for AuthService<R> {
-    async fn auth(&self, user: &UserAuth) -> Result<AuthResponse, String> {
-        if !self.user_repo.exists(&user.name).await {
-            Err(String::from("User de not exists."))
-        } else if !self.match_password(user).await {
-            Err(String::from("Miss match password."))
+    async fn claims(&self, token: &str) -> Result<Claims, AppError> {
+        let claim = self.keys.decode::<Claims>(token);
+
+        let Ok(cl) = claim else {
+            return Err(AppError::unauthorized(
+                "invalid_token",
+                "Missing or invalid bearer token.",
+            ));
+        };
+
+        let version = match self.revoke_repo.token_version(cl.claims.user_id).await {
+            Err(AppError::NotFound { .. }) => {
+                return Err(AppError::unauthorized(
+                    "token_revoked",
+                    "Token has been revoked.",
+                ));
+            }
+            version => version?,
+        };
+
+        let session_ended = match cl.claims.sid {
+            Some(sid) => !matches!(
+                self.session_repo.fetch(sid).await?,
+                Some(session) if session.user_id == cl.claims.user_id
+            ),
+            None => false,
+        };
+
+        if session_ended
+            || self.revoke_repo.is_revoked(&cl.claims.jti).await?
+            || version != cl.claims.ver
+        {
+            Err(AppError::unauthorized(
+                "token_revoked",
+                "Token has been revoked.",
+            ))
         } else {
-            Ok(self.token_build(user).await)
+            Ok(cl.claims)
+        }
+    }
+
+    /// Opens a session for a fresh login and issues its first tokens.
+    async fn start_session(
+        &self,
+        user_id: i32,
+        client: &ClientInfo,
+    ) -> Result<AuthResponse, AppError> {
+        let family = random_token();
+        let session: SessionFetched = self
+            .session_repo
+            .create(&SessionCreate {
+                user_id,
+                family: family.clone(),
+                user_agent: client.user_agent.clone(),
+                ip: client.ip.clone(),
+                created_at: now() as i64,
+            })
+            .await?;
+
+        self.token_build(user_id, &family, session.id).await
+    }
+
+    /// Revokes a login's refresh tokens and forgets its session.
+    async fn end_session(&self, family: &str) -> Result<(), AppError> {
+        self.refresh_repo.revoke_family(family).await?;
+
+        if let Some(session) = self.session_repo.fetch_by_family(family).await? {
+            self.session_repo.delete(session.id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<
+    R: IUserRepo + Sync,
+    T: IRefreshTokenRepo + Sync,
+    V: IRevocationRepo + Sync,
+    H: IPasswordHasher + Sync,
+    A: ILoginAttemptRepo + Sync,
+    M: IMfaRepo + Sync,
+    K: IApiKeyRepo + Sync,
+    S: ISessionRepo + Sync,
+> IAuthService for AuthService<R, T, V, H, A, M, K, S>
+{
+    async fn auth(&self, user: &UserAuth, client: &ClientInfo) -> Result<AuthOutcome, AppError> {
+        let ip = client.ip.as_deref();
+        self.throttle.check(&user.name, ip).await?;
+
+        match self.match_password(user).await? {
+            // Failures stay counted until the second factor is passed too.
+            Some(fetch_user)
+                if self
+                    .mfa_repo
+                    .fetch(fetch_user.id)
+                    .await?
+                    .is_some_and(|mfa| mfa.enabled) =>
+            {
+                Ok(AuthOutcome::MfaRequired(
+                    self.challenge_build(fetch_user.id)?,
+                ))
+            }
+            Some(fetch_user) => {
+                self.throttle.succeeded(&user.name).await?;
+                Ok(AuthOutcome::Tokens(
+                    self.start_session(fetch_user.id, client).await?,
+                ))
+            }
+            None => {
+                self.throttle.failed(&user.name, ip).await?;
+                Err(AppError::unauthorized(
+                    "invalid_credentials",
+                    "Invalid username or password.",
+                ))
+            }
         }
     }
 
-    async fn user(&self, req: HttpRequest) -> Result<UserFetched, StatusCode> {
-        let token_sercret = env::var("token_sercret").unwrap_or(String::from("secret"));
-        let claim: Option<_> = req
-            .headers()
-            .get(AUTHORIZATION)
-            .and_then(|h| h.to_str().ok())
-            .and_then(|h| h.strip_prefix("Bearer "))
-            .map(|token| {
-                decode::<Claims>(
-                    token,
-                    &DecodingKey::from_secret(token_sercret.as_ref()),
-                    &Validation::default(),
-                )
-            });
-
-        if let Some(Ok(cl)) = claim {
-            Ok(self.user_repo.fetch_by_id(cl.claims.user_id).await)
+    async fn verify_mfa(
+        &self,
+        challenge: &str,
+        code: &str,
+        client: &ClientInfo,
+    ) -> Result<AuthResponse, AppError> {
+        let ip = client.ip.as_deref();
+        let claims = self.challenge_claims(challenge)?;
+        let user = match self.user_repo.fetch_by_id(claims.user_id).await {
+            Err(AppError::NotFound { .. }) => return Err(invalid_challenge()),
+            fetched => fetched?,
+        };
+
+        self.throttle.check(&user.name, ip).await?;
+
+        let Some(mfa) = self
+            .mfa_repo
+            .fetch(claims.user_id)
+            .await?
+            .filter(|mfa| mfa.enabled)
+        else {
+            return Err(invalid_challenge());
+        };
+
+        if !use_code(&self.mfa_repo, &mfa, code).await? {
+            self.throttle.failed(&user.name, ip).await?;
+            return Err(AppError::unauthorized(
+                "invalid_mfa_code",
+                "Invalid two-factor code.",
+            ));
+        }
+
+        self.throttle.succeeded(&user.name).await?;
+        self.start_session(claims.user_id, client).await
+    }
+
+    async fn refresh(&self, refresh: &str) -> Result<AuthResponse, AppError> {
+        let Some(stored) = self
+            .refresh_repo
+            .fetch_by_hash(&hash_token(refresh))
+            .await?
+        else {
+            return Err(AppError::unauthorized(
+                "invalid_refresh_token",
+                "Invalid refresh token.",
+            ));
+        };
+
+        if stored.revoked {
+            Err(AppError::unauthorized(
+                "refresh_token_revoked",
+                "Refresh token revoked.",
+            ))
+        } else if stored.expires_at < now() as i64 {
+            Err(AppError::unauthorized(
+                "refresh_token_expired",
+                "Refresh token expired.",
+            ))
+        } else if stored.used || !self.refresh_repo.mark_used(stored.id).await? {
+            // A rotated token showing up again means it leaked: kill the whole family.
+            self.refresh_repo.revoke_family(&stored.family).await?;
+            Err(AppError::unauthorized(
+                "refresh_token_reused",
+                "Refresh token reused.",
+            ))
         } else {
-            Err(StatusCode::UNAUTHORIZED)
+            let session = match self.session_repo.fetch_by_family(&stored.family).await? {
+                Some(session) => {
+                    self.session_repo.touch(session.id, now() as i64).await?;
+                    session
+                }
+                // Logged in before sessions were recorded.
+                None => {
+                    self.session_repo
+                        .create(&SessionCreate {
+                            user_id: stored.user_id,
+                            family: stored.family.clone(),
+                            user_agent: None,
+                            ip: None,
+                            created_at: now() as i64,
+                        })
+                        .await?
+                }
+            };
+
+            self.token_build(stored.user_id, &stored.family, session.id)
+                .await
+        }
+    }
+
+    async fn user(&self, token: &str) -> Result<Caller, AppError> {
+        let claims = self.claims(token).await?;
+
+        match self.user_repo.fetch_by_id(claims.user_id).await {
+            Err(AppError::NotFound { .. }) => Err(AppError::unauthorized(
+                "token_revoked",
+                "Token has been revoked.",
+            )),
+            fetched => Ok(Caller {
+                user: fetched?,
+                roles: claims.roles,
+                permissions: claims.perms,
+                scopes: None,
+                session_id: claims.sid,
+            }),
         }
     }
+
+    async fn api_key_user(&self, key: &str) -> Result<Caller, AppError> {
+        let invalid = || AppError::unauthorized("invalid_api_key", "Invalid or expired API key.");
+
+        let stored = self
+            .api_key_repo
+            .fetch_by_hash(&hash_api_key(key))
+            .await?
+            .ok_or_else(invalid)?;
+
+        let now = now() as i64;
+        if stored
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now)
+        {
+            return Err(invalid());
+        }
+
+        self.api_key_repo.touch(stored.id, now).await?;
+
+        // Keys get the owner's current permissions, narrowed to the key's
+        // scopes, and none of the owner's roles: a role can't be narrowed, so
+        // an admin's read-only key must not pass a role guard.
+        let user = self.user_repo.fetch_by_id(stored.user_id).await?;
+        let access = self.user_repo.access(user.id).await?;
+        let scopes = stored.scope_list();
+
+        Ok(Caller {
+            user,
+            roles: Vec::new(),
+            permissions: access
+                .permissions
+                .into_iter()
+                .filter(|permission| scopes.contains(permission))
+                .collect(),
+            scopes: Some(scopes),
+            session_id: None,
+        })
+    }
+
+    async fn logout(&self, token: &str, refresh: Option<&str>) -> Result<(), AppError> {
+        let claims = self.claims(token).await?;
+
+        self.revoke_repo
+            .revoke(&claims.jti, claims.exp as i64)
+            .await?;
+
+        if let Some(sid) = claims.sid
+            && let Some(session) = self.session_repo.fetch(sid).await?
+        {
+            self.end_session(&session.family).await?;
+        }
+
+        if let Some(refresh) = refresh
+            && let Some(stored) = self
+                .refresh_repo
+                .fetch_by_hash(&hash_token(refresh))
+                .await?
+            && stored.user_id == claims.user_id
+        {
+            self.end_session(&stored.family).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn logout_all(&self, token: &str) -> Result<(), AppError> {
+        let claims = self.claims(token).await?;
+
+        self.revoke_repo.bump_token_version(claims.user_id).await?;
+        self.refresh_repo.revoke_user(claims.user_id).await?;
+        self.session_repo.delete_user(claims.user_id).await
+    }
+
+    async fn sessions(&self, token: &str) -> Result<Vec<SessionView>, AppError> {
+        let claims = self.claims(token).await?;
+        let seen_since = now().saturating_sub(REFRESH_TOKEN_TTL) as i64;
+
+        Ok(self
+            .session_repo
+            .list(claims.user_id, seen_since)
+            .await?
+            .into_iter()
+            .map(|session| SessionView {
+                current: claims.sid == Some(session.id),
+                id: session.id,
+                user_agent: session.user_agent,
+                ip: session.ip,
+                created_at: session.created_at,
+                last_seen_at: session.last_seen_at,
+            })
+            .collect())
+    }
+
+    async fn revoke_session(&self, token: &str, id: i32) -> Result<(), AppError> {
+        let claims = self.claims(token).await?;
+
+        let session = self
+            .session_repo
+            .fetch(id)
+            .await?
+            .filter(|session| session.user_id == claims.user_id)
+            .ok_or_else(|| AppError::not_found("session_not_found", "Session not found."))?;
+
+        self.end_session(&session.family).await
+    }
+
+    fn jwks(&self) -> JwkSet {
+        self.keys.jwks().clone()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use actix_web::http::header;
     use jsonwebtoken::{EncodingKey, Header, encode};
     use serde::{Deserialize, Serialize};
 
-    use crate::entity::user_entity::UserRegister;
+    use crate::entity::{
+        api_key_entity::{ApiKeyCreate, SCOPE_ITEMS_READ},
+        role_entity::{PERMISSION_READ_USERS, ROLE_ADMIN},
+        user_entity::UserRegister,
+    };
 
     use super::*;
-    use async_trait::async_trait;
+    use crate::{
+        entity::mfa_entity::MfaFetched,
+        repo::memory::{
+            MemoryStore, api_key_repo::InMemoryApiKeyRepo,
+            login_attempt_repo::InMemoryLoginAttemptRepo, mfa_repo::InMemoryMfaRepo,
+            refresh_token_repo::InMemoryRefreshTokenRepo, revocation_repo::InMemoryRevocationRepo,
+            session_repo::InMemorySessionRepo, user_repo::InMemoryUserRepo,
+        },
+        service::{
+            login_throttle::LoginThrottling,
+            password_hasher::{PasswordHasher, fast_settings},
+            totp,
+        },
+    };
     use bcrypt::{DEFAULT_COST, hash};
 
-    struct MockUserRepo<'a> {
-        mock_exists: bool,
-        fetch_user: Option<&'a UserFetched>,
-    }
+    const TEST_SECRET: &str = "test_secret";
 
-    #[async_trait]
-    impl IUserRepo for MockUserRepo<'_> {
-        async fn exists(&self, _: &str) -> bool {
-            self.mock_exists
-        }
+    type MemoryAuthService = AuthService<
+        InMemoryUserRepo,
+        InMemoryRefreshTokenRepo,
+        InMemoryRevocationRepo,
+        PasswordHasher,
+        InMemoryLoginAttemptRepo,
+        InMemoryMfaRepo,
+        InMemoryApiKeyRepo,
+        InMemorySessionRepo,
+    >;
 
-        async fn password_hash(&self, _: &str) -> String {
-            String::from("pass")
-        }
+    /// Every repo shares `store`.
+    fn store_service(store: MemoryStore, throttling: LoginThrottling) -> MemoryAuthService {
+        AuthService::new(
+            InMemoryUserRepo::new(store.clone()),
+            InMemoryRefreshTokenRepo::new(store.clone()),
+            InMemoryRevocationRepo::new(store.clone()),
+            PasswordHasher::new(fast_settings()),
+            LoginThrottle::new(InMemoryLoginAttemptRepo::new(store.clone()), throttling),
+            InMemoryMfaRepo::new(store.clone()),
+            InMemoryApiKeyRepo::new(store.clone()),
+            InMemorySessionRepo::new(store),
+            TokenKeys::hmac(TEST_SECRET),
+        )
+    }
 
-        async fn register(&self, _: &UserRegister) {
-            todo!()
-        }
+    fn memory_service() -> MemoryAuthService {
+        store_service(MemoryStore::new(), LoginThrottling::default())
+    }
 
-        async fn fetch_by_name(&self, _: &str) -> UserFetched {
-            let user = self.fetch_user.unwrap();
-            UserFetched {
-                id: 123,
-                name: user.name.clone(),
-                password: user.password.clone(),
-            }
+    /// Stores `nk` with `password` as its hash and returns the new id.
+    async fn add_user(service: &MemoryAuthService, password: String) -> i32 {
+        service
+            .user_repo
+            .register(&UserRegister {
+                name: String::from("nk"),
+                password,
+            })
+            .await
+            .unwrap();
+        service.user_repo.fetch_by_name("nk").await.unwrap().id
+    }
+
+    fn client_from(ip: &str) -> ClientInfo {
+        ClientInfo {
+            ip: Some(String::from(ip)),
+            ..Default::default()
         }
+    }
 
-        async fn fetch_by_id(&self, _: i32) -> UserFetched {
-            UserFetched {
-                id: 1,
-                name: String::from("name"),
-                password: String::from("password"),
-            }
+    async fn tokens(service: &MemoryAuthService, dto: &UserAuth) -> AuthResponse {
+        match service.auth(dto, &ClientInfo::default()).await.unwrap() {
+            AuthOutcome::Tokens(tokens) => tokens,
+            AuthOutcome::MfaRequired(_) => panic!("expected tokens"),
         }
     }
 
     #[tokio::test]
     async fn auth_error_when_user_do_not_exists() {
-        let mock_repo = MockUserRepo {
-            mock_exists: false,
-            fetch_user: None,
+        let service = memory_service();
+
+        let dto = UserAuth {
+            name: String::from("nk"),
+            password: String::from("123"),
         };
 
-        let service = AuthService::new(mock_repo);
+        let res: Result<_, _> = service.auth(&dto, &ClientInfo::default()).await;
+
+        assert!(res.is_err(), "User does not exist.");
+    }
 
+    #[tokio::test]
+    async fn unknown_user_and_wrong_password_look_alike() {
         let dto = UserAuth {
             name: String::from("nk"),
             password: String::from("123"),
         };
 
-        let res: Result<_, _> = service.auth(&dto).await;
+        let missing = memory_service()
+            .auth(&dto, &ClientInfo::default())
+            .await
+            .unwrap_err();
+        let service = memory_service();
+        add_user(&service, hash("456", 4).unwrap()).await;
+        let mismatch = service
+            .auth(&dto, &ClientInfo::default())
+            .await
+            .unwrap_err();
 
-        assert!(res.is_err(), "Not created because it already exists.");
+        assert_eq!(missing.code(), "invalid_credentials");
+        assert_eq!(missing.code(), mismatch.code());
+        assert_eq!(missing.to_string(), mismatch.to_string());
     }
 
-    fn template_service() -> AuthService<MockUserRepo<'static>> {
-        let mock_repo = MockUserRepo {
-            mock_exists: true,
-            fetch_user: None,
+    #[tokio::test]
+    async fn repeated_failures_lock_the_account() {
+        let service = store_service(
+            MemoryStore::new(),
+            LoginThrottling {
+                account_free_attempts: 2,
+                base_delay_secs: 60,
+                ..Default::default()
+            },
+        );
+        add_user(&service, hash("123", 4).unwrap()).await;
+        let wrong = UserAuth {
+            name: String::from("nk"),
+            password: String::from("456"),
         };
-        AuthService::new(mock_repo)
+
+        for _ in 0..3 {
+            let res = service.auth(&wrong, &client_from("10.0.0.1")).await;
+            assert!(matches!(res, Err(AppError::Unauthorized { .. })));
+        }
+
+        let right = UserAuth {
+            password: String::from("123"),
+            ..wrong
+        };
+        let res = service.auth(&right, &client_from("10.0.0.2")).await;
+
+        assert!(matches!(res, Err(AppError::TooManyRequests { .. })));
     }
 
     #[tokio::test]
-    async fn wrong_password() {
-        let fetch_user = UserFetched {
-            id: 1,
+    async fn mfa_challenge_then_code() {
+        let store = MemoryStore::new();
+        let service = store_service(store.clone(), LoginThrottling::default());
+        let user_id = add_user(&service, hash("123", 4).unwrap()).await;
+        let secret = totp::generate_secret();
+        store.tables().await.user_mfa.insert(
+            user_id,
+            MfaFetched {
+                user_id,
+                secret: secret.clone(),
+                enabled: true,
+                last_step: 0,
+            },
+        );
+        let dto = UserAuth {
             name: String::from("nk"),
-            password: String::from("456"),
+            password: String::from("123"),
         };
 
-        let mock_repo = MockUserRepo {
-            mock_exists: true,
-            fetch_user: Some(&fetch_user),
+        let AuthOutcome::MfaRequired(challenge) =
+            service.auth(&dto, &ClientInfo::default()).await.unwrap()
+        else {
+            panic!("expected an mfa challenge");
         };
+        let challenge = challenge.challenge_token;
 
-        let service = AuthService::new(mock_repo);
+        assert!(
+            service.user(&challenge).await.is_err(),
+            "a challenge is not an access token"
+        );
+
+        let res = service
+            .verify_mfa(&challenge, "000000x", &ClientInfo::default())
+            .await;
+        assert_eq!(res.unwrap_err().code(), "invalid_mfa_code");
+
+        let code = totp::code_for(&secret, now()).unwrap();
+        let auth = service
+            .verify_mfa(&challenge, &code, &ClientInfo::default())
+            .await
+            .unwrap();
+        assert!(service.user(&auth.token).await.is_ok());
+
+        let res = service
+            .verify_mfa(&challenge, &code, &ClientInfo::default())
+            .await;
+        assert!(res.is_err(), "codes can't be replayed");
+    }
+
+    #[tokio::test]
+    async fn access_token_is_not_a_challenge() {
+        let (service, auth) = logged_service().await;
+
+        let res = service
+            .verify_mfa(&auth.token, "000000", &ClientInfo::default())
+            .await;
+
+        assert_eq!(res.unwrap_err().code(), "invalid_mfa_challenge");
+    }
+
+    #[tokio::test]
+    async fn wrong_password() {
+        let service = memory_service();
+        add_user(&service, String::from("456")).await;
 
         let dto = UserAuth {
             name: String::from("nk"),
             password: String::from("123"),
         };
 
-        let res: Result<_, _> = service.auth(&dto).await;
+        let res: Result<_, _> = service.auth(&dto, &ClientInfo::default()).await;
 
-        assert!(res.is_err(), "Miss match password");
+        assert!(res.is_err(), "Password does not match.");
     }
 
     #[tokio::test]
     async fn auth_api_fetch_token() {
-        let hash_password = hash("123", DEFAULT_COST).unwrap();
+        let service = memory_service();
+        add_user(&service, hash("123", DEFAULT_COST).unwrap()).await;
 
-        let fetch_user = UserFetched {
-            id: 1,
+        let dto = UserAuth {
             name: String::from("nk"),
-            password: hash_password,
+            password: String::from("123"),
         };
 
-        let mock_repo = MockUserRepo {
-            mock_exists: true,
-            fetch_user: Some(&fetch_user),
-        };
+        let auth_token = tokens(&service, &dto).await;
+
+        assert!(!auth_token.token.is_empty());
+        assert!(!auth_token.refresh.is_empty());
+    }
+
+    #[tokio::test]
+    async fn auth_rehashes_legacy_hash() {
+        let (service, _) = logged_service().await;
 
-        let service = AuthService::new(mock_repo);
+        let rehashed = service
+            .user_repo
+            .fetch_by_name("nk")
+            .await
+            .unwrap()
+            .password;
+
+        assert!(rehashed.starts_with("$argon2id$"));
+        assert_eq!(
+            service.hasher.verify("123", &rehashed).await.unwrap(),
+            PasswordCheck::Valid
+        );
+    }
+
+    async fn logged_service() -> (MemoryAuthService, AuthResponse) {
+        let service = memory_service();
+        add_user(&service, hash("123", DEFAULT_COST).unwrap()).await;
 
         let dto = UserAuth {
             name: String::from("nk"),
             password: String::from("123"),
         };
 
-        let res: Result<_, _> = service.auth(&dto).await;
+        let auth = tokens(&service, &dto).await;
+
+        (service, auth)
+    }
 
-        assert!(res.is_ok(), "Match password");
+    #[tokio::test]
+    async fn refresh_rotates_token() {
+        let (service, auth) = logged_service().await;
 
-        let auth_token = res.unwrap();
+        let res = service.refresh(&auth.refresh).await;
 
-        assert!(!auth_token.token.is_empty());
+        assert!(res.is_ok());
+
+        let rotated = res.unwrap();
+
+        assert_ne!(rotated.refresh, auth.refresh);
+        assert!(service.refresh(&rotated.refresh).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn refresh_unknown_token() {
+        let (service, _) = logged_service().await;
+
+        assert!(service.refresh("unknown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn refresh_reuse_revokes_family() {
+        let (service, auth) = logged_service().await;
+
+        let rotated = service.refresh(&auth.refresh).await.unwrap();
+
+        assert!(service.refresh(&auth.refresh).await.is_err());
+        assert!(service.refresh(&rotated.refresh).await.is_err());
     }
     #[tokio::test]
     async fn auth_middeware_no_token() {
-        let req = actix_web::test::TestRequest::default().to_http_request();
-        let service = template_service();
-        let res = service.user(req).await;
+        let service = memory_service();
+        let res = service.user("").await;
 
         assert!(res.is_err());
 
-        assert_eq!(res.err().unwrap(), StatusCode::UNAUTHORIZED);
+        assert!(matches!(res, Err(AppError::Unauthorized { .. })));
     }
 
     #[tokio::test]
     async fn auth_middeware_not_valid_jwt_token() {
-        let req = actix_web::test::TestRequest::default()
-            .insert_header((header::AUTHORIZATION, "invalid token"))
-            .to_http_request();
-
-        let service = template_service();
-        let res = service.user(req).await;
+        let service = memory_service();
+        let res = service.user("invalid token").await;
 
         assert!(res.is_err());
 
-        assert_eq!(res.err().unwrap(), StatusCode::UNAUTHORIZED);
+        assert!(matches!(res, Err(AppError::Unauthorized { .. })));
     }
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -261,55 +899,165 @@ mod tests {
     async fn auth_middeware_not_rigth_clames() {
         let claim = WrongClaim {};
 
-        let token_sercret = env::var("token_sercret").unwrap_or(String::from("secret"));
-
         let token = encode(
             &Header::default(),
             &claim,
-            &EncodingKey::from_secret(token_sercret.as_ref()),
+            &EncodingKey::from_secret(TEST_SECRET.as_ref()),
         )
         .unwrap();
-        let req = actix_web::test::TestRequest::default()
-            .insert_header((header::AUTHORIZATION, token))
-            .to_http_request();
 
-        let service = template_service();
-        let res = service.user(req).await;
+        let service = memory_service();
+        let res = service.user(&token).await;
 
         assert!(res.is_err());
 
-        assert_eq!(res.err().unwrap(), StatusCode::UNAUTHORIZED);
+        assert!(matches!(res, Err(AppError::Unauthorized { .. })));
     }
 
     #[tokio::test]
     async fn auth_middeware_rigth_clames() {
+        let service = memory_service();
+        let user_id = add_user(&service, String::from("hash")).await;
         let claim = Claims {
-            user_id: 123,
-            exp: one_year_exp(),
+            user_id,
+            exp: now() + ACCESS_TOKEN_TTL,
+            jti: random_token(),
+            ver: 0,
+            roles: vec![String::from(ROLE_ADMIN)],
+            perms: vec![String::from(PERMISSION_READ_USERS)],
+            sid: None,
         };
 
-        let token_sercret = env::var("token_sercret").unwrap_or(String::from("secret"));
-
         let token = encode(
             &Header::default(),
             &claim,
-            &EncodingKey::from_secret(token_sercret.as_ref()),
+            &EncodingKey::from_secret(TEST_SECRET.as_ref()),
         )
         .unwrap();
 
-        let token = format!("Bearer {}", token);
+        let res = service.user(&token).await;
+
+        assert!(res.is_ok());
+
+        if let Ok(res) = res {
+            assert_eq!(res.user.id, user_id);
+            assert_eq!(res.roles, [ROLE_ADMIN]);
+            assert_eq!(res.permissions, [PERMISSION_READ_USERS]);
+        }
+    }
 
-        let req = actix_web::test::TestRequest::default()
-            .insert_header((header::AUTHORIZATION, token))
-            .to_http_request();
+    #[tokio::test]
+    async fn logout_revokes_access_token() {
+        let (service, auth) = logged_service().await;
 
-        let service = template_service();
-        let res = service.user(req).await;
+        assert!(service.user(&auth.token).await.is_ok());
+
+        let res = service.logout(&auth.token, Some(&auth.refresh)).await;
 
         assert!(res.is_ok());
 
-        if let Ok(res) = res {
-            assert_eq!(res.id, 1)
+        let res = service.user(&auth.token).await;
+
+        assert!(matches!(res, Err(AppError::Unauthorized { .. })));
+        assert!(service.refresh(&auth.refresh).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn logout_all_revokes_every_session() {
+        let (service, first) = logged_service().await;
+
+        let dto = UserAuth {
+            name: String::from("nk"),
+            password: String::from("123"),
+        };
+
+        let second = tokens(&service, &dto).await;
+
+        assert!(service.logout_all(&first.token).await.is_ok());
+
+        assert!(service.user(&first.token).await.is_err());
+        assert!(service.user(&second.token).await.is_err());
+        assert!(service.refresh(&second.refresh).await.is_err());
+
+        let third = tokens(&service, &dto).await;
+
+        assert!(service.user(&third.token).await.is_ok());
+        assert_eq!(service.sessions(&third.token).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn revoke_session_signs_out_one_login() {
+        let (service, first) = logged_service().await;
+
+        let dto = UserAuth {
+            name: String::from("nk"),
+            password: String::from("123"),
+        };
+
+        let second = tokens(&service, &dto).await;
+
+        let sessions = service.sessions(&first.token).await.unwrap();
+
+        assert_eq!(sessions.len(), 2);
+
+        let other = sessions.iter().find(|s| !s.current).unwrap();
+
+        assert!(service.revoke_session(&first.token, other.id).await.is_ok());
+
+        assert!(service.user(&second.token).await.is_err());
+        assert!(service.refresh(&second.refresh).await.is_err());
+        assert!(service.user(&first.token).await.is_ok());
+
+        let res = service.revoke_session(&first.token, other.id).await;
+
+        assert!(matches!(res, Err(AppError::NotFound { .. })));
+    }
+
+    #[tokio::test]
+    async fn api_key_user_rejects_expired_keys() {
+        let store = MemoryStore::new();
+        let service = store_service(store.clone(), LoginThrottling::default());
+        let user_id = add_user(&service, String::from("hash")).await;
+        let keys = InMemoryApiKeyRepo::new(store);
+        for (key, expires_at) in [("ak_live", None), ("ak_expired", Some(1))] {
+            keys.create(&ApiKeyCreate {
+                user_id,
+                name: String::from(key),
+                prefix: String::from(key),
+                key_hash: hash_api_key(key),
+                scopes: vec![
+                    String::from(PERMISSION_READ_USERS),
+                    String::from(SCOPE_ITEMS_READ),
+                ],
+                created_at: 0,
+                expires_at,
+            })
+            .await
+            .unwrap();
+        }
+
+        let caller = service.api_key_user("ak_live").await.unwrap();
+        assert_eq!(
+            caller.scopes.unwrap(),
+            [PERMISSION_READ_USERS, SCOPE_ITEMS_READ]
+        );
+        // The owner lacks `users:read`, so the scope grants nothing.
+        assert!(caller.permissions.is_empty());
+        assert!(caller.roles.is_empty());
+        assert!(
+            keys.fetch_by_hash(&hash_api_key("ak_live"))
+                .await
+                .unwrap()
+                .unwrap()
+                .last_used_at
+                .is_some()
+        );
+
+        for key in ["ak_expired", "ak_unknown"] {
+            assert!(matches!(
+                service.api_key_user(key).await,
+                Err(AppError::Unauthorized { .. })
+            ));
         }
     }
 }
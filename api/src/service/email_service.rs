@@ -0,0 +1,322 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use lettre::Address;
+
+use crate::{
+    contract::{
+        repo::{login_attempt_repo_trait::ILoginAttemptRepo, user_repo_trait::IUserRepo},
+        service::{email_service_trait::IEmailService, mailer_trait::IMailer},
+    },
+    entity::{
+        mail_entity::Mail,
+        user_entity::{EmailClaims, UserEmail},
+    },
+    error::AppError,
+    service::{login_throttle::LoginThrottle, token_keys::TokenKeys},
+};
+
+const VERIFICATION_TTL: u64 = 60 * 60 * 24;
+const VERIFY_EMAIL_PURPOSE: &str = "verify_email";
+/// The longest address SMTP can carry.
+const MAX_EMAIL_LEN: usize = 254;
+
+pub struct EmailService<UserRepo: IUserRepo, Mailer: IMailer, AttemptRepo: ILoginAttemptRepo> {
+    user_repo: UserRepo,
+    mailer: Mailer,
+    keys: TokenKeys,
+    /// Verification links point here.
+    public_url: String,
+    /// Counts every mail sent, per user and per target address.
+    throttle: LoginThrottle<AttemptRepo>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn invalid_link() -> AppError {
+    AppError::validation(
+        "invalid_verification_token",
+        "Verification link is invalid or has expired.",
+    )
+}
+
+/// Lowercased, so the unique index on verified addresses treats them
+/// case-insensitively.
+fn normalize(email: &str) -> Result<String, AppError> {
+    let email = email.trim().to_lowercase();
+
+    if email.len() > MAX_EMAIL_LEN || email.parse::<Address>().is_err() {
+        return Err(AppError::validation(
+            "invalid_email",
+            "Not a valid email address.",
+        ));
+    }
+
+    Ok(email)
+}
+
+impl<UserRepo: IUserRepo, Mailer: IMailer, AttemptRepo: ILoginAttemptRepo>
+    EmailService<UserRepo, Mailer, AttemptRepo>
+{
+    pub fn new(
+        user_repo: UserRepo,
+        mailer: Mailer,
+        keys: TokenKeys,
+        public_url: &str,
+        throttle: LoginThrottle<AttemptRepo>,
+    ) -> Self {
+        Self {
+            user_repo,
+            mailer,
+            keys,
+            public_url: String::from(public_url.trim_end_matches('/')),
+            throttle,
+        }
+    }
+
+    /// Counts a mail to `email` for `user_id`, or fails if either is locked.
+    async fn count_mail(&self, user_id: i32, email: &str) -> Result<(), AppError> {
+        let user = user_id.to_string();
+
+        self.throttle.check(&user, Some(email)).await?;
+        self.throttle.failed(&user, Some(email)).await
+    }
+
+    async fn send_link(&self, user_id: i32, email: &str) -> Result<(), AppError> {
+        let user = self.user_repo.fetch_by_id(user_id).await?;
+        let token = self.keys.encode(&EmailClaims {
+            user_id,
+            email: String::from(email),
+            exp: now() + VERIFICATION_TTL,
+            purpose: String::from(VERIFY_EMAIL_PURPOSE),
+        })?;
+        let link = format!("{}/user/email/verify?token={token}", self.public_url);
+
+        self.mailer
+            .send(&Mail {
+                to: String::from(email),
+                subject: String::from("Verify your email address"),
+                body: format!(
+                    "Hi {},\n\n\
+                     Open this link within 24 hours to confirm this is your address:\n\n\
+                     {link}\n\n\
+                     If you didn't ask for this, you can ignore this email.\n",
+                    user.name
+                ),
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl<R: IUserRepo + Sync, M: IMailer + Sync, A: ILoginAttemptRepo + Sync> IEmailService
+    for EmailService<R, M, A>
+{
+    async fn email(&self, user_id: i32) -> Result<UserEmail, AppError> {
+        self.user_repo.email(user_id).await
+    }
+
+    async fn set_email(&self, user_id: i32, email: &str) -> Result<(), AppError> {
+        let email = normalize(email)?;
+
+        self.count_mail(user_id, &email).await?;
+        self.user_repo.set_email(user_id, Some(&email)).await?;
+        self.send_link(user_id, &email).await
+    }
+
+    async fn resend(&self, user_id: i32) -> Result<(), AppError> {
+        let current = self.user_repo.email(user_id).await?;
+
+        if current.is_verified() {
+            return Err(AppError::conflict(
+                "email_already_verified",
+                "This email address is already verified.",
+            ));
+        }
+        let Some(email) = current.email else {
+            return Err(AppError::validation(
+                "email_missing",
+                "Set an email address first.",
+            ));
+        };
+
+        self.count_mail(user_id, &email).await?;
+        self.send_link(user_id, &email).await
+    }
+
+    async fn verify(&self, token: &str) -> Result<(), AppError> {
+        let claims = match self.keys.decode::<EmailClaims>(token) {
+            Ok(data) if data.claims.purpose == VERIFY_EMAIL_PURPOSE => data.claims,
+            _ => return Err(invalid_link()),
+        };
+
+        if self
+            .user_repo
+            .verify_email(claims.user_id, &claims.email, now() as i64)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(invalid_link())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        entity::user_entity::UserRegister,
+        repo::memory::{
+            MemoryStore, login_attempt_repo::InMemoryLoginAttemptRepo, user_repo::InMemoryUserRepo,
+        },
+        service::{
+            login_throttle::{LoginThrottling, Throttled},
+            mailer::InMemoryMailer,
+        },
+    };
+
+    type Service = EmailService<InMemoryUserRepo, InMemoryMailer, InMemoryLoginAttemptRepo>;
+
+    async fn service() -> (Service, InMemoryMailer, i32) {
+        let store = MemoryStore::new();
+        let users = InMemoryUserRepo::new(store.clone());
+        users
+            .register(&UserRegister {
+                name: String::from("nk"),
+                password: String::from("hash"),
+            })
+            .await
+            .unwrap();
+        let user_id = users.fetch_by_name("nk").await.unwrap().id;
+        let mailer = InMemoryMailer::default();
+
+        let service = EmailService::new(
+            users,
+            mailer.clone(),
+            TokenKeys::hmac("test_secret"),
+            "https://api.example.com/",
+            LoginThrottle::counting(
+                Throttled::VerificationMail,
+                InMemoryLoginAttemptRepo::new(store),
+                LoginThrottling::default(),
+            ),
+        );
+
+        (service, mailer, user_id)
+    }
+
+    fn token(mail: &Mail) -> &str {
+        let (_, rest) = mail.body.split_once("?token=").unwrap();
+        rest.split_whitespace().next().unwrap()
+    }
+
+    #[tokio::test]
+    async fn link_verifies_only_the_current_address() {
+        let (service, mailer, user_id) = service().await;
+
+        service
+            .set_email(user_id, " NK@Example.com ")
+            .await
+            .unwrap();
+        service.set_email(user_id, "new@example.com").await.unwrap();
+
+        let sent = mailer.sent();
+        assert_eq!(sent[0].to, "nk@example.com");
+        assert!(
+            sent[1]
+                .body
+                .contains("https://api.example.com/user/email/verify?token=")
+        );
+
+        let res = service.verify(token(&sent[0])).await;
+        assert!(matches!(res, Err(AppError::Validation { .. })));
+        assert!(!service.email(user_id).await.unwrap().is_verified());
+
+        service.verify(token(&sent[1])).await.unwrap();
+        assert!(service.email(user_id).await.unwrap().is_verified());
+
+        let res = service.resend(user_id).await;
+        assert!(matches!(res, Err(AppError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn setting_an_address_reserves_nothing() {
+        let (service, mailer, owner) = service().await;
+        service
+            .user_repo
+            .register(&UserRegister {
+                name: String::from("squatter"),
+                password: String::from("hash"),
+            })
+            .await
+            .unwrap();
+        let squatter = service
+            .user_repo
+            .fetch_by_name("squatter")
+            .await
+            .unwrap()
+            .id;
+
+        service.set_email(squatter, "nk@example.com").await.unwrap();
+        service.set_email(owner, "nk@example.com").await.unwrap();
+
+        let sent = mailer.sent();
+        service.verify(token(&sent[1])).await.unwrap();
+        assert!(service.email(owner).await.unwrap().is_verified());
+
+        let res = service.verify(token(&sent[0])).await;
+        assert!(matches!(res, Err(AppError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_addresses_and_tokens() {
+        let (service, mailer, user_id) = service().await;
+
+        let res = service.set_email(user_id, "not-an-address").await;
+        assert!(matches!(res, Err(AppError::Validation { .. })));
+
+        let res = service.resend(user_id).await;
+        assert!(matches!(res, Err(AppError::Validation { .. })));
+
+        let res = service.verify("garbage").await;
+        assert!(matches!(res, Err(AppError::Validation { .. })));
+        assert!(mailer.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn mail_is_throttled_per_user_and_address() {
+        let (service, mailer, user_id) = service().await;
+        let free = LoginThrottling::default().account_free_attempts;
+
+        // The free mails go out, and the one after them starts the lockout.
+        for _ in 0..=free {
+            service.set_email(user_id, "nk@example.com").await.unwrap();
+        }
+        let res = service.resend(user_id).await;
+        assert!(matches!(
+            res,
+            Err(AppError::TooManyRequests { retry_after, .. }) if retry_after > 0
+        ));
+
+        service
+            .user_repo
+            .register(&UserRegister {
+                name: String::from("other"),
+                password: String::from("hash"),
+            })
+            .await
+            .unwrap();
+        let other = service.user_repo.fetch_by_name("other").await.unwrap().id;
+
+        let res = service.set_email(other, "NK@example.com").await;
+        assert!(matches!(res, Err(AppError::TooManyRequests { .. })));
+        service.set_email(other, "other@example.com").await.unwrap();
+        assert_eq!(mailer.sent().len(), free as usize + 2);
+    }
+}
@@ -0,0 +1,266 @@
+use async_trait::async_trait;
+
+use crate::{
+    contract::{repo::item_repo_trait::IItemRepo, service::item_service_trait::IItemService},
+    entity::item_entity::{ItemCreate, ItemFetched, ItemUpdate},
+    error::AppError,
+};
+
+pub struct ItemService<R: IItemRepo> {
+    repo: R,
+}
+
+impl<R: IItemRepo> ItemService<R> {
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    /// Only the owner of an item may change it.
+    async fn authorize(&self, user_id: i32, id: i32) -> Result<ItemFetched, AppError> {
+        let item = self.repo.fetch_by_id(id).await?.ok_or_else(not_found)?;
+
+        if item.user_id == user_id {
+            Ok(item)
+        } else {
+            Err(AppError::forbidden(
+                "item_forbidden",
+                "Only the owner can change this item.",
+            ))
+        }
+    }
+}
+
+fn not_found() -> AppError {
+    AppError::not_found("item_not_found", "Item not found.")
+}
+
+fn invalid_item() -> AppError {
+    AppError::validation(
+        "invalid_item",
+        "Item name must not be empty and price must be a non-negative number.",
+    )
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.trim().is_empty()
+}
+
+fn valid_price(price: f64) -> bool {
+    price.is_finite() && price >= 0.0
+}
+
+#[async_trait]
+impl<R: IItemRepo + Sync> IItemService for ItemService<R> {
+    async fn create(&self, item: &ItemCreate) -> Result<ItemFetched, AppError> {
+        if !valid_name(&item.name) || !valid_price(item.price) {
+            Err(invalid_item())
+        } else {
+            self.repo.register(item).await
+        }
+    }
+
+    async fn fetch(&self, id: i32) -> Result<ItemFetched, AppError> {
+        self.repo.fetch_by_id(id).await?.ok_or_else(not_found)
+    }
+
+    async fn list(&self, user_id: i32) -> Result<Vec<ItemFetched>, AppError> {
+        self.repo.fetch_by_user(user_id).await
+    }
+
+    async fn update(
+        &self,
+        user_id: i32,
+        id: i32,
+        item: &ItemUpdate,
+    ) -> Result<ItemFetched, AppError> {
+        self.authorize(user_id, id).await?;
+
+        if !item.name.as_deref().is_none_or(valid_name) || !item.price.is_none_or(valid_price) {
+            Err(invalid_item())
+        } else {
+            self.repo.update(id, item).await?.ok_or_else(not_found)
+        }
+    }
+
+    async fn delete(&self, user_id: i32, id: i32) -> Result<(), AppError> {
+        self.authorize(user_id, id).await?;
+
+        if self.repo.delete(id).await? {
+            Ok(())
+        } else {
+            Err(not_found())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct MockItemRepo {
+        item: Option<ItemFetched>,
+        mutated: AtomicBool,
+    }
+
+    #[async_trait]
+    impl IItemRepo for MockItemRepo {
+        async fn register(&self, item: &ItemCreate) -> Result<ItemFetched, AppError> {
+            Ok(ItemFetched {
+                id: 1,
+                user_id: item.user_id,
+                name: item.name.clone(),
+                price: item.price,
+            })
+        }
+
+        async fn fetch_by_id(&self, _: i32) -> Result<Option<ItemFetched>, AppError> {
+            Ok(self.item.clone())
+        }
+
+        async fn fetch_by_user(&self, _: i32) -> Result<Vec<ItemFetched>, AppError> {
+            Ok(self.item.clone().into_iter().collect())
+        }
+
+        async fn update(&self, _: i32, item: &ItemUpdate) -> Result<Option<ItemFetched>, AppError> {
+            self.mutated.store(true, Ordering::SeqCst);
+            Ok(self.item.clone().map(|fetched| ItemFetched {
+                name: item.name.clone().unwrap_or(fetched.name),
+                price: item.price.unwrap_or(fetched.price),
+                ..fetched
+            }))
+        }
+
+        async fn delete(&self, _: i32) -> Result<bool, AppError> {
+            self.mutated.store(true, Ordering::SeqCst);
+            Ok(self.item.is_some())
+        }
+    }
+
+    fn stored_item() -> ItemFetched {
+        ItemFetched {
+            id: 1,
+            user_id: 7,
+            name: String::from("item"),
+            price: 1.5,
+        }
+    }
+
+    #[tokio::test]
+    async fn create_item() {
+        let service = ItemService::new(MockItemRepo::default());
+
+        let item = ItemCreate {
+            name: String::from("item"),
+            price: 1.5,
+            user_id: 7,
+        };
+
+        let res = service.create(&item).await;
+
+        assert_eq!(res.unwrap(), stored_item());
+    }
+
+    #[tokio::test]
+    async fn create_item_bad_request() {
+        let service = ItemService::new(MockItemRepo::default());
+
+        let item = ItemCreate {
+            name: String::from(" "),
+            price: -1.0,
+            user_id: 7,
+        };
+
+        let res = service.create(&item).await;
+
+        assert!(matches!(res, Err(AppError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn fetch_missing_item() {
+        let service = ItemService::new(MockItemRepo::default());
+
+        assert!(matches!(
+            service.fetch(1).await,
+            Err(AppError::NotFound { .. })
+        ));
+        assert!(matches!(
+            service.delete(7, 1).await,
+            Err(AppError::NotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_item_partially() {
+        let service = ItemService::new(MockItemRepo {
+            item: Some(stored_item()),
+            ..Default::default()
+        });
+
+        let patch = ItemUpdate {
+            name: None,
+            price: Some(2.0),
+        };
+
+        let res = service.update(7, 1, &patch).await.unwrap();
+
+        assert_eq!(res.name, "item");
+        assert_eq!(res.price, 2.0);
+    }
+
+    #[tokio::test]
+    async fn update_item_bad_request() {
+        let service = ItemService::new(MockItemRepo {
+            item: Some(stored_item()),
+            ..Default::default()
+        });
+
+        let patch = ItemUpdate {
+            name: Some(String::from("")),
+            price: None,
+        };
+
+        assert!(matches!(
+            service.update(7, 1, &patch).await,
+            Err(AppError::Validation { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_item_forbidden_for_other_user() {
+        let service = ItemService::new(MockItemRepo {
+            item: Some(stored_item()),
+            ..Default::default()
+        });
+
+        let patch = ItemUpdate {
+            name: Some(String::from("stolen")),
+            price: None,
+        };
+
+        assert!(matches!(
+            service.update(8, 1, &patch).await,
+            Err(AppError::Forbidden { .. })
+        ));
+        assert!(!service.repo.mutated.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn delete_item_forbidden_for_other_user() {
+        let service = ItemService::new(MockItemRepo {
+            item: Some(stored_item()),
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            service.delete(8, 1).await,
+            Err(AppError::Forbidden { .. })
+        ));
+        assert!(!service.repo.mutated.load(Ordering::SeqCst));
+
+        assert!(service.delete(7, 1).await.is_ok());
+        assert!(service.repo.mutated.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use crate::{contract::repo::login_attempt_repo_trait::ILoginAttemptRepo, error::AppError};
+
+/// Failed logins are allowed for free up to a point; after that every further
+/// failure locks the key for twice as long as the previous one.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LoginThrottling {
+    pub account_free_attempts: i32,
+    pub ip_free_attempts: i32,
+    pub base_delay_secs: u64,
+    pub max_lockout_secs: u64,
+    /// Failures older than this are forgotten.
+    pub window_secs: u64,
+}
+
+impl Default for LoginThrottling {
+    fn default() -> Self {
+        Self {
+            account_free_attempts: 5,
+            ip_free_attempts: 20,
+            base_delay_secs: 1,
+            max_lockout_secs: 15 * 60,
+            window_secs: 60 * 60,
+        }
+    }
+}
+
+impl LoginThrottling {
+    /// Lockout after `failures` failed attempts against a key allowing `free`.
+    pub fn lockout_secs(&self, failures: i32, free: i32) -> u64 {
+        let excess = failures.saturating_sub(free);
+        if excess <= 0 {
+            return 0;
+        }
+
+        let factor = 1u64.checked_shl(excess as u32 - 1).unwrap_or(u64::MAX);
+        self.base_delay_secs
+            .saturating_mul(factor)
+            .min(self.max_lockout_secs)
+    }
+}
+
+/// What a throttle counts. Each kind keeps its own counters, so asking for
+/// password resets doesn't lock anyone out of logging in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Throttled {
+    /// Password guesses: logins, 2FA codes and password changes.
+    Login,
+    /// Password reset requests, every one of which counts.
+    PasswordReset,
+    /// Verification mails, counted per user id and per target address (in
+    /// place of the IP), every one of which counts.
+    VerificationMail,
+}
+
+impl Throttled {
+    /// The kinds keyed by user name.
+    const BY_NAME: [Throttled; 2] = [Throttled::Login, Throttled::PasswordReset];
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Throttled::Login => "",
+            Throttled::PasswordReset => "reset:",
+            Throttled::VerificationMail => "verify:",
+        }
+    }
+
+    fn locked_out(self, retry_after: u64) -> AppError {
+        match self {
+            Throttled::Login => AppError::too_many_requests(
+                "too_many_attempts",
+                "Too many failed login attempts, try again later.",
+                retry_after,
+            ),
+            Throttled::PasswordReset => AppError::too_many_requests(
+                "too_many_reset_requests",
+                "Too many password reset requests, try again later.",
+                retry_after,
+            ),
+            Throttled::VerificationMail => AppError::too_many_requests(
+                "too_many_verification_mails",
+                "Too many verification emails, try again later.",
+                retry_after,
+            ),
+        }
+    }
+}
+
+pub struct LoginThrottle<Repo: ILoginAttemptRepo> {
+    repo: Repo,
+    settings: LoginThrottling,
+    kind: Throttled,
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+fn account_key(kind: Throttled, account: &str) -> String {
+    format!("{}account:{account}", kind.prefix())
+}
+
+fn ip_key(kind: Throttled, ip: &str) -> String {
+    match kind {
+        Throttled::VerificationMail => format!("{}address:{ip}", kind.prefix()),
+        _ => format!("{}ip:{ip}", kind.prefix()),
+    }
+}
+
+/// Every counter kept for `account`, of any kind. A freed name must not
+/// carry them over to whoever takes it next.
+pub fn account_keys(account: &str) -> Vec<String> {
+    Throttled::BY_NAME
+        .iter()
+        .map(|&kind| account_key(kind, account))
+        .collect()
+}
+
+impl<Repo: ILoginAttemptRepo> LoginThrottle<Repo> {
+    pub fn new(repo: Repo, settings: LoginThrottling) -> Self {
+        Self::counting(Throttled::Login, repo, settings)
+    }
+
+    pub fn counting(kind: Throttled, repo: Repo, settings: LoginThrottling) -> Self {
+        Self {
+            repo,
+            settings,
+            kind,
+        }
+    }
+
+    fn keys(&self, account: &str, ip: Option<&str>) -> Vec<(String, i32)> {
+        let mut keys = vec![(
+            account_key(self.kind, account),
+            self.settings.account_free_attempts,
+        )];
+
+        if let Some(ip) = ip {
+            // One address gets no more mail than one account may ask for.
+            let free = match self.kind {
+                Throttled::VerificationMail => self.settings.account_free_attempts,
+                _ => self.settings.ip_free_attempts,
+            };
+            keys.push((ip_key(self.kind, ip), free));
+        }
+
+        keys
+    }
+
+    /// Refuses the attempt while the account or the IP is locked out.
+    pub async fn check(&self, account: &str, ip: Option<&str>) -> Result<(), AppError> {
+        let now = now();
+        let mut locked_until = 0;
+
+        for (key, _) in self.keys(account, ip) {
+            if let Some(attempt) = self.repo.fetch(&key).await? {
+                locked_until = locked_until.max(attempt.locked_until);
+            }
+        }
+
+        if locked_until > now {
+            Err(self.kind.locked_out((locked_until - now) as u64))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub async fn failed(&self, account: &str, ip: Option<&str>) -> Result<(), AppError> {
+        let now = now();
+        let reset_before = now - self.settings.window_secs as i64;
+
+        for (key, free) in self.keys(account, ip) {
+            let failures = self.repo.record_failure(&key, now, reset_before).await?;
+            let lockout = self.settings.lockout_secs(failures, free);
+
+            if lockout > 0 {
+                self.repo.lock(&key, now + lockout as i64).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Only the account is forgiven; an IP spraying many accounts stays counted.
+    pub async fn succeeded(&self, account: &str) -> Result<(), AppError> {
+        self.repo.clear(&account_key(self.kind, account)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo::memory::{MemoryStore, login_attempt_repo::InMemoryLoginAttemptRepo};
+
+    fn throttle(settings: LoginThrottling) -> LoginThrottle<InMemoryLoginAttemptRepo> {
+        LoginThrottle::new(InMemoryLoginAttemptRepo::new(MemoryStore::new()), settings)
+    }
+
+    #[test]
+    fn lockout_doubles_up_to_the_cap() {
+        let settings = LoginThrottling {
+            base_delay_secs: 2,
+            max_lockout_secs: 10,
+            ..Default::default()
+        };
+
+        let lockouts: Vec<_> = (3..=8).map(|n| settings.lockout_secs(n, 4)).collect();
+
+        assert_eq!(lockouts, vec![0, 0, 2, 4, 8, 10]);
+    }
+
+    #[tokio::test]
+    async fn account_is_locked_after_free_attempts() {
+        let throttle = throttle(LoginThrottling {
+            account_free_attempts: 2,
+            base_delay_secs: 60,
+            ..Default::default()
+        });
+
+        for _ in 0..2 {
+            throttle.failed("nk", None).await.unwrap();
+            assert!(throttle.check("nk", None).await.is_ok());
+        }
+        throttle.failed("nk", None).await.unwrap();
+
+        let Err(AppError::TooManyRequests { retry_after, .. }) = throttle.check("nk", None).await
+        else {
+            panic!("expected the account to be locked");
+        };
+        assert!(retry_after > 0 && retry_after <= 60);
+        assert!(throttle.check("other", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn ip_is_locked_across_accounts() {
+        let throttle = throttle(LoginThrottling {
+            ip_free_attempts: 2,
+            base_delay_secs: 60,
+            ..Default::default()
+        });
+
+        for name in ["a", "b", "c"] {
+            throttle.failed(name, Some("10.0.0.1")).await.unwrap();
+        }
+
+        assert!(throttle.check("d", Some("10.0.0.1")).await.is_err());
+        assert!(throttle.check("d", Some("10.0.0.2")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn kinds_are_counted_apart() {
+        let store = MemoryStore::new();
+        let settings = LoginThrottling {
+            account_free_attempts: 1,
+            base_delay_secs: 60,
+            ..Default::default()
+        };
+        let resets = LoginThrottle::counting(
+            Throttled::PasswordReset,
+            InMemoryLoginAttemptRepo::new(store.clone()),
+            settings.clone(),
+        );
+        let logins = LoginThrottle::new(InMemoryLoginAttemptRepo::new(store), settings);
+
+        for _ in 0..2 {
+            resets.failed("nk", None).await.unwrap();
+        }
+
+        let Err(AppError::TooManyRequests { code, .. }) = resets.check("nk", None).await else {
+            panic!("expected resets to be locked");
+        };
+        assert_eq!(code, "too_many_reset_requests");
+        assert!(logins.check("nk", None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn success_clears_the_account() {
+        let throttle = throttle(LoginThrottling {
+            account_free_attempts: 1,
+            base_delay_secs: 60,
+            ..Default::default()
+        });
+
+        throttle.failed("nk", None).await.unwrap();
+        throttle.succeeded("nk").await.unwrap();
+        throttle.failed("nk", None).await.unwrap();
+
+        assert!(throttle.check("nk", None).await.is_ok());
+    }
+}
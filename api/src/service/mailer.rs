@@ -0,0 +1,265 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use serde::Deserialize;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+use crate::{
+    config::ConfigError, contract::service::mailer_trait::IMailer, entity::mail_entity::Mail,
+    error::AppError,
+};
+
+/// Shared, since both email verification and password resets send mail.
+pub type DynMailer = Arc<dyn IMailer + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain text, for a relay on the same host or a test stand-in.
+    None,
+    #[default]
+    StartTls,
+    /// TLS from the first byte, usually on port 465.
+    Tls,
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(SmtpSecurity::None),
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" => Ok(SmtpSecurity::Tls),
+            other => Err(format!("unknown smtp security `{other}`")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for SmtpSettings {
+    fn default() -> Self {
+        Self {
+            host: String::from("localhost"),
+            port: 587,
+            security: SmtpSecurity::default(),
+            username: None,
+            password: None,
+        }
+    }
+}
+
+/// How outbound mail leaves the process. Production only accepts `smtp`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(tag = "transport", rename_all = "lowercase")]
+pub enum MailTransport {
+    /// Keeps mail in memory, where nothing reads it; for tests.
+    #[default]
+    Memory,
+    /// Appends each message to `path`, for local development.
+    File {
+        path: String,
+    },
+    Smtp(SmtpSettings),
+}
+
+impl MailTransport {
+    pub fn mailer(&self, from: &str) -> Result<DynMailer, ConfigError> {
+        let from: Mailbox = from.parse().map_err(|err| ConfigError::Invalid {
+            key: "mail_from",
+            reason: format!("{err}"),
+        })?;
+
+        Ok(match self {
+            MailTransport::Memory => Arc::new(InMemoryMailer::default()),
+            MailTransport::File { path } => Arc::new(FileMailer::new(path, from)),
+            MailTransport::Smtp(settings) => Arc::new(SmtpMailer::new(settings, from)?),
+        })
+    }
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(settings: &SmtpSettings, from: Mailbox) -> Result<Self, ConfigError> {
+        let invalid = |err: lettre::transport::smtp::Error| ConfigError::Invalid {
+            key: "mailer",
+            reason: err.to_string(),
+        };
+
+        let builder = match settings.security {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+                    .map_err(invalid)?
+            }
+            SmtpSecurity::Tls => {
+                AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host).map_err(invalid)?
+            }
+        };
+
+        let mut builder = builder.port(settings.port);
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+fn message(from: &Mailbox, mail: &Mail) -> Result<Message, AppError> {
+    let to: Mailbox = mail
+        .to
+        .parse()
+        .map_err(|err| AppError::Internal(format!("invalid recipient {}: {err}", mail.to)))?;
+
+    Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(&mail.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(mail.body.clone())
+        .map_err(|err| AppError::Internal(err.to_string()))
+}
+
+#[async_trait]
+impl IMailer for SmtpMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        self.transport
+            .send(message(&self.from, mail)?)
+            .await
+            .map_err(|err| AppError::Internal(format!("cannot send mail: {err}")))?;
+
+        Ok(())
+    }
+}
+
+pub struct FileMailer {
+    path: String,
+    from: Mailbox,
+}
+
+impl FileMailer {
+    pub fn new(path: &str, from: Mailbox) -> Self {
+        Self {
+            path: String::from(path),
+            from,
+        }
+    }
+}
+
+#[async_trait]
+impl IMailer for FileMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        let write_error =
+            |err: std::io::Error| AppError::Internal(format!("cannot write {}: {err}", self.path));
+
+        let entry = format!(
+            "From: {}\nTo: {}\nSubject: {}\n\n{}\n\n",
+            self.from, mail.to, mail.subject, mail.body
+        );
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(write_error)?;
+        file.write_all(entry.as_bytes())
+            .await
+            .map_err(write_error)?;
+        file.flush().await.map_err(write_error)
+    }
+}
+
+/// Records mail instead of sending it. Clones share the outbox.
+#[derive(Clone, Default)]
+pub struct InMemoryMailer {
+    sent: Arc<Mutex<Vec<Mail>>>,
+}
+
+impl InMemoryMailer {
+    pub fn sent(&self) -> Vec<Mail> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl IMailer for InMemoryMailer {
+    async fn send(&self, mail: &Mail) -> Result<(), AppError> {
+        self.sent.lock().unwrap().push(mail.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mail() -> Mail {
+        Mail {
+            to: String::from("nk@example.com"),
+            subject: String::from("Hello"),
+            body: String::from("Hi there."),
+        }
+    }
+
+    #[tokio::test]
+    async fn file_mailer_appends_messages() {
+        let path = std::env::temp_dir().join(format!("mail-{}.log", rand::random::<u64>()));
+        let mailer = MailTransport::File {
+            path: path.to_string_lossy().into_owned(),
+        }
+        .mailer("API <api@example.com>")
+        .unwrap();
+
+        mailer.send(&mail()).await.unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            content,
+            "From: API <api@example.com>\nTo: nk@example.com\nSubject: Hello\n\nHi there.\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn memory_mailer_clones_share_outbox() {
+        let mailer = InMemoryMailer::default();
+
+        mailer.clone().send(&mail()).await.unwrap();
+
+        assert_eq!(mailer.sent(), [mail()]);
+    }
+
+    #[test]
+    fn mail_from_must_be_an_address() {
+        assert!(MailTransport::Memory.mailer("not an address").is_err());
+    }
+}
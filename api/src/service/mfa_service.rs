@@ -0,0 +1,195 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    contract::{repo::mfa_repo_trait::IMfaRepo, service::mfa_service_trait::IMfaService},
+    entity::{
+        mfa_entity::{MfaEnrollment, MfaFetched, RecoveryCodes},
+        user_entity::UserFetched,
+    },
+    error::AppError,
+    service::totp,
+};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+
+pub struct MfaService<R: IMfaRepo> {
+    repo: R,
+    issuer: String,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn recovery_code() -> String {
+    let code = hex::encode(rand::random::<[u8; 5]>());
+    format!("{}-{}", &code[..5], &code[5..])
+}
+
+/// Recovery codes are stored hashed, like refresh tokens. Dashes and case
+/// don't matter when typing one in.
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| *c != '-' && !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+async fn use_totp<R: IMfaRepo>(repo: &R, mfa: &MfaFetched, code: &str) -> Result<bool, AppError> {
+    match totp::verify(&mfa.secret, code.trim(), now()) {
+        Some(step) => repo.use_step(mfa.user_id, step).await,
+        None => Ok(false),
+    }
+}
+
+/// Accepts a current TOTP code or an unused recovery code, and burns it.
+pub async fn use_code<R: IMfaRepo>(
+    repo: &R,
+    mfa: &MfaFetched,
+    code: &str,
+) -> Result<bool, AppError> {
+    if use_totp(repo, mfa, code).await? {
+        return Ok(true);
+    }
+
+    repo.use_recovery_code(mfa.user_id, &hash_recovery_code(code))
+        .await
+}
+
+fn already_enabled() -> AppError {
+    AppError::conflict(
+        "mfa_already_enabled",
+        "Two-factor authentication is already enabled.",
+    )
+}
+
+impl<R: IMfaRepo> MfaService<R> {
+    pub fn new(repo: R, issuer: &str) -> Self {
+        Self {
+            repo,
+            issuer: String::from(issuer),
+        }
+    }
+}
+
+#[async_trait]
+impl<R: IMfaRepo + Sync> IMfaService for MfaService<R> {
+    async fn enroll(&self, user: &UserFetched) -> Result<MfaEnrollment, AppError> {
+        if let Some(mfa) = self.repo.fetch(user.id).await?
+            && mfa.enabled
+        {
+            return Err(already_enabled());
+        }
+
+        let secret = totp::generate_secret();
+        self.repo.set_pending(user.id, &secret).await?;
+
+        Ok(MfaEnrollment {
+            otpauth_uri: totp::otpauth_uri(&self.issuer, &user.name, &secret),
+            secret,
+        })
+    }
+
+    async fn confirm(&self, user_id: i32, code: &str) -> Result<RecoveryCodes, AppError> {
+        let Some(mfa) = self.repo.fetch(user_id).await? else {
+            return Err(AppError::conflict(
+                "mfa_not_enrolled",
+                "Start two-factor enrollment first.",
+            ));
+        };
+
+        if mfa.enabled {
+            return Err(already_enabled());
+        }
+
+        if !use_totp(&self.repo, &mfa, code).await? {
+            return Err(AppError::validation(
+                "invalid_mfa_code",
+                "Invalid two-factor code.",
+            ));
+        }
+
+        let recovery_codes: Vec<String> =
+            (0..RECOVERY_CODE_COUNT).map(|_| recovery_code()).collect();
+        let hashes: Vec<String> = recovery_codes
+            .iter()
+            .map(|c| hash_recovery_code(c))
+            .collect();
+
+        self.repo.enable(user_id, &hashes).await?;
+
+        Ok(RecoveryCodes { recovery_codes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo,
+        entity::user_entity::UserRegister,
+        repo::memory::{MemoryStore, mfa_repo::InMemoryMfaRepo, user_repo::InMemoryUserRepo},
+    };
+
+    fn current_code(secret: &str) -> String {
+        totp::code_for(secret, now()).unwrap()
+    }
+
+    async fn service() -> (MfaService<InMemoryMfaRepo>, UserFetched) {
+        let store = MemoryStore::new();
+        let users = InMemoryUserRepo::new(store.clone());
+        users
+            .register(&UserRegister {
+                name: String::from("nk"),
+                password: String::from("hash"),
+            })
+            .await
+            .unwrap();
+        let user = users.fetch_by_name("nk").await.unwrap();
+
+        (MfaService::new(InMemoryMfaRepo::new(store), "api"), user)
+    }
+
+    #[tokio::test]
+    async fn enroll_then_confirm() {
+        let (service, user) = service().await;
+
+        let enrollment = service.enroll(&user).await.unwrap();
+        assert!(enrollment.otpauth_uri.starts_with("otpauth://totp/api:nk?"));
+
+        assert!(service.confirm(user.id, "000000x").await.is_err());
+
+        let code = current_code(&enrollment.secret);
+        let recovery = service.confirm(user.id, &code).await.unwrap();
+        assert_eq!(recovery.recovery_codes.len(), RECOVERY_CODE_COUNT);
+
+        assert!(matches!(
+            service.enroll(&user).await,
+            Err(AppError::Conflict { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn codes_are_single_use() {
+        let (service, user) = service().await;
+        let enrollment = service.enroll(&user).await.unwrap();
+        let code = current_code(&enrollment.secret);
+        let recovery = service.confirm(user.id, &code).await.unwrap();
+        let mfa = service.repo.fetch(user.id).await.unwrap().unwrap();
+
+        assert!(!use_code(&service.repo, &mfa, &code).await.unwrap());
+
+        let recovery_code = recovery.recovery_codes[0].to_uppercase();
+        assert!(use_code(&service.repo, &mfa, &recovery_code).await.unwrap());
+        assert!(!use_code(&service.repo, &mfa, &recovery_code).await.unwrap());
+    }
+}
@@ -1,2 +1,15 @@
+pub mod admin_service;
+pub mod api_key_service;
 pub mod auth_service;
+pub mod email_service;
+pub mod item_service;
+pub mod login_throttle;
+pub mod mailer;
+pub mod mfa_service;
+pub mod notifier;
+pub mod password_hasher;
+pub mod password_policy;
+pub mod password_service;
+pub mod token_keys;
+pub mod totp;
 pub mod user_service;
@@ -0,0 +1,170 @@
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+use crate::{
+    contract::service::{mailer_trait::IMailer, notifier_trait::INotifier},
+    entity::{mail_entity::Mail, notification_entity::Notification},
+    error::AppError,
+    service::mailer::DynMailer,
+};
+
+/// Where notifications go. Only `mail` reaches the user; the other sinks are
+/// for local development and tests and are refused in production.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(tag = "sink", rename_all = "lowercase")]
+pub enum NotifierSink {
+    /// Mails each notification to the user's verified address through the
+    /// configured mailer. Users without one are not notified.
+    Mail,
+    /// Prints each notification, secret included, to stdout.
+    #[default]
+    Log,
+    /// Appends one line per notification, secret included, to `path`.
+    File { path: String },
+}
+
+impl FromStr for NotifierSink {
+    type Err = String;
+
+    /// The sinks that need no settings; `file` is chosen by its path.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "mail" => Ok(NotifierSink::Mail),
+            "log" => Ok(NotifierSink::Log),
+            other => Err(format!("unknown notifier sink `{other}`")),
+        }
+    }
+}
+
+pub struct Notifier {
+    sink: NotifierSink,
+    mailer: DynMailer,
+}
+
+impl Notifier {
+    pub fn new(sink: NotifierSink, mailer: DynMailer) -> Self {
+        Self { sink, mailer }
+    }
+}
+
+fn line(notification: &Notification) -> String {
+    match notification {
+        Notification::PasswordReset {
+            name,
+            token,
+            expires_at,
+            ..
+        } => format!("password_reset token={token} expires_at={expires_at} name={name}"),
+    }
+}
+
+fn mail(notification: &Notification) -> Option<Mail> {
+    match notification {
+        Notification::PasswordReset {
+            name, email, token, ..
+        } => Some(Mail {
+            to: email.clone()?,
+            subject: String::from("Reset your password"),
+            body: format!(
+                "Hi {name},\n\n\
+                 Use this reset token within 30 minutes to choose a new password:\n\n\
+                 {token}\n\n\
+                 If you didn't ask for this, you can ignore this email.\n"
+            ),
+        }),
+    }
+}
+
+#[async_trait]
+impl INotifier for Notifier {
+    async fn notify(&self, notification: &Notification) -> Result<(), AppError> {
+        match &self.sink {
+            NotifierSink::Mail => {
+                if let Some(mail) = mail(notification) {
+                    self.mailer.send(&mail).await?;
+                }
+            }
+            NotifierSink::Log => println!("{}", line(notification)),
+            NotifierSink::File { path } => {
+                let line = line(notification);
+                let write_error =
+                    |err: std::io::Error| AppError::Internal(format!("cannot write {path}: {err}"));
+
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await
+                    .map_err(write_error)?;
+                file.write_all(format!("{line}\n").as_bytes())
+                    .await
+                    .map_err(write_error)?;
+                // tokio finishes writes in the background unless flushed.
+                file.flush().await.map_err(write_error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::service::mailer::InMemoryMailer;
+
+    fn reset(email: Option<&str>, token: &str) -> Notification {
+        Notification::PasswordReset {
+            name: String::from("nk"),
+            email: email.map(String::from),
+            token: String::from(token),
+            expires_at: 100,
+        }
+    }
+
+    #[tokio::test]
+    async fn mail_sink_sends_to_verified_address_only() {
+        let mailer = InMemoryMailer::default();
+        let notifier = Notifier::new(NotifierSink::Mail, Arc::new(mailer.clone()));
+
+        notifier.notify(&reset(None, "first")).await.unwrap();
+        notifier
+            .notify(&reset(Some("nk@example.com"), "second"))
+            .await
+            .unwrap();
+
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "nk@example.com");
+        assert!(sent[0].body.contains("second"));
+    }
+
+    #[tokio::test]
+    async fn file_sink_appends_lines() {
+        let path = std::env::temp_dir().join(format!("notifier-{}.log", rand::random::<u64>()));
+        let notifier = Notifier::new(
+            NotifierSink::File {
+                path: path.to_string_lossy().into_owned(),
+            },
+            Arc::new(InMemoryMailer::default()),
+        );
+
+        for token in ["first", "second"] {
+            notifier.notify(&reset(None, token)).await.unwrap();
+        }
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            content,
+            "password_reset token=first expires_at=100 name=nk\n\
+             password_reset token=second expires_at=100 name=nk\n"
+        );
+    }
+}
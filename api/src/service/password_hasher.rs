@@ -0,0 +1,256 @@
+use std::str::FromStr;
+
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHash, PasswordVerifier, Version,
+    password_hash::{PasswordHasher as _, SaltString},
+};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    contract::service::password_hasher_trait::{IPasswordHasher, PasswordCheck},
+    error::AppError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Argon2id,
+    /// Kept so existing hashes still verify; new deployments should not pick it.
+    Bcrypt,
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "argon2id" => Ok(HashAlgorithm::Argon2id),
+            "bcrypt" => Ok(HashAlgorithm::Bcrypt),
+            other => Err(format!("unknown hash algorithm `{other}`")),
+        }
+    }
+}
+
+/// Defaults follow the OWASP recommendation for argon2id.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PasswordHashing {
+    pub algorithm: HashAlgorithm,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+    pub bcrypt_cost: u32,
+}
+
+impl Default for PasswordHashing {
+    fn default() -> Self {
+        Self {
+            algorithm: HashAlgorithm::Argon2id,
+            argon2_memory_kib: 19 * 1024,
+            argon2_iterations: 2,
+            argon2_parallelism: 1,
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+        }
+    }
+}
+
+impl PasswordHashing {
+    pub fn argon2_params(&self) -> Result<Params, String> {
+        Params::new(
+            self.argon2_memory_kib,
+            self.argon2_iterations,
+            self.argon2_parallelism,
+            None,
+        )
+        .map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PasswordHasher {
+    settings: PasswordHashing,
+}
+
+fn internal(err: impl ToString) -> AppError {
+    AppError::Internal(err.to_string())
+}
+
+impl PasswordHasher {
+    pub fn new(settings: PasswordHashing) -> Self {
+        Self { settings }
+    }
+
+    fn argon2(&self) -> Result<Argon2<'static>, AppError> {
+        let params = self.settings.argon2_params().map_err(internal)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    fn hash_now(&self, password: &str) -> Result<String, AppError> {
+        match self.settings.algorithm {
+            HashAlgorithm::Argon2id => {
+                let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>()).map_err(internal)?;
+
+                Ok(self
+                    .argon2()?
+                    .hash_password(password.as_bytes(), &salt)
+                    .map_err(internal)?
+                    .to_string())
+            }
+            HashAlgorithm::Bcrypt => {
+                bcrypt::hash(password, self.settings.bcrypt_cost).map_err(internal)
+            }
+        }
+    }
+
+    fn verify_now(&self, password: &str, hash: &str) -> PasswordCheck {
+        let current = if hash.starts_with("$argon2") {
+            match PasswordHash::new(hash) {
+                Ok(parsed)
+                    if Argon2::default()
+                        .verify_password(password.as_bytes(), &parsed)
+                        .is_ok() =>
+                {
+                    self.is_current_argon2(&parsed)
+                }
+                _ => return PasswordCheck::Mismatch,
+            }
+        } else if hash.starts_with("$2") {
+            if !bcrypt::verify(password, hash).unwrap_or(false) {
+                return PasswordCheck::Mismatch;
+            }
+            self.is_current_bcrypt(hash)
+        } else {
+            return PasswordCheck::Mismatch;
+        };
+
+        if current {
+            PasswordCheck::Valid
+        } else {
+            PasswordCheck::NeedsRehash
+        }
+    }
+
+    fn is_current_argon2(&self, parsed: &PasswordHash) -> bool {
+        let Ok(params) = Params::try_from(parsed) else {
+            return false;
+        };
+
+        self.settings.algorithm == HashAlgorithm::Argon2id
+            && parsed.algorithm == Algorithm::Argon2id.ident()
+            && parsed.version == Some(Version::V0x13.into())
+            && params.m_cost() == self.settings.argon2_memory_kib
+            && params.t_cost() == self.settings.argon2_iterations
+            && params.p_cost() == self.settings.argon2_parallelism
+    }
+
+    /// bcrypt hashes look like `$2b$<cost>$<salt and hash>`.
+    fn is_current_bcrypt(&self, hash: &str) -> bool {
+        let cost = hash.split('$').nth(2).and_then(|cost| cost.parse().ok());
+
+        self.settings.algorithm == HashAlgorithm::Bcrypt && cost == Some(self.settings.bcrypt_cost)
+    }
+}
+
+/// Hashing is deliberately slow, so it must not run on an executor thread.
+async fn blocking<T: Send + 'static>(
+    task: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, AppError> {
+    tokio::task::spawn_blocking(task).await.map_err(internal)
+}
+
+#[async_trait]
+impl IPasswordHasher for PasswordHasher {
+    async fn hash(&self, password: &str) -> Result<String, AppError> {
+        let (hasher, password) = (self.clone(), String::from(password));
+        blocking(move || hasher.hash_now(&password)).await?
+    }
+
+    async fn verify(&self, password: &str, hash: &str) -> Result<PasswordCheck, AppError> {
+        let (hasher, password, hash) = (self.clone(), String::from(password), String::from(hash));
+        blocking(move || hasher.verify_now(&password, &hash)).await
+    }
+}
+
+/// Cheapest valid parameters, so tests don't spend seconds hashing.
+#[cfg(test)]
+pub(crate) fn fast_settings() -> PasswordHashing {
+    PasswordHashing {
+        argon2_memory_kib: 64,
+        argon2_iterations: 1,
+        bcrypt_cost: 4,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn argon2id_round_trip() {
+        let hasher = PasswordHasher::new(fast_settings());
+
+        let hash = hasher.hash("plum-orbit-42").await.unwrap();
+
+        assert!(hash.starts_with("$argon2id$v=19$m=64,t=1,p=1$"));
+        assert_eq!(
+            hasher.verify("plum-orbit-42", &hash).await.unwrap(),
+            PasswordCheck::Valid
+        );
+        assert_eq!(
+            hasher.verify("wrong", &hash).await.unwrap(),
+            PasswordCheck::Mismatch
+        );
+    }
+
+    #[tokio::test]
+    async fn outdated_hashes_need_rehash() {
+        let hasher = PasswordHasher::new(fast_settings());
+
+        let bcrypt = bcrypt::hash("plum-orbit-42", 4).unwrap();
+        assert_eq!(
+            hasher.verify("plum-orbit-42", &bcrypt).await.unwrap(),
+            PasswordCheck::NeedsRehash
+        );
+
+        let weaker = PasswordHasher::new(PasswordHashing {
+            argon2_memory_kib: 32,
+            ..fast_settings()
+        })
+        .hash("plum-orbit-42")
+        .await
+        .unwrap();
+        assert_eq!(
+            hasher.verify("plum-orbit-42", &weaker).await.unwrap(),
+            PasswordCheck::NeedsRehash
+        );
+    }
+
+    #[tokio::test]
+    async fn bcrypt_stays_current_when_configured() {
+        let hasher = PasswordHasher::new(PasswordHashing {
+            algorithm: HashAlgorithm::Bcrypt,
+            ..fast_settings()
+        });
+
+        let hash = hasher.hash("plum-orbit-42").await.unwrap();
+
+        assert!(hash.starts_with("$2b$04$"));
+        assert_eq!(
+            hasher.verify("plum-orbit-42", &hash).await.unwrap(),
+            PasswordCheck::Valid
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_hash_format_never_matches() {
+        let hasher = PasswordHasher::new(fast_settings());
+
+        assert_eq!(
+            hasher.verify("plain", "plain").await.unwrap(),
+            PasswordCheck::Mismatch
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::{collections::HashSet, sync::LazyLock};
+
+use serde::Deserialize;
+
+use crate::error::{AppError, Violation};
+
+/// bcrypt silently ignores everything past this many bytes.
+pub const BCRYPT_MAX_BYTES: usize = 72;
+
+static COMMON_PASSWORDS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    include_str!("../../data/common_passwords.txt")
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+});
+
+/// Rules a new password must satisfy. `min_length` counts characters while
+/// `max_length` counts bytes, since the byte length is what the hasher sees.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub reject_common: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: BCRYPT_MAX_BYTES,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            reject_common: true,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn check(&self, password: &str) -> Result<(), AppError> {
+        let violations = self.violations(password);
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::violations(
+                "weak_password",
+                "Password does not meet the password policy.",
+                violations,
+            ))
+        }
+    }
+
+    pub fn violations(&self, password: &str) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let has = |class: fn(char) -> bool| password.chars().any(class);
+
+        if password.chars().count() < self.min_length {
+            violations.push(Violation::new(
+                "password_too_short",
+                &format!(
+                    "Password must be at least {} characters long.",
+                    self.min_length
+                ),
+            ));
+        }
+        if password.len() > self.max_length {
+            violations.push(Violation::new(
+                "password_too_long",
+                &format!("Password must be at most {} bytes long.", self.max_length),
+            ));
+        }
+        if self.require_lowercase && !has(char::is_lowercase) {
+            violations.push(Violation::new(
+                "password_missing_lowercase",
+                "Password must contain a lowercase letter.",
+            ));
+        }
+        if self.require_uppercase && !has(char::is_uppercase) {
+            violations.push(Violation::new(
+                "password_missing_uppercase",
+                "Password must contain an uppercase letter.",
+            ));
+        }
+        if self.require_digit && !has(char::is_numeric) {
+            violations.push(Violation::new(
+                "password_missing_digit",
+                "Password must contain a digit.",
+            ));
+        }
+        if self.require_symbol && !has(|c| !c.is_alphanumeric()) {
+            violations.push(Violation::new(
+                "password_missing_symbol",
+                "Password must contain a symbol.",
+            ));
+        }
+        if self.reject_common && COMMON_PASSWORDS.contains(password.to_lowercase().as_str()) {
+            violations.push(Violation::new(
+                "password_common",
+                "Password is too common or has appeared in a data breach.",
+            ));
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(policy: &PasswordPolicy, password: &str) -> Vec<String> {
+        policy
+            .violations(password)
+            .into_iter()
+            .map(|v| v.code)
+            .collect()
+    }
+
+    #[test]
+    fn default_policy() {
+        let policy = PasswordPolicy::default();
+
+        assert!(policy.check("plum-orbit-42").is_ok());
+        assert_eq!(
+            codes(&policy, ""),
+            vec!["password_too_short"],
+            "an empty password is only too short"
+        );
+        assert_eq!(codes(&policy, "PassWord"), vec!["password_common"]);
+        assert_eq!(codes(&policy, &"a".repeat(73)), vec!["password_too_long"]);
+    }
+
+    #[test]
+    fn character_classes() {
+        let policy = PasswordPolicy {
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            codes(&policy, "        "),
+            vec![
+                "password_missing_lowercase",
+                "password_missing_uppercase",
+                "password_missing_digit",
+            ]
+        );
+        assert!(policy.check("Plum-orbit-42").is_ok());
+    }
+
+    #[test]
+    fn check_reports_every_violation() {
+        let Err(AppError::Validation { code, errors, .. }) =
+            PasswordPolicy::default().check("123456")
+        else {
+            panic!("expected a validation error");
+        };
+
+        assert_eq!(code, "weak_password");
+        assert_eq!(errors.len(), 2);
+    }
+}
@@ -0,0 +1,471 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+
+use crate::{
+    contract::{
+        repo::{
+            login_attempt_repo_trait::ILoginAttemptRepo,
+            password_reset_repo_trait::IPasswordResetRepo,
+            refresh_token_repo_trait::IRefreshTokenRepo, revocation_repo_trait::IRevocationRepo,
+            session_repo_trait::ISessionRepo, user_repo_trait::IUserRepo,
+        },
+        service::{
+            notifier_trait::INotifier,
+            password_hasher_trait::{IPasswordHasher, PasswordCheck},
+            password_service_trait::IPasswordService,
+        },
+    },
+    dto::password_dto::{PasswordChangeDto, PasswordResetDto},
+    entity::{
+        auth_entity::ClientInfo, notification_entity::Notification,
+        password_reset_entity::PasswordResetCreate,
+    },
+    error::AppError,
+    service::{
+        auth_service::{hash_token, random_token},
+        login_throttle::LoginThrottle,
+        password_policy::PasswordPolicy,
+    },
+};
+
+const RESET_TOKEN_TTL: i64 = 60 * 30;
+
+pub struct PasswordService<
+    UserRepo: IUserRepo,
+    ResetRepo: IPasswordResetRepo,
+    RefreshRepo: IRefreshTokenRepo,
+    RevokeRepo: IRevocationRepo,
+    SessionRepo: ISessionRepo,
+    Hasher: IPasswordHasher,
+    Notify: INotifier,
+    AttemptRepo: ILoginAttemptRepo,
+> {
+    user_repo: UserRepo,
+    reset_repo: ResetRepo,
+    refresh_repo: RefreshRepo,
+    revoke_repo: RevokeRepo,
+    session_repo: SessionRepo,
+    hasher: Hasher,
+    notifier: Notify,
+    policy: PasswordPolicy,
+    /// Shared with logins: a wrong current password is a password guess.
+    login_throttle: LoginThrottle<AttemptRepo>,
+    reset_throttle: LoginThrottle<AttemptRepo>,
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+}
+
+impl<
+    UserRepo: IUserRepo,
+    ResetRepo: IPasswordResetRepo,
+    RefreshRepo: IRefreshTokenRepo,
+    RevokeRepo: IRevocationRepo,
+    SessionRepo: ISessionRepo,
+    Hasher: IPasswordHasher,
+    Notify: INotifier,
+    AttemptRepo: ILoginAttemptRepo,
+>
+    PasswordService<
+        UserRepo,
+        ResetRepo,
+        RefreshRepo,
+        RevokeRepo,
+        SessionRepo,
+        Hasher,
+        Notify,
+        AttemptRepo,
+    >
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        user_repo: UserRepo,
+        reset_repo: ResetRepo,
+        refresh_repo: RefreshRepo,
+        revoke_repo: RevokeRepo,
+        session_repo: SessionRepo,
+        hasher: Hasher,
+        notifier: Notify,
+        policy: PasswordPolicy,
+        login_throttle: LoginThrottle<AttemptRepo>,
+        reset_throttle: LoginThrottle<AttemptRepo>,
+    ) -> Self {
+        Self {
+            user_repo,
+            reset_repo,
+            refresh_repo,
+            revoke_repo,
+            session_repo,
+            hasher,
+            notifier,
+            policy,
+            login_throttle,
+            reset_throttle,
+        }
+    }
+}
+
+#[async_trait]
+impl<
+    R: IUserRepo + Sync,
+    P: IPasswordResetRepo + Sync,
+    T: IRefreshTokenRepo + Sync,
+    V: IRevocationRepo + Sync,
+    S: ISessionRepo + Sync,
+    H: IPasswordHasher + Sync,
+    N: INotifier + Sync,
+    A: ILoginAttemptRepo + Sync,
+> IPasswordService for PasswordService<R, P, T, V, S, H, N, A>
+{
+    async fn change(
+        &self,
+        user_id: i32,
+        keep_session: Option<i32>,
+        dto: &PasswordChangeDto,
+        client: &ClientInfo,
+    ) -> Result<(), AppError> {
+        let ip = client.ip.as_deref();
+        let user = self.user_repo.fetch_by_id(user_id).await?;
+
+        self.login_throttle.check(&user.name, ip).await?;
+
+        if self
+            .hasher
+            .verify(&dto.current_password, &user.password)
+            .await?
+            == PasswordCheck::Mismatch
+        {
+            self.login_throttle.failed(&user.name, ip).await?;
+            return Err(AppError::validation(
+                "invalid_current_password",
+                "Current password is incorrect.",
+            ));
+        }
+
+        self.login_throttle.succeeded(&user.name).await?;
+
+        self.policy.check(&dto.new_password)?;
+
+        let hashed = self.hasher.hash(&dto.new_password).await?;
+        self.user_repo.update_password(user_id, &hashed).await?;
+
+        for session in self.session_repo.list(user_id, 0).await? {
+            if Some(session.id) != keep_session {
+                self.refresh_repo.revoke_family(&session.family).await?;
+                self.session_repo.delete(session.id).await?;
+            }
+        }
+
+        // A link requested with the old password must not undo the change.
+        self.reset_repo.delete_user(user_id).await
+    }
+
+    async fn forgot(&self, name: &str, client: &ClientInfo) -> Result<(), AppError> {
+        let ip = client.ip.as_deref();
+
+        self.reset_throttle.check(name, ip).await?;
+        self.reset_throttle.failed(name, ip).await
+    }
+
+    async fn send_reset(&self, name: &str) -> Result<(), AppError> {
+        let user = match self.user_repo.fetch_by_name(name).await {
+            Err(AppError::NotFound { .. }) => return Ok(()),
+            user => user?,
+        };
+
+        let email = self.user_repo.email(user.id).await?;
+        let token = random_token();
+        let expires_at = now() + RESET_TOKEN_TTL;
+
+        // Only the most recent link works.
+        self.reset_repo.delete_user(user.id).await?;
+        self.reset_repo
+            .create(&PasswordResetCreate {
+                user_id: user.id,
+                token_hash: hash_token(&token),
+                expires_at,
+            })
+            .await?;
+
+        self.notifier
+            .notify(&Notification::PasswordReset {
+                name: user.name,
+                email: email.is_verified().then_some(email.email).flatten(),
+                token,
+                expires_at,
+            })
+            .await
+    }
+
+    async fn reset(&self, dto: &PasswordResetDto) -> Result<(), AppError> {
+        // Checked first so a rejected password doesn't burn the token.
+        self.policy.check(&dto.password)?;
+
+        let Some(user_id) = self
+            .reset_repo
+            .consume(&hash_token(&dto.token), now())
+            .await?
+        else {
+            return Err(AppError::validation(
+                "invalid_reset_token",
+                "Reset token is invalid or has expired.",
+            ));
+        };
+
+        let hashed = self.hasher.hash(&dto.password).await?;
+        self.user_repo.update_password(user_id, &hashed).await?;
+        self.reset_repo.delete_user(user_id).await?;
+
+        self.revoke_repo.bump_token_version(user_id).await?;
+        self.refresh_repo.revoke_user(user_id).await?;
+        self.session_repo.delete_user(user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{
+        entity::{session_entity::SessionCreate, user_entity::UserRegister},
+        repo::memory::{
+            MemoryStore, login_attempt_repo::InMemoryLoginAttemptRepo,
+            password_reset_repo::InMemoryPasswordResetRepo,
+            refresh_token_repo::InMemoryRefreshTokenRepo, revocation_repo::InMemoryRevocationRepo,
+            session_repo::InMemorySessionRepo, user_repo::InMemoryUserRepo,
+        },
+        service::{
+            login_throttle::{LoginThrottling, Throttled},
+            password_hasher::{PasswordHasher, fast_settings},
+        },
+    };
+
+    #[derive(Default)]
+    struct MockNotifier {
+        sent: Mutex<Vec<Notification>>,
+    }
+
+    #[async_trait]
+    impl INotifier for &MockNotifier {
+        async fn notify(&self, notification: &Notification) -> Result<(), AppError> {
+            self.sent.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    type TestService<'a> = PasswordService<
+        InMemoryUserRepo,
+        InMemoryPasswordResetRepo,
+        InMemoryRefreshTokenRepo,
+        InMemoryRevocationRepo,
+        InMemorySessionRepo,
+        PasswordHasher,
+        &'a MockNotifier,
+        InMemoryLoginAttemptRepo,
+    >;
+
+    const PASSWORD: &str = "plum-orbit-42";
+
+    fn throttling() -> LoginThrottling {
+        LoginThrottling {
+            account_free_attempts: 2,
+            base_delay_secs: 60,
+            ..Default::default()
+        }
+    }
+
+    async fn service<'a>(
+        store: &MemoryStore,
+        notifier: &'a MockNotifier,
+    ) -> (TestService<'a>, i32) {
+        let hasher = PasswordHasher::new(fast_settings());
+        let users = InMemoryUserRepo::new(store.clone());
+        users
+            .register(&UserRegister {
+                name: String::from("nk"),
+                password: hasher.hash(PASSWORD).await.unwrap(),
+            })
+            .await
+            .unwrap();
+        let user_id = users.fetch_by_name("nk").await.unwrap().id;
+
+        let service = PasswordService::new(
+            users,
+            InMemoryPasswordResetRepo::new(store.clone()),
+            InMemoryRefreshTokenRepo::new(store.clone()),
+            InMemoryRevocationRepo::new(store.clone()),
+            InMemorySessionRepo::new(store.clone()),
+            hasher,
+            notifier,
+            PasswordPolicy::default(),
+            LoginThrottle::new(InMemoryLoginAttemptRepo::new(store.clone()), throttling()),
+            LoginThrottle::counting(
+                Throttled::PasswordReset,
+                InMemoryLoginAttemptRepo::new(store.clone()),
+                throttling(),
+            ),
+        );
+
+        (service, user_id)
+    }
+
+    async fn open_sessions(store: &MemoryStore, user_id: i32) -> Vec<i32> {
+        let sessions = InMemorySessionRepo::new(store.clone());
+        let mut ids = Vec::new();
+        for family in ["laptop", "phone"] {
+            let session = sessions
+                .create(&SessionCreate {
+                    user_id,
+                    family: String::from(family),
+                    user_agent: None,
+                    ip: None,
+                    created_at: now(),
+                })
+                .await
+                .unwrap();
+            ids.push(session.id);
+        }
+        ids
+    }
+
+    async fn password_matches(service: &TestService<'_>, password: &str) -> bool {
+        let user = service.user_repo.fetch_by_name("nk").await.unwrap();
+        service
+            .hasher
+            .verify(password, &user.password)
+            .await
+            .unwrap()
+            != PasswordCheck::Mismatch
+    }
+
+    #[tokio::test]
+    async fn change_requires_current_password_and_keeps_this_session() {
+        let store = MemoryStore::new();
+        let notifier = MockNotifier::default();
+        let (service, user_id) = service(&store, &notifier).await;
+        let sessions = open_sessions(&store, user_id).await;
+
+        let res = service
+            .change(
+                user_id,
+                Some(sessions[0]),
+                &PasswordChangeDto {
+                    current_password: String::from("wrong-password"),
+                    new_password: String::from("quartz-lemon-17"),
+                },
+                &ClientInfo::default(),
+            )
+            .await;
+        assert!(matches!(res, Err(AppError::Validation { .. })));
+
+        service
+            .change(
+                user_id,
+                Some(sessions[0]),
+                &PasswordChangeDto {
+                    current_password: String::from(PASSWORD),
+                    new_password: String::from("quartz-lemon-17"),
+                },
+                &ClientInfo::default(),
+            )
+            .await
+            .unwrap();
+
+        assert!(password_matches(&service, "quartz-lemon-17").await);
+        let left = service.session_repo.list(user_id, 0).await.unwrap();
+        assert_eq!(left.iter().map(|s| s.id).collect::<Vec<_>>(), [sessions[0]]);
+    }
+
+    #[tokio::test]
+    async fn reset_token_is_single_use() {
+        let store = MemoryStore::new();
+        let notifier = MockNotifier::default();
+        let (service, user_id) = service(&store, &notifier).await;
+        open_sessions(&store, user_id).await;
+
+        service.send_reset("nobody").await.unwrap();
+        assert!(notifier.sent.lock().unwrap().is_empty());
+
+        service.send_reset("nk").await.unwrap();
+        let Some(Notification::PasswordReset { token, .. }) = notifier.sent.lock().unwrap().pop()
+        else {
+            panic!("expected a reset notification");
+        };
+
+        let weak = PasswordResetDto {
+            token: token.clone(),
+            password: String::from("short"),
+        };
+        assert!(service.reset(&weak).await.is_err());
+
+        let dto = PasswordResetDto {
+            token,
+            password: String::from("quartz-lemon-17"),
+        };
+        service.reset(&dto).await.unwrap();
+
+        assert!(password_matches(&service, "quartz-lemon-17").await);
+        assert!(
+            service
+                .session_repo
+                .list(user_id, 0)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let res = service.reset(&dto).await;
+        assert!(matches!(res, Err(AppError::Validation { .. })));
+    }
+
+    #[tokio::test]
+    async fn wrong_current_passwords_lock_the_account() {
+        let store = MemoryStore::new();
+        let notifier = MockNotifier::default();
+        let (service, user_id) = service(&store, &notifier).await;
+        let change = |current: &str| PasswordChangeDto {
+            current_password: String::from(current),
+            new_password: String::from("quartz-lemon-17"),
+        };
+
+        for _ in 0..3 {
+            let res = service
+                .change(
+                    user_id,
+                    None,
+                    &change("wrong-password"),
+                    &ClientInfo::default(),
+                )
+                .await;
+            assert!(matches!(res, Err(AppError::Validation { .. })));
+        }
+
+        let res = service
+            .change(user_id, None, &change(PASSWORD), &ClientInfo::default())
+            .await;
+        assert!(matches!(res, Err(AppError::TooManyRequests { .. })));
+    }
+
+    #[tokio::test]
+    async fn reset_requests_are_throttled_for_any_name() {
+        let store = MemoryStore::new();
+        let notifier = MockNotifier::default();
+        let (service, _) = service(&store, &notifier).await;
+
+        for name in ["nk", "nobody"] {
+            for _ in 0..3 {
+                service.forgot(name, &ClientInfo::default()).await.unwrap();
+            }
+
+            let res = service.forgot(name, &ClientInfo::default()).await;
+            assert!(matches!(res, Err(AppError::TooManyRequests { .. })));
+        }
+        assert!(notifier.sent.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+use std::{collections::HashMap, fs};
+
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation, decode, decode_header,
+    encode,
+    errors::{Error as JwtError, ErrorKind},
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use rsa::{RsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{config::ConfigError, error::AppError};
+
+/// The algorithms a key file may be used with.
+pub const SUPPORTED_ALGORITHMS: &[Algorithm] = &[Algorithm::RS256, Algorithm::EdDSA];
+
+/// DER prefix of an Ed25519 SubjectPublicKeyInfo; the raw key follows it.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TokenKeyFiles {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub public_key: String,
+    /// Retired keys have none: they only verify tokens issued before rotation.
+    #[serde(default)]
+    pub private_key: Option<String>,
+}
+
+/// Asymmetric keys tokens are signed with. With no keys, tokens fall back to
+/// HS256 and the shared `token_secret`. Once keys are configured the secret,
+/// if still set, only verifies the HS256 tokens issued before the switch, so
+/// nobody is signed out by it.
+///
+/// To rotate, add the new key, point `signing_kid` at it, and drop the old
+/// key's `private_key` once deployed; remove the old key entirely after the
+/// longest-lived token it signed has expired.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TokenSigning {
+    pub signing_kid: Option<String>,
+    pub keys: Vec<TokenKeyFiles>,
+}
+
+#[derive(Clone)]
+struct VerifyingKey {
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+/// Signs tokens with the active key and verifies them with whichever key
+/// their `kid` names.
+#[derive(Clone)]
+pub struct TokenKeys {
+    header: Header,
+    signing: EncodingKey,
+    verifying: HashMap<Option<String>, VerifyingKey>,
+    jwks: JwkSet,
+}
+
+fn read(path: &str) -> Result<Vec<u8>, ConfigError> {
+    fs::read(path).map_err(|err| ConfigError::File {
+        path: String::from(path),
+        reason: err.to_string(),
+    })
+}
+
+fn bad_key(path: &str, reason: impl ToString) -> ConfigError {
+    ConfigError::File {
+        path: String::from(path),
+        reason: reason.to_string(),
+    }
+}
+
+fn public_jwk(files: &TokenKeyFiles, pem: &[u8]) -> Result<Jwk, ConfigError> {
+    let invalid = |reason: &str| bad_key(&files.public_key, reason);
+
+    let (key_algorithm, algorithm) = match files.algorithm {
+        Algorithm::RS256 => {
+            let pem = std::str::from_utf8(pem).map_err(|_| invalid("not a PEM file"))?;
+            let key = RsaPublicKey::from_public_key_pem(pem)
+                .map_err(|_| invalid("not an RSA public key"))?;
+
+            (
+                KeyAlgorithm::RS256,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: BASE64URL_NOPAD.encode(&key.n().to_bytes_be()),
+                    e: BASE64URL_NOPAD.encode(&key.e().to_bytes_be()),
+                }),
+            )
+        }
+        Algorithm::EdDSA => {
+            let der = pem::parse(pem).map_err(|_| invalid("not a PEM file"))?;
+            let x = der
+                .contents()
+                .strip_prefix(&ED25519_SPKI_PREFIX)
+                .filter(|x| x.len() == 32)
+                .ok_or_else(|| invalid("not an Ed25519 public key"))?;
+
+            (
+                KeyAlgorithm::EdDSA,
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: BASE64URL_NOPAD.encode(x),
+                }),
+            )
+        }
+        other => return Err(invalid(&format!("unsupported algorithm {other:?}"))),
+    };
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(files.kid.clone()),
+            ..Default::default()
+        },
+        algorithm,
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct Probe {
+    exp: u64,
+}
+
+impl TokenKeys {
+    pub fn hmac(secret: &str) -> Self {
+        let verifying = VerifyingKey {
+            algorithm: Algorithm::HS256,
+            key: DecodingKey::from_secret(secret.as_ref()),
+        };
+
+        Self {
+            header: Header::default(),
+            signing: EncodingKey::from_secret(secret.as_ref()),
+            verifying: HashMap::from([(None, verifying)]),
+            jwks: JwkSet { keys: Vec::new() },
+        }
+    }
+
+    /// Reads every key file. Expects `Config::validate` to have passed.
+    /// With signing keys, `secret` only verifies tokens that have no `kid`.
+    pub fn load(signing: &TokenSigning, secret: Option<&str>) -> Result<Self, ConfigError> {
+        let Some(signing_kid) = &signing.signing_kid else {
+            return secret.map(Self::hmac).ok_or_else(|| ConfigError::Invalid {
+                key: "token_secret",
+                reason: String::from("must be set without token signing keys"),
+            });
+        };
+
+        let mut keys = Self {
+            header: Header::new(Algorithm::HS256),
+            signing: EncodingKey::from_secret(&[]),
+            verifying: HashMap::new(),
+            jwks: JwkSet { keys: Vec::new() },
+        };
+
+        if let Some(secret) = secret {
+            keys.verifying.insert(
+                None,
+                VerifyingKey {
+                    algorithm: Algorithm::HS256,
+                    key: DecodingKey::from_secret(secret.as_ref()),
+                },
+            );
+        }
+
+        for files in &signing.keys {
+            let public = read(&files.public_key)?;
+            let decoding = match files.algorithm {
+                Algorithm::RS256 => DecodingKey::from_rsa_pem(&public),
+                _ => DecodingKey::from_ed_pem(&public),
+            }
+            .map_err(|err| bad_key(&files.public_key, err))?;
+
+            keys.jwks.keys.push(public_jwk(files, &public)?);
+            keys.verifying.insert(
+                Some(files.kid.clone()),
+                VerifyingKey {
+                    algorithm: files.algorithm,
+                    key: decoding,
+                },
+            );
+
+            if &files.kid == signing_kid
+                && let Some(path) = &files.private_key
+            {
+                let private = read(path)?;
+                keys.signing = match files.algorithm {
+                    Algorithm::RS256 => EncodingKey::from_rsa_pem(&private),
+                    _ => EncodingKey::from_ed_pem(&private),
+                }
+                .map_err(|err| bad_key(path, err))?;
+
+                keys.header = Header::new(files.algorithm);
+                keys.header.kid = Some(files.kid.clone());
+            }
+        }
+
+        // Catches a private key that doesn't belong to its public key.
+        let probe = keys
+            .encode(&Probe { exp: u64::MAX })
+            .map_err(|err| bad_key(signing_kid, err))?;
+        keys.decode::<Probe>(&probe)
+            .map_err(|_| bad_key(signing_kid, "private and public key don't match"))?;
+
+        Ok(keys)
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, AppError> {
+        encode(&self.header, claims, &self.signing)
+            .map_err(|err| AppError::Internal(err.to_string()))
+    }
+
+    /// Only the algorithm registered for the token's `kid` is accepted.
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<TokenData<T>, JwtError> {
+        let header = decode_header(token)?;
+        let verifying = self
+            .verifying
+            .get(&header.kid)
+            .ok_or_else(|| JwtError::from(ErrorKind::InvalidToken))?;
+
+        decode(token, &verifying.key, &Validation::new(verifying.algorithm))
+    }
+
+    /// The public keys, for `/.well-known/jwks.json`.
+    pub fn jwks(&self) -> &JwkSet {
+        &self.jwks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_path(name: &str) -> String {
+        format!("{}/tests/keys/{name}", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn key(kid: &str, algorithm: Algorithm, file: &str, signs: bool) -> TokenKeyFiles {
+        TokenKeyFiles {
+            kid: String::from(kid),
+            algorithm,
+            public_key: key_path(&format!("{file}.pub.pem")),
+            private_key: signs.then(|| key_path(&format!("{file}.pem"))),
+        }
+    }
+
+    fn signing(kid: &str, keys: Vec<TokenKeyFiles>) -> TokenSigning {
+        TokenSigning {
+            signing_kid: Some(String::from(kid)),
+            keys,
+        }
+    }
+
+    #[test]
+    fn signs_with_the_active_key() {
+        for (algorithm, file) in [(Algorithm::RS256, "rsa"), (Algorithm::EdDSA, "ed25519")] {
+            let keys = TokenKeys::load(&signing("a", vec![key("a", algorithm, file, true)]), None)
+                .unwrap();
+
+            let token = keys.encode(&Probe { exp: u64::MAX }).unwrap();
+            let header = decode_header(&token).unwrap();
+
+            assert_eq!(header.alg, algorithm);
+            assert_eq!(header.kid.as_deref(), Some("a"));
+            assert!(keys.decode::<Probe>(&token).is_ok());
+        }
+    }
+
+    #[test]
+    fn rotation_keeps_old_tokens_valid() {
+        let old = TokenKeys::load(
+            &signing("old", vec![key("old", Algorithm::RS256, "rsa", true)]),
+            None,
+        )
+        .unwrap();
+        let token = old.encode(&Probe { exp: u64::MAX }).unwrap();
+
+        let rotated = TokenKeys::load(
+            &signing(
+                "new",
+                vec![
+                    key("new", Algorithm::EdDSA, "ed25519", true),
+                    key("old", Algorithm::RS256, "rsa", false),
+                ],
+            ),
+            None,
+        )
+        .unwrap();
+
+        assert!(rotated.decode::<Probe>(&token).is_ok());
+        assert_eq!(rotated.jwks().keys.len(), 2);
+        assert!(rotated.jwks().find("old").is_some());
+    }
+
+    #[test]
+    fn hmac_tokens_stay_valid_after_switching_to_keys() {
+        let token = TokenKeys::hmac("secret")
+            .encode(&Probe { exp: u64::MAX })
+            .unwrap();
+        let config = signing("a", vec![key("a", Algorithm::EdDSA, "ed25519", true)]);
+
+        let keys = TokenKeys::load(&config, Some("secret")).unwrap();
+        assert!(keys.decode::<Probe>(&token).is_ok());
+        assert_eq!(keys.jwks().keys.len(), 1);
+
+        let keys = TokenKeys::load(&config, None).unwrap();
+        assert!(keys.decode::<Probe>(&token).is_err());
+        assert!(TokenKeys::hmac("secret").jwks().keys.is_empty());
+    }
+
+    #[test]
+    fn mismatched_key_pair_is_rejected() {
+        let mut files = key("a", Algorithm::RS256, "rsa", true);
+        files.public_key = key_path("ed25519.pub.pem");
+
+        assert!(TokenKeys::load(&signing("a", vec![files]), None).is_err());
+    }
+
+    #[test]
+    fn jwks_round_trips_to_a_decoding_key() {
+        let keys = TokenKeys::load(
+            &signing(
+                "a",
+                vec![
+                    key("a", Algorithm::EdDSA, "ed25519", true),
+                    key("b", Algorithm::RS256, "rsa", false),
+                ],
+            ),
+            None,
+        )
+        .unwrap();
+        let token = keys.encode(&Probe { exp: u64::MAX }).unwrap();
+
+        let jwk = keys.jwks().find("a").unwrap();
+        let external = DecodingKey::from_jwk(jwk).unwrap();
+
+        assert!(decode::<Probe>(&token, &external, &Validation::new(Algorithm::EdDSA)).is_ok());
+        assert!(DecodingKey::from_jwk(keys.jwks().find("b").unwrap()).is_ok());
+    }
+}
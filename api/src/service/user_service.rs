@@ -1,117 +1,324 @@
 use async_trait::async_trait;
 
 use crate::{
-    contract::{repo::user_repo_trait::IUserRepo, service::user_service_trait::IUserService},
-    entity::user_entity::UserRegister,
+    contract::{
+        repo::{
+            unit_of_work_trait::{IUnitOfWork, IUnitOfWorkFactory},
+            user_repo_trait::user_exists,
+        },
+        service::{password_hasher_trait::IPasswordHasher, user_service_trait::IUserService},
+    },
+    dto::user_dto::UserPatchDto,
+    entity::user_entity::{UserProfile, UserRegister},
+    error::AppError,
+    service::{login_throttle::account_keys, password_policy::PasswordPolicy},
 };
 
-pub struct UserService<R: IUserRepo> {
-    repo: R,
+/// Longest user name, in characters.
+pub const MAX_NAME_CHARS: usize = 64;
+
+fn not_found() -> AppError {
+    AppError::not_found("user_not_found", "User not found.")
 }
 
-impl<R: IUserRepo> UserService<R> {
-    pub fn new(repo: R) -> Self {
-        Self { repo }
+/// Names are shown to other users, so they can't be blank, padded or
+/// unreasonably long.
+fn check_name(name: &str) -> Result<(), AppError> {
+    if name.trim().is_empty() || name.trim() != name || name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::validation(
+            "invalid_name",
+            &format!(
+                "Name must be 1 to {MAX_NAME_CHARS} characters without leading or trailing spaces."
+            ),
+        ));
     }
+
+    Ok(())
 }
 
-#[async_trait]
-impl<R: IUserRepo + Sync> IUserService for UserService<R> {
-    async fn register(&self, dto: &UserRegister) -> Result<(), String> {
-        if self.repo.exists(&dto.name).await {
-            Err(String::from("Not created because it already exists."))
-        } else {
-            let dto = UserRegister {
-                name: dto.name.clone(),
-                password: self.repo.password_hash(&dto.password).await,
-            };
-            self.repo.register(&dto).await;
-            Ok(())
-        }
+/// Lockouts are keyed by name; dropping them in the unit of work that frees
+/// the name keeps the next owner from inheriting them.
+async fn forget_lockouts(uow: &dyn IUnitOfWork, name: &str) -> Result<(), AppError> {
+    for key in account_keys(name) {
+        uow.login_attempts().clear(&key).await?;
     }
+
+    Ok(())
 }
-#[cfg(test)]
-mod tests {
-    use crate::{entity::user_entity::UserFetched, repo::user_repo::UserRepo};
 
-    use super::*;
-    use async_trait::async_trait;
-    use dotenvy::dotenv;
-    use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
-    use std::env;
+pub struct UserService<U: IUnitOfWorkFactory, H: IPasswordHasher> {
+    uow: U,
+    policy: PasswordPolicy,
+    hasher: H,
+}
 
-    struct MockUserRepo {
-        mock_exists: bool,
+impl<U: IUnitOfWorkFactory, H: IPasswordHasher> UserService<U, H> {
+    pub fn new(uow: U, policy: PasswordPolicy, hasher: H) -> Self {
+        Self {
+            uow,
+            policy,
+            hasher,
+        }
     }
+}
 
-    #[async_trait]
-    impl IUserRepo for MockUserRepo {
-        async fn exists(&self, _: &str) -> bool {
-            self.mock_exists
-        }
+#[async_trait]
+impl<U: IUnitOfWorkFactory + Sync, H: IPasswordHasher + Sync> IUserService for UserService<U, H> {
+    async fn register(&self, dto: &UserRegister) -> Result<(), AppError> {
+        check_name(&dto.name)?;
+        self.policy.check(&dto.password)?;
 
-        async fn password_hash(&self, _: &str) -> String {
-            String::from("pass")
-        }
+        // Hashed before the transaction opens, so it doesn't hold a
+        // connection (or the in-memory store) for the length of a hash.
+        let dto = UserRegister {
+            name: dto.name.clone(),
+            password: self.hasher.hash(&dto.password).await?,
+        };
 
-        async fn register(&self, dto: &UserRegister) {
-            assert_eq!(dto.name, "nk");
-            assert_eq!(dto.password, "pass");
+        let uow = self.uow.begin().await?;
+
+        // The unique constraint still settles concurrent signups.
+        if uow.users().exists(&dto.name).await? {
+            return Err(user_exists());
         }
+        uow.users().register(&dto).await?;
 
-        async fn fetch_by_name(&self, _: &str) -> UserFetched {
-            todo!()
+        uow.commit().await
+    }
+
+    async fn profile(&self, id: i32) -> Result<UserProfile, AppError> {
+        let uow = self.uow.begin().await?;
+        let user = uow.users().fetch_by_id(id).await?;
+
+        Ok(UserProfile {
+            id: user.id,
+            name: user.name,
+        })
+    }
+
+    async fn update(&self, id: i32, dto: &UserPatchDto) -> Result<UserProfile, AppError> {
+        let uow = self.uow.begin().await?;
+        let mut user = uow.users().fetch_by_id(id).await?;
+
+        if let Some(name) = dto.name.as_ref().filter(|name| **name != user.name) {
+            check_name(name)?;
+            if uow.users().exists(name).await? {
+                return Err(user_exists());
+            }
+            if !uow.users().rename(id, name).await? {
+                return Err(not_found());
+            }
+            forget_lockouts(uow.as_ref(), &user.name).await?;
+            user.name = name.clone();
         }
 
-        async fn fetch_by_id(&self, _: i32) -> UserFetched {
-            todo!()
+        uow.commit().await?;
+
+        Ok(UserProfile {
+            id: user.id,
+            name: user.name,
+        })
+    }
+
+    async fn delete(&self, id: i32) -> Result<(), AppError> {
+        let uow = self.uow.begin().await?;
+        let user = uow.users().fetch_by_id(id).await?;
+
+        if !uow.users().delete(id).await? {
+            return Err(not_found());
         }
+        forget_lockouts(uow.as_ref(), &user.name).await?;
+
+        uow.commit().await
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::{
+        contract::repo::user_repo_trait::IUserRepo,
+        contract::service::password_hasher_trait::PasswordCheck,
+        repo::{storage::Storage, user_repo::UserRepo},
+        service::{
+            login_throttle::{LoginThrottle, LoginThrottling},
+            password_hasher::{PasswordHasher, fast_settings},
+        },
+    };
+
+    use super::*;
+    use crate::config::Config;
+    use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
+
+    fn memory_service() -> (UserService<Storage, PasswordHasher>, Storage) {
+        let storage = Storage::memory();
+        let service = UserService::new(
+            storage.clone(),
+            PasswordPolicy::default(),
+            PasswordHasher::new(fast_settings()),
+        );
+
+        (service, storage)
     }
 
     #[tokio::test]
     async fn error_on_create_existing_user() {
-        let mock_repo = MockUserRepo { mock_exists: true };
-
-        let service = UserService { repo: mock_repo };
+        let (service, _) = memory_service();
 
         let dto = UserRegister {
             name: String::from("nk"),
-            password: String::from("123"),
+            password: String::from("plum-orbit-42"),
         };
+        service.register(&dto).await.unwrap();
 
         let result = service.register(&dto).await;
 
-        assert!(result.is_err(), "Not created because it already exists.");
+        assert!(
+            matches!(result, Err(AppError::Conflict { .. })),
+            "Not created because it already exists."
+        );
     }
 
     #[tokio::test]
     async fn create_user() {
-        let mut _dto = UserRegister {
-            name: String::from(""),
-            password: String::from(""),
+        let (service, storage) = memory_service();
+
+        let dto = UserRegister {
+            name: String::from("nk"),
+            password: String::from("plum-orbit-42"),
         };
 
-        let mock_repo = MockUserRepo { mock_exists: false };
+        let result = service.register(&dto).await;
+
+        assert!(result.is_ok(), " Created.");
+
+        let stored = storage.user_repo().fetch_by_name("nk").await.unwrap();
+        let check = PasswordHasher::new(fast_settings())
+            .verify("plum-orbit-42", &stored.password)
+            .await
+            .unwrap();
+        assert!(matches!(check, PasswordCheck::Valid), "Stored hashed.");
+    }
 
-        let service = UserService { repo: mock_repo };
+    #[tokio::test]
+    async fn weak_password_is_rejected() {
+        let (service, storage) = memory_service();
 
         let dto = UserRegister {
             name: String::from("nk"),
             password: String::from("123"),
         };
 
-        let result = service.register(&dto).await;
+        assert!(matches!(
+            service.register(&dto).await,
+            Err(AppError::Validation {
+                code: "weak_password",
+                ..
+            })
+        ));
+        assert!(!storage.user_repo().exists("nk").await.unwrap());
+    }
 
-        assert!(result.is_ok(), " Created.");
+    #[tokio::test]
+    async fn concurrent_registers_create_one_user() {
+        let (service, _) = memory_service();
+
+        let dto = UserRegister {
+            name: String::from("racer"),
+            password: String::from("plum-orbit-42"),
+        };
+
+        let (first, second) = tokio::join!(service.register(&dto), service.register(&dto));
+
+        assert!(first.is_ok() != second.is_ok());
+        assert!(matches!(first.and(second), Err(AppError::Conflict { .. })));
+    }
+
+    #[tokio::test]
+    async fn names_are_validated_on_register_and_rename() {
+        let (service, storage) = memory_service();
+        let long = "n".repeat(MAX_NAME_CHARS + 1);
+
+        for name in ["", "   ", " nk", long.as_str()] {
+            let dto = UserRegister {
+                name: String::from(name),
+                password: String::from("plum-orbit-42"),
+            };
+            assert!(matches!(
+                service.register(&dto).await,
+                Err(AppError::Validation {
+                    code: "invalid_name",
+                    ..
+                })
+            ));
+        }
+
+        service
+            .register(&UserRegister {
+                name: String::from("nk"),
+                password: String::from("plum-orbit-42"),
+            })
+            .await
+            .unwrap();
+        let id = storage.user_repo().fetch_by_name("nk").await.unwrap().id;
+
+        for name in ["", "   ", long.as_str()] {
+            let dto = UserPatchDto {
+                name: Some(String::from(name)),
+            };
+            assert!(matches!(
+                service.update(id, &dto).await,
+                Err(AppError::Validation {
+                    code: "invalid_name",
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn freed_names_drop_their_lockouts() {
+        let (service, storage) = memory_service();
+        let throttle = LoginThrottle::new(
+            storage.login_attempt_repo(),
+            LoginThrottling {
+                account_free_attempts: 1,
+                base_delay_secs: 60,
+                ..Default::default()
+            },
+        );
+
+        for name in ["renamed", "deleted"] {
+            service
+                .register(&UserRegister {
+                    name: String::from(name),
+                    password: String::from("plum-orbit-42"),
+                })
+                .await
+                .unwrap();
+            for _ in 0..2 {
+                throttle.failed(name, None).await.unwrap();
+            }
+            assert!(throttle.check(name, None).await.is_err());
+        }
+
+        let users = storage.user_repo();
+        let renamed = users.fetch_by_name("renamed").await.unwrap().id;
+        let dto = UserPatchDto {
+            name: Some(String::from("new_name")),
+        };
+        service.update(renamed, &dto).await.unwrap();
+        let deleted = users.fetch_by_name("deleted").await.unwrap().id;
+        service.delete(deleted).await.unwrap();
+
+        assert!(throttle.check("renamed", None).await.is_ok());
+        assert!(throttle.check("deleted", None).await.is_ok());
     }
 
     async fn load_pool() -> Pool<Postgres> {
-        dotenv().ok();
-        let db_str = env::var("db_str").unwrap();
+        let config = Config::load().unwrap();
 
         PgPoolOptions::new()
-            .max_connections(5)
-            .connect(&db_str)
+            .max_connections(config.max_connections)
+            .connect(&config.db_str)
             .await
             .unwrap()
     }
@@ -121,9 +328,9 @@ mod tests {
     async fn user_repo_exists() {
         let pool = load_pool().await;
 
-        let repo = UserRepo::new(&pool);
+        let repo = UserRepo::new(pool.clone());
 
-        repo.exists(&String::from("test")).await;
+        repo.exists(&String::from("test")).await.unwrap();
     }
 
     #[tokio::test]
@@ -136,14 +343,21 @@ mod tests {
             password: String::from("new_password"),
         };
 
-        let repo = UserRepo::new(&pool);
+        let repo = UserRepo::new(pool.clone());
 
-        repo.register(&new_user).await;
+        repo.register(&new_user).await.unwrap();
 
-        let exists = repo.exists(&String::from("new_user")).await;
+        let exists = repo.exists(&String::from("new_user")).await.unwrap();
 
         assert!(exists, "exists user");
 
+        sqlx::query(
+            "DELETE FROM user_roles WHERE user_id IN (SELECT id FROM users WHERE name = $1)",
+        )
+        .bind(&new_user.name)
+        .execute(&pool)
+        .await
+        .unwrap();
         sqlx::query("DELETE FROM users WHERE name = $1")
             .bind(new_user.name)
             .execute(&pool)
@@ -161,8 +375,11 @@ mod tests {
             password: String::from("new_password"),
         };
 
-        let repo = UserRepo::new(&pool);
-        let service = UserService::new(repo);
+        let service = UserService::new(
+            Storage::Postgres(pool.clone()),
+            PasswordPolicy::default(),
+            PasswordHasher::new(fast_settings()),
+        );
 
         let res = service.register(&new_user).await;
         assert!(res.is_ok());
@@ -170,6 +387,13 @@ mod tests {
         let res = service.register(&new_user).await;
         assert!(res.is_err());
 
+        sqlx::query(
+            "DELETE FROM user_roles WHERE user_id IN (SELECT id FROM users WHERE name = $1)",
+        )
+        .bind(&new_user.name)
+        .execute(&pool)
+        .await
+        .unwrap();
         sqlx::query("DELETE FROM users WHERE name = $1")
             .bind(new_user.name)
             .execute(&pool)
@@ -193,17 +417,27 @@ mod tests {
             .await
             .unwrap();
 
-        let repo = UserRepo::new(&pool);
-        let service = UserService::new(repo);
+        let service = UserService::new(
+            Storage::Postgres(pool.clone()),
+            PasswordPolicy::default(),
+            PasswordHasher::new(fast_settings()),
+        );
 
         let res = service.register(&new_user).await;
         assert!(res.is_ok());
 
-        let repo = UserRepo::new(&pool);
-        let fetch_user = repo.fetch_by_name("new_user").await;
+        let repo = UserRepo::new(pool.clone());
+        let fetch_user = repo.fetch_by_name("new_user").await.unwrap();
 
         assert_eq!(fetch_user.name, "new_user");
 
+        sqlx::query(
+            "DELETE FROM user_roles WHERE user_id IN (SELECT id FROM users WHERE name = $1)",
+        )
+        .bind(&new_user.name)
+        .execute(&pool)
+        .await
+        .unwrap();
         sqlx::query("DELETE FROM users WHERE name = $1")
             .bind(new_user.name)
             .execute(&pool)
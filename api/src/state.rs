@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use crate::{
+    config::{Config, ConfigError},
+    contract::service::{
+        admin_service_trait::IAdminService, api_key_service_trait::IApiKeyService,
+        auth_service_trait::IAuthService, email_service_trait::IEmailService,
+        item_service_trait::IItemService, mfa_service_trait::IMfaService,
+        password_service_trait::IPasswordService, user_service_trait::IUserService,
+    },
+    repo::storage::Storage,
+    service::{
+        admin_service::AdminService, api_key_service::ApiKeyService, auth_service::AuthService,
+        email_service::EmailService, item_service::ItemService, login_throttle::LoginThrottle,
+        login_throttle::Throttled, mfa_service::MfaService, notifier::Notifier,
+        password_hasher::PasswordHasher, password_service::PasswordService, token_keys::TokenKeys,
+        user_service::UserService,
+    },
+};
+
+/// The services handlers run against, built once at startup and shared by
+/// every worker.
+#[derive(Clone)]
+pub struct AppState {
+    pub user_service: Arc<dyn IUserService + Send + Sync>,
+    pub auth_service: Arc<dyn IAuthService + Send + Sync>,
+    pub item_service: Arc<dyn IItemService + Send + Sync>,
+    pub mfa_service: Arc<dyn IMfaService + Send + Sync>,
+    pub admin_service: Arc<dyn IAdminService + Send + Sync>,
+    pub api_key_service: Arc<dyn IApiKeyService + Send + Sync>,
+    pub password_service: Arc<dyn IPasswordService + Send + Sync>,
+    pub email_service: Arc<dyn IEmailService + Send + Sync>,
+}
+
+impl AppState {
+    /// Fails if a token signing key can't be read or the mailer can't be set
+    /// up.
+    pub fn new(storage: &Storage, config: &Config) -> Result<Self, ConfigError> {
+        let hasher = PasswordHasher::new(config.password_hashing.clone());
+        let keys = TokenKeys::load(&config.token_signing, config.trusted_token_secret())?;
+        let mailer = config.mailer.mailer(&config.mail_from)?;
+
+        Ok(Self {
+            user_service: Arc::new(UserService::new(
+                storage.clone(),
+                config.password_policy.clone(),
+                hasher.clone(),
+            )),
+            auth_service: Arc::new(AuthService::new(
+                storage.user_repo(),
+                storage.refresh_token_repo(),
+                storage.revocation_repo(),
+                hasher.clone(),
+                LoginThrottle::new(
+                    storage.login_attempt_repo(),
+                    config.login_throttling.clone(),
+                ),
+                storage.mfa_repo(),
+                storage.api_key_repo(),
+                storage.session_repo(),
+                keys.clone(),
+            )),
+            item_service: Arc::new(ItemService::new(storage.item_repo())),
+            mfa_service: Arc::new(MfaService::new(storage.mfa_repo(), &config.mfa_issuer)),
+            admin_service: Arc::new(AdminService::new(
+                storage.user_repo(),
+                storage.revocation_repo(),
+            )),
+            api_key_service: Arc::new(ApiKeyService::new(storage.api_key_repo())),
+            password_service: Arc::new(PasswordService::new(
+                storage.user_repo(),
+                storage.password_reset_repo(),
+                storage.refresh_token_repo(),
+                storage.revocation_repo(),
+                storage.session_repo(),
+                hasher,
+                Notifier::new(config.notifier.clone(), mailer.clone()),
+                config.password_policy.clone(),
+                LoginThrottle::new(
+                    storage.login_attempt_repo(),
+                    config.login_throttling.clone(),
+                ),
+                LoginThrottle::counting(
+                    Throttled::PasswordReset,
+                    storage.login_attempt_repo(),
+                    config.login_throttling.clone(),
+                ),
+            )),
+            email_service: Arc::new(EmailService::new(
+                storage.user_repo(),
+                mailer,
+                keys,
+                &config.public_url,
+                LoginThrottle::counting(
+                    Throttled::VerificationMail,
+                    storage.login_attempt_repo(),
+                    config.login_throttling.clone(),
+                ),
+            )),
+        })
+    }
+}
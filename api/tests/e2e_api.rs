@@ -1,12 +1,16 @@
 use actix_web::http::StatusCode;
+use api::config::Config;
+use api::dto::auth_dto::RefreshDto;
+use api::dto::item_dto::{ItemDto, ItemPatchDto};
+use api::dto::user_dto::UserPatchDto;
 use api::entity::auth_entity::AuthMe;
-use api::entity::user_entity::UserRegister;
-use dotenvy::dotenv;
+use api::entity::item_entity::ItemFetched;
+use api::entity::user_entity::{UserProfile, UserRegister};
+use api::error::{PROBLEM_CONTENT_TYPE, ProblemDetails};
 use reqwest::Client;
 use serde::Deserialize;
 use sqlx::postgres::PgPoolOptions;
 use sqlx::{Pool, Postgres};
-use std::env;
 use std::future::Future;
 use std::process::{Child, Command};
 use std::thread::sleep;
@@ -30,6 +34,7 @@ where
     callback().await;
 
     server.kill().unwrap();
+    server.wait().unwrap();
 }
 
 #[tokio::test]
@@ -79,7 +84,7 @@ async fn full_body_ok() {
     server_on(|| async {
         let user = UserRegister {
             name: String::from("name"),
-            password: String::from("password"),
+            password: String::from("plum-orbit-42"),
         };
         let client = Client::new();
         let res = client
@@ -94,16 +99,24 @@ async fn full_body_ok() {
 }
 
 async fn load_pool() -> Pool<Postgres> {
-    dotenv().ok();
-    let db_str = env::var("db_str").unwrap();
+    let config = Config::load().unwrap();
 
     PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&db_str)
+        .max_connections(config.max_connections)
+        .connect(&config.db_str)
         .await
         .unwrap()
 }
 
+async fn delete_user(pool: &Pool<Postgres>, name: &str) {
+    // Everything the user owns cascades.
+    sqlx::query("DELETE FROM users WHERE name = $1")
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 #[ignore = "e2e"]
 async fn e2e_register_user() {
@@ -112,7 +125,7 @@ async fn e2e_register_user() {
     server_on(|| async {
         let user = UserRegister {
             name: String::from("name"),
-            password: String::from("password"),
+            password: String::from("plum-orbit-42"),
         };
         let client = Client::new();
         let res = client
@@ -125,16 +138,13 @@ async fn e2e_register_user() {
     })
     .await;
 
-    sqlx::query("DELETE FROM users WHERE name = $1")
-        .bind("name")
-        .execute(&pool)
-        .await
-        .unwrap();
+    delete_user(&pool, "name").await;
 }
 
 #[derive(Debug, Deserialize)]
 struct AuthToken {
     token: String,
+    refresh: String,
 }
 
 #[tokio::test]
@@ -142,16 +152,12 @@ struct AuthToken {
 async fn e2e_fetch_token() {
     let pool = load_pool().await;
 
-    sqlx::query("DELETE FROM users WHERE name = $1")
-        .bind("name")
-        .execute(&pool)
-        .await
-        .unwrap();
+    delete_user(&pool, "name").await;
 
     server_on(|| async {
         let user = UserRegister {
             name: String::from("name"),
-            password: String::from("password"),
+            password: String::from("plum-orbit-42"),
         };
         let client = Client::new();
 
@@ -179,16 +185,7 @@ async fn e2e_fetch_token() {
     })
     .await;
 
-    sqlx::query("DELETE FROM users WHERE name = $1")
-        .bind("name")
-        .execute(&pool)
-        .await
-        .unwrap();
-}
-
-#[derive(Debug, Deserialize)]
-struct UserResponse {
-    name: String,
+    delete_user(&pool, "name").await;
 }
 
 #[tokio::test]
@@ -196,16 +193,12 @@ struct UserResponse {
 async fn e2e_me() {
     let pool = load_pool().await;
 
-    sqlx::query("DELETE FROM users WHERE name = $1")
-        .bind("name")
-        .execute(&pool)
-        .await
-        .unwrap();
+    delete_user(&pool, "name").await;
 
     server_on(|| async {
         let user = UserRegister {
             name: String::from("name"),
-            password: String::from("password"),
+            password: String::from("plum-orbit-42"),
         };
         let client = Client::new();
 
@@ -246,9 +239,563 @@ async fn e2e_me() {
     })
     .await;
 
-    sqlx::query("DELETE FROM users WHERE name = $1")
-        .bind("name")
-        .execute(&pool)
+    delete_user(&pool, "name").await;
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_refresh_token() {
+    let pool = load_pool().await;
+
+    delete_user(&pool, "name").await;
+
+    server_on(|| async {
+        let user = UserRegister {
+            name: String::from("name"),
+            password: String::from("plum-orbit-42"),
+        };
+        let client = Client::new();
+
+        let res = client
+            .post("http://localhost:8080/user")
+            .json(&user)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 201);
+
+        let res = client
+            .post("http://localhost:8080/auth")
+            .json(&user)
+            .send()
+            .await
+            .unwrap();
+
+        let auth_token = res.json::<AuthToken>().await.unwrap();
+
+        let res = client
+            .post("http://localhost:8080/auth/refresh")
+            .json(&RefreshDto {
+                refresh: auth_token.refresh.clone(),
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 200);
+
+        let rotated = res.json::<AuthToken>().await.unwrap();
+
+        assert_ne!(rotated.refresh, auth_token.refresh);
+
+        let res = client
+            .post("http://localhost:8080/auth/refresh")
+            .json(&RefreshDto {
+                refresh: auth_token.refresh.clone(),
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 401);
+
+        let res = client
+            .post("http://localhost:8080/auth/refresh")
+            .json(&RefreshDto {
+                refresh: rotated.refresh.clone(),
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 401);
+    })
+    .await;
+
+    delete_user(&pool, "name").await;
+}
+
+async fn register_and_auth(client: &Client, user: &UserRegister) -> AuthToken {
+    let res = client
+        .post("http://localhost:8080/user")
+        .json(user)
+        .send()
         .await
         .unwrap();
+
+    assert_eq!(res.status(), 201);
+
+    let res = client
+        .post("http://localhost:8080/auth")
+        .json(user)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(res.status(), 200);
+
+    res.json::<AuthToken>().await.unwrap()
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_logout() {
+    let pool = load_pool().await;
+
+    delete_user(&pool, "name").await;
+
+    server_on(|| async {
+        let user = UserRegister {
+            name: String::from("name"),
+            password: String::from("plum-orbit-42"),
+        };
+        let client = Client::new();
+
+        let auth_token = register_and_auth(&client, &user).await;
+
+        let res = client
+            .post("http://localhost:8080/auth/logout")
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 204);
+
+        let res = client
+            .get("http://localhost:8080/auth/me")
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 401);
+    })
+    .await;
+
+    delete_user(&pool, "name").await;
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_logout_all() {
+    let pool = load_pool().await;
+
+    delete_user(&pool, "name").await;
+
+    server_on(|| async {
+        let user = UserRegister {
+            name: String::from("name"),
+            password: String::from("plum-orbit-42"),
+        };
+        let client = Client::new();
+
+        let first = register_and_auth(&client, &user).await;
+
+        let res = client
+            .post("http://localhost:8080/auth")
+            .json(&user)
+            .send()
+            .await
+            .unwrap();
+
+        let second = res.json::<AuthToken>().await.unwrap();
+
+        let res = client
+            .post("http://localhost:8080/auth/logout-all")
+            .bearer_auth(&first.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 204);
+
+        let res = client
+            .get("http://localhost:8080/auth/me")
+            .bearer_auth(&second.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 401);
+
+        let res = client
+            .post("http://localhost:8080/auth/refresh")
+            .json(&RefreshDto {
+                refresh: second.refresh.clone(),
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 401);
+    })
+    .await;
+
+    delete_user(&pool, "name").await;
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_items_crud() {
+    let pool = load_pool().await;
+
+    delete_user(&pool, "name").await;
+
+    server_on(|| async {
+        let user = UserRegister {
+            name: String::from("name"),
+            password: String::from("plum-orbit-42"),
+        };
+        let client = Client::new();
+
+        let auth_token = register_and_auth(&client, &user).await;
+
+        let res = client
+            .post("http://localhost:8080/items")
+            .json(&ItemDto {
+                name: String::from("item"),
+                price: 1.5,
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 401);
+
+        let res = client
+            .post("http://localhost:8080/items")
+            .bearer_auth(&auth_token.token)
+            .json(&ItemDto {
+                name: String::from("item"),
+                price: 1.5,
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 201);
+
+        let created = res.json::<ItemFetched>().await.unwrap();
+
+        let res = client
+            .get("http://localhost:8080/items")
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.json::<Vec<ItemFetched>>().await.unwrap(),
+            vec![created.clone()]
+        );
+
+        let res = client
+            .patch(format!("http://localhost:8080/items/{}", created.id))
+            .bearer_auth(&auth_token.token)
+            .json(&ItemPatchDto {
+                name: None,
+                price: Some(2.0),
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 200);
+
+        let res = client
+            .put(format!("http://localhost:8080/items/{}", created.id))
+            .bearer_auth(&auth_token.token)
+            .json(&ItemDto {
+                name: String::from("renamed"),
+                price: 3.0,
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 200);
+
+        let res = client
+            .get(format!("http://localhost:8080/items/{}", created.id))
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+
+        let fetched = res.json::<ItemFetched>().await.unwrap();
+
+        assert_eq!(fetched.name, "renamed");
+        assert_eq!(fetched.price, 3.0);
+
+        let res = client
+            .delete(format!("http://localhost:8080/items/{}", created.id))
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 204);
+
+        let res = client
+            .get(format!("http://localhost:8080/items/{}", created.id))
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 404);
+    })
+    .await;
+
+    delete_user(&pool, "name").await;
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_items_forbidden_for_other_user() {
+    let pool = load_pool().await;
+
+    delete_user(&pool, "name").await;
+    delete_user(&pool, "other").await;
+
+    server_on(|| async {
+        let owner = UserRegister {
+            name: String::from("name"),
+            password: String::from("plum-orbit-42"),
+        };
+        let other = UserRegister {
+            name: String::from("other"),
+            password: String::from("plum-orbit-42"),
+        };
+        let client = Client::new();
+
+        let owner_token = register_and_auth(&client, &owner).await;
+        let other_token = register_and_auth(&client, &other).await;
+
+        let res = client
+            .post("http://localhost:8080/items")
+            .bearer_auth(&owner_token.token)
+            .json(&ItemDto {
+                name: String::from("item"),
+                price: 1.5,
+            })
+            .send()
+            .await
+            .unwrap();
+
+        let created = res.json::<ItemFetched>().await.unwrap();
+
+        let res = client
+            .patch(format!("http://localhost:8080/items/{}", created.id))
+            .bearer_auth(&other_token.token)
+            .json(&ItemPatchDto {
+                name: Some(String::from("stolen")),
+                price: None,
+            })
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 403);
+
+        let res = client
+            .delete(format!("http://localhost:8080/items/{}", created.id))
+            .bearer_auth(&other_token.token)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 403);
+    })
+    .await;
+
+    delete_user(&pool, "name").await;
+    delete_user(&pool, "other").await;
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_register_existing_user_conflict() {
+    let pool = load_pool().await;
+
+    delete_user(&pool, "name").await;
+
+    server_on(|| async {
+        let user = UserRegister {
+            name: String::from("name"),
+            password: String::from("plum-orbit-42"),
+        };
+        let client = Client::new();
+
+        let res = client
+            .post("http://localhost:8080/user")
+            .json(&user)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 201);
+
+        let res = client
+            .post("http://localhost:8080/user")
+            .json(&user)
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 409);
+
+        let problem: ProblemDetails = res.json().await.unwrap();
+        assert_eq!(problem.code, "user_exists");
+    })
+    .await;
+
+    delete_user(&pool, "name").await;
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_problem_details() {
+    server_on(|| async {
+        let client = Client::new();
+
+        let res = client
+            .get("http://localhost:8080/auth/me")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 401);
+        assert_eq!(
+            res.headers().get("content-type").unwrap(),
+            PROBLEM_CONTENT_TYPE
+        );
+        let problem: ProblemDetails = res.json().await.unwrap();
+        assert_eq!(problem.status, 401);
+        assert_eq!(problem.code, "invalid_token");
+
+        let res = client
+            .post("http://localhost:8080/user")
+            .header("content-type", "application/json")
+            .body("{")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 400);
+        let problem: ProblemDetails = res.json().await.unwrap();
+        assert_eq!(problem.code, "invalid_body");
+
+        let res = client
+            .get("http://localhost:8080/missing")
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), 404);
+        let problem: ProblemDetails = res.json().await.unwrap();
+        assert_eq!(problem.code, "route_not_found");
+    })
+    .await;
+}
+
+#[tokio::test]
+#[ignore = "e2e"]
+async fn e2e_rename_and_delete_me() {
+    let pool = load_pool().await;
+
+    delete_user(&pool, "name").await;
+    delete_user(&pool, "renamed").await;
+    delete_user(&pool, "other").await;
+
+    server_on(|| async {
+        let user = UserRegister {
+            name: String::from("name"),
+            password: String::from("plum-orbit-42"),
+        };
+        let client = Client::new();
+
+        let auth_token = register_and_auth(&client, &user).await;
+        register_and_auth(
+            &client,
+            &UserRegister {
+                name: String::from("other"),
+                password: String::from("plum-orbit-42"),
+            },
+        )
+        .await;
+
+        let res = client
+            .post("http://localhost:8080/items")
+            .bearer_auth(&auth_token.token)
+            .json(&ItemDto {
+                name: String::from("item"),
+                price: 1.5,
+            })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 201);
+
+        let res = client
+            .patch("http://localhost:8080/user/me")
+            .bearer_auth(&auth_token.token)
+            .json(&UserPatchDto {
+                name: Some(String::from("other")),
+            })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 409);
+
+        let res = client
+            .patch("http://localhost:8080/user/me")
+            .bearer_auth(&auth_token.token)
+            .json(&UserPatchDto {
+                name: Some(String::from("renamed")),
+            })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        let renamed: UserProfile = res.json().await.unwrap();
+        assert_eq!(renamed.name, "renamed");
+
+        let res = client
+            .get(format!("http://localhost:8080/user/{}", renamed.id))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json::<UserProfile>().await.unwrap(), renamed);
+
+        let res = client
+            .delete("http://localhost:8080/user/me")
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 204);
+
+        let res = client
+            .get(format!("http://localhost:8080/user/{}", renamed.id))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 404);
+
+        let res = client
+            .get("http://localhost:8080/auth/me")
+            .bearer_auth(&auth_token.token)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 401);
+    })
+    .await;
+
+    delete_user(&pool, "other").await;
 }
@@ -0,0 +1,1030 @@
+use std::sync::Arc;
+
+use actix_web::{
+    App,
+    body::MessageBody,
+    dev::{Service, ServiceResponse},
+    http::header,
+    test, web,
+};
+use api::config::Config;
+use api::contract::repo::user_repo_trait::IUserRepo;
+use api::contract::service::user_service_trait::IUserService;
+use api::dto::admin_dto::RolesDto;
+use api::dto::api_key_dto::ApiKeyDto;
+use api::dto::auth_dto::RefreshDto;
+use api::dto::email_dto::EmailDto;
+use api::dto::item_dto::{ItemDto, ItemPatchDto};
+use api::dto::mfa_dto::{MfaChallengeDto, MfaCodeDto};
+use api::dto::password_dto::{ForgotPasswordDto, PasswordChangeDto, PasswordResetDto};
+use api::dto::user_dto::UserPatchDto;
+use api::entity::api_key_entity::{ApiKeyCreated, ApiKeyView, SCOPE_ITEMS_READ};
+use api::entity::auth_entity::{AuthMe, AuthResponse, MfaChallenge};
+use api::entity::item_entity::ItemFetched;
+use api::entity::mfa_entity::{MfaEnrollment, RecoveryCodes};
+use api::entity::role_entity::{ROLE_ADMIN, ROLE_USER, UserSummary};
+use api::entity::session_entity::SessionView;
+use api::entity::user_entity::{UserEmail, UserProfile, UserRegister};
+use api::error::{AppError, ProblemDetails};
+use api::repo::storage::Storage;
+use api::routes;
+use api::service::{
+    login_throttle::LoginThrottling,
+    mailer::{MailTransport, SmtpSecurity, SmtpSettings},
+    notifier::NotifierSink,
+    token_keys::{TokenKeyFiles, TokenSigning},
+    totp,
+};
+use api::state::AppState;
+use async_trait::async_trait;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header, jwk::JwkSet};
+
+async fn memory_app() -> impl Service<
+    actix_http::Request,
+    Response = ServiceResponse<impl MessageBody>,
+    Error = actix_web::Error,
+> {
+    memory_app_with(Config::default()).await
+}
+
+async fn memory_app_with(
+    config: Config,
+) -> impl Service<
+    actix_http::Request,
+    Response = ServiceResponse<impl MessageBody>,
+    Error = actix_web::Error,
+> {
+    let config = Config {
+        db_str: String::from("memory://"),
+        ..config
+    };
+
+    let storage = Storage::connect(&config).await.unwrap();
+
+    test::init_service(
+        App::new()
+            .app_data(web::Data::new(AppState::new(&storage, &config).unwrap()))
+            .configure(routes::configure),
+    )
+    .await
+}
+
+async fn register_and_auth<S, B>(app: &S, name: &str) -> AuthResponse
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let user = UserRegister {
+        name: String::from(name),
+        password: String::from("plum-orbit-42"),
+    };
+
+    let req = test::TestRequest::post()
+        .uri("/user")
+        .set_json(&user)
+        .to_request();
+    assert_eq!(test::call_service(app, req).await.status(), 201);
+
+    let req = test::TestRequest::post()
+        .uri("/auth")
+        .set_json(&user)
+        .to_request();
+    test::call_and_read_body_json(app, req).await
+}
+
+struct ClosedUserService;
+
+#[async_trait]
+impl IUserService for ClosedUserService {
+    async fn register(&self, _: &UserRegister) -> Result<(), AppError> {
+        Err(AppError::forbidden(
+            "registration_closed",
+            "Registration is closed.",
+        ))
+    }
+
+    async fn profile(&self, _: i32) -> Result<UserProfile, AppError> {
+        unimplemented!()
+    }
+
+    async fn update(&self, _: i32, _: &UserPatchDto) -> Result<UserProfile, AppError> {
+        unimplemented!()
+    }
+
+    async fn delete(&self, _: i32) -> Result<(), AppError> {
+        unimplemented!()
+    }
+}
+
+fn bearer(auth: &AuthResponse) -> (header::HeaderName, String) {
+    (header::AUTHORIZATION, format!("Bearer {}", auth.token))
+}
+
+#[actix_web::test]
+async fn memory_register_and_me() {
+    let app = memory_app().await;
+
+    let auth = register_and_auth(&app, "memory_me").await;
+
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(bearer(&auth))
+        .to_request();
+    let me: AuthMe = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(me.name, "memory_me");
+
+    let req = test::TestRequest::post()
+        .uri("/user")
+        .set_json(UserRegister {
+            name: String::from("memory_me"),
+            password: String::from("plum-orbit-42"),
+        })
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), 409);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "user_exists");
+}
+
+#[actix_web::test]
+async fn memory_register_weak_password() {
+    let app = memory_app().await;
+
+    let req = test::TestRequest::post()
+        .uri("/user")
+        .set_json(UserRegister {
+            name: String::from("memory_weak"),
+            password: String::from("qwerty"),
+        })
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), 400);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "weak_password");
+
+    let codes: Vec<_> = problem.errors.iter().map(|e| e.code.as_str()).collect();
+    assert_eq!(codes, vec!["password_too_short", "password_common"]);
+}
+
+#[actix_web::test]
+async fn memory_refresh_and_logout() {
+    let app = memory_app().await;
+
+    let auth = register_and_auth(&app, "memory_refresh").await;
+
+    let req = test::TestRequest::post()
+        .uri("/auth/refresh")
+        .set_json(RefreshDto {
+            refresh: auth.refresh.clone(),
+        })
+        .to_request();
+    let rotated: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/auth/logout-all")
+        .insert_header(bearer(&rotated))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(bearer(&rotated))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+}
+
+#[actix_web::test]
+async fn memory_items_crud() {
+    let app = memory_app().await;
+
+    let owner = register_and_auth(&app, "memory_owner").await;
+    let other = register_and_auth(&app, "memory_other").await;
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer(&owner))
+        .set_json(ItemDto {
+            name: String::from("item"),
+            price: 1.5,
+        })
+        .to_request();
+    let item: ItemFetched = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/items/{}", item.id))
+        .insert_header(bearer(&other))
+        .set_json(ItemPatchDto {
+            name: None,
+            price: Some(0.0),
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 403);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/items/{}", item.id))
+        .insert_header(bearer(&owner))
+        .set_json(ItemPatchDto {
+            name: None,
+            price: Some(2.0),
+        })
+        .to_request();
+    let updated: ItemFetched = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(updated.price, 2.0);
+
+    let req = test::TestRequest::get()
+        .uri("/items")
+        .insert_header(bearer(&owner))
+        .to_request();
+    let items: Vec<ItemFetched> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(items, vec![updated]);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/items/{}", item.id))
+        .insert_header(bearer(&owner))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/items/{}", item.id))
+        .insert_header(bearer(&owner))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn app_state_services_are_swappable() {
+    let config = Config {
+        db_str: String::from("memory://"),
+        ..Default::default()
+    };
+    let mut state = AppState::new(&Storage::memory(), &config).unwrap();
+    state.user_service = Arc::new(ClosedUserService);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/user")
+        .set_json(UserRegister {
+            name: String::from("memory_closed"),
+            password: String::from("plum-orbit-42"),
+        })
+        .to_request();
+    let res = test::call_service(&app, req).await;
+
+    assert_eq!(res.status(), 403);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "registration_closed");
+}
+
+#[actix_web::test]
+async fn memory_auth_lockout() {
+    let app = memory_app_with(Config {
+        login_throttling: LoginThrottling {
+            account_free_attempts: 2,
+            base_delay_secs: 60,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .await;
+    register_and_auth(&app, "nk").await;
+
+    let wrong = UserRegister {
+        name: String::from("nk"),
+        password: String::from("wrong-password"),
+    };
+    let attempt = || {
+        test::TestRequest::post()
+            .uri("/auth")
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .set_json(&wrong)
+            .to_request()
+    };
+
+    for _ in 0..3 {
+        let res = test::call_service(&app, attempt()).await;
+        assert_eq!(res.status(), 401);
+        let problem: ProblemDetails = test::read_body_json(res).await;
+        assert_eq!(problem.code, "invalid_credentials");
+    }
+
+    let res = test::call_service(&app, attempt()).await;
+
+    assert_eq!(res.status(), 429);
+    let retry_after: u64 = res
+        .headers()
+        .get(header::RETRY_AFTER)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after > 0 && retry_after <= 60);
+}
+
+#[actix_web::test]
+async fn memory_mfa_enroll_and_login() {
+    let app = memory_app().await;
+    let auth = register_and_auth(&app, "nk").await;
+
+    let req = test::TestRequest::post()
+        .uri("/user/mfa")
+        .insert_header(bearer(&auth))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 201);
+    let enrollment: MfaEnrollment = test::read_body_json(res).await;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let req = test::TestRequest::post()
+        .uri("/user/mfa/confirm")
+        .insert_header(bearer(&auth))
+        .set_json(MfaCodeDto {
+            code: totp::code_for(&enrollment.secret, now).unwrap(),
+        })
+        .to_request();
+    let recovery: RecoveryCodes = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/auth")
+        .set_json(UserRegister {
+            name: String::from("nk"),
+            password: String::from("plum-orbit-42"),
+        })
+        .to_request();
+    let challenge: MfaChallenge = test::call_and_read_body_json(&app, req).await;
+    assert!(challenge.mfa_required);
+
+    let exchange = |code: &str| {
+        test::TestRequest::post()
+            .uri("/auth/mfa")
+            .set_json(MfaChallengeDto {
+                challenge_token: challenge.challenge_token.clone(),
+                code: String::from(code),
+            })
+            .to_request()
+    };
+
+    let res = test::call_service(&app, exchange("000000x")).await;
+    assert_eq!(res.status(), 401);
+
+    let mfa_auth: AuthResponse =
+        test::call_and_read_body_json(&app, exchange(&recovery.recovery_codes[0])).await;
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(bearer(&mfa_auth))
+        .to_request();
+    let me: AuthMe = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(me.name, "nk");
+
+    let res = test::call_service(&app, exchange(&recovery.recovery_codes[0])).await;
+    assert_eq!(res.status(), 401, "recovery codes are single-use");
+}
+
+fn test_key(name: &str) -> String {
+    format!("{}/tests/keys/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[actix_web::test]
+async fn memory_tokens_verify_against_jwks() {
+    let app = memory_app_with(Config {
+        token_signing: TokenSigning {
+            signing_kid: Some(String::from("current")),
+            keys: vec![
+                TokenKeyFiles {
+                    kid: String::from("current"),
+                    algorithm: Algorithm::EdDSA,
+                    public_key: test_key("ed25519.pub.pem"),
+                    private_key: Some(test_key("ed25519.pem")),
+                },
+                TokenKeyFiles {
+                    kid: String::from("retired"),
+                    algorithm: Algorithm::RS256,
+                    public_key: test_key("rsa.pub.pem"),
+                    private_key: None,
+                },
+            ],
+        },
+        ..Default::default()
+    })
+    .await;
+    let auth = register_and_auth(&app, "nk").await;
+
+    let req = test::TestRequest::get()
+        .uri("/.well-known/jwks.json")
+        .to_request();
+    let jwks: JwkSet = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(jwks.keys.len(), 2);
+
+    let kid = decode_header(&auth.token).unwrap().kid.unwrap();
+    let key = DecodingKey::from_jwk(jwks.find(&kid).unwrap()).unwrap();
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.required_spec_claims.clear();
+
+    assert_eq!(kid, "current");
+    assert!(decode::<serde::de::IgnoredAny>(&auth.token, &key, &validation).is_ok());
+}
+
+#[actix_web::test]
+async fn memory_admin_manages_roles() {
+    let storage = Storage::memory();
+    let state = AppState::new(&storage, &Config::default()).unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+
+    register_and_auth(&app, "memory_root").await;
+    let member = register_and_auth(&app, "memory_member").await;
+
+    let req = test::TestRequest::get()
+        .uri("/admin/users")
+        .insert_header(bearer(&member))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 403);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "missing_permission");
+
+    // Stands in for `--grant-admin`.
+    let users = storage.user_repo();
+    let root = users.fetch_by_name("memory_root").await.unwrap();
+    let admin_roles = vec![String::from(ROLE_ADMIN), String::from(ROLE_USER)];
+    users.set_roles(root.id, &admin_roles).await.unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/auth")
+        .set_json(UserRegister {
+            name: String::from("memory_root"),
+            password: String::from("plum-orbit-42"),
+        })
+        .to_request();
+    let admin: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/admin/users?limit=10")
+        .insert_header(bearer(&admin))
+        .to_request();
+    let listed: Vec<UserSummary> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[0].roles, admin_roles);
+    assert_eq!(listed[1].roles, [ROLE_USER]);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/admin/users/{}/roles", listed[1].id))
+        .insert_header(bearer(&admin))
+        .set_json(RolesDto {
+            roles: admin_roles.clone(),
+        })
+        .to_request();
+    let promoted: UserSummary = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(promoted.roles, admin_roles);
+
+    // The member's old token doesn't carry the new role, so it is revoked.
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(bearer(&member))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    for (roles, status, code) in [
+        (vec![String::from(ROLE_USER)], 403, "cannot_demote_self"),
+        (
+            vec![String::from(ROLE_ADMIN), String::from("wizard")],
+            400,
+            "unknown_role",
+        ),
+    ] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/admin/users/{}/roles", root.id))
+            .insert_header(bearer(&admin))
+            .set_json(RolesDto { roles })
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), status);
+        let problem: ProblemDetails = test::read_body_json(res).await;
+        assert_eq!(problem.code, code);
+    }
+}
+
+#[actix_web::test]
+async fn memory_api_key_lifecycle() {
+    let storage = Storage::memory();
+    let state = AppState::new(&storage, &Config::default()).unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+    let auth = register_and_auth(&app, "memory_ci").await;
+
+    let create_key = || {
+        test::TestRequest::post()
+            .uri("/user/api-keys")
+            .insert_header(bearer(&auth))
+            .set_json(ApiKeyDto {
+                name: String::from("ci"),
+                scopes: vec![String::from(SCOPE_ITEMS_READ)],
+                expires_at: None,
+            })
+            .to_request()
+    };
+    let res = test::call_service(&app, create_key()).await;
+    assert_eq!(res.status(), 403);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "email_unverified");
+
+    let users = storage.user_repo();
+    let user = users.fetch_by_name("memory_ci").await.unwrap();
+    users
+        .set_email(user.id, Some("ci@example.com"))
+        .await
+        .unwrap();
+    assert!(
+        users
+            .verify_email(user.id, "ci@example.com", 0)
+            .await
+            .unwrap()
+    );
+
+    let res = test::call_service(&app, create_key()).await;
+    assert_eq!(res.status(), 201);
+    let created: ApiKeyCreated = test::read_body_json(res).await;
+
+    // Even an admin's key carries no roles, so it can't pass a role guard.
+    users
+        .set_roles(
+            user.id,
+            &[String::from(ROLE_ADMIN), String::from(ROLE_USER)],
+        )
+        .await
+        .unwrap();
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(("X-Api-Key", created.key.as_str()))
+        .to_request();
+    let me: AuthMe = test::call_and_read_body_json(&app, req).await;
+    assert!(me.roles.is_empty());
+
+    let req = test::TestRequest::get()
+        .uri("/items")
+        .insert_header(("X-Api-Key", created.key.as_str()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header((header::AUTHORIZATION, format!("ApiKey {}", created.key)))
+        .set_json(ItemDto {
+            name: String::from("widget"),
+            price: 1.0,
+        })
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 403);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "missing_scope");
+
+    // A key can't be used to mint more keys.
+    let req = test::TestRequest::get()
+        .uri("/user/api-keys")
+        .insert_header(("X-Api-Key", created.key.as_str()))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 403);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "session_required");
+
+    // Nor log out sessions it doesn't belong to.
+    for uri in ["/auth/logout", "/auth/logout-all"] {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .insert_header(("X-Api-Key", created.key.as_str()))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 403);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/user/api-keys")
+        .insert_header(bearer(&auth))
+        .to_request();
+    let listed: Vec<ApiKeyView> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].prefix, created.api_key.prefix);
+    assert!(listed[0].last_used_at.is_some());
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/user/api-keys/{}", created.api_key.id))
+        .insert_header(bearer(&auth))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri("/items")
+        .insert_header(("X-Api-Key", created.key.as_str()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+}
+
+#[actix_web::test]
+async fn memory_sessions_list_and_revoke() {
+    let app = memory_app().await;
+    let laptop = register_and_auth(&app, "memory_sessions").await;
+
+    let req = test::TestRequest::post()
+        .uri("/auth")
+        .insert_header((header::USER_AGENT, "phone"))
+        .set_json(UserRegister {
+            name: String::from("memory_sessions"),
+            password: String::from("plum-orbit-42"),
+        })
+        .to_request();
+    let phone: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/auth/sessions")
+        .insert_header(bearer(&laptop))
+        .to_request();
+    let sessions: Vec<SessionView> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(sessions.len(), 2);
+    let other = sessions.iter().find(|s| !s.current).unwrap();
+    assert_eq!(other.user_agent.as_deref(), Some("phone"));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/auth/sessions/{}", other.id))
+        .insert_header(bearer(&laptop))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(bearer(&phone))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    let req = test::TestRequest::post()
+        .uri("/auth/refresh")
+        .set_json(RefreshDto {
+            refresh: phone.refresh.clone(),
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/auth/sessions/{}", other.id))
+        .insert_header(bearer(&laptop))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 404);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "session_not_found");
+}
+
+async fn login<S, B>(app: &S, name: &str, password: &str) -> ServiceResponse<B>
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let req = test::TestRequest::post()
+        .uri("/auth")
+        .set_json(UserRegister {
+            name: String::from(name),
+            password: String::from(password),
+        })
+        .to_request();
+    test::call_service(app, req).await
+}
+
+#[actix_web::test]
+async fn memory_password_change_and_reset() {
+    let outbox = std::env::temp_dir().join(format!("reset-{}.log", std::process::id()));
+    let app = memory_app_with(Config {
+        notifier: NotifierSink::File {
+            path: outbox.to_string_lossy().into_owned(),
+        },
+        ..Default::default()
+    })
+    .await;
+    let laptop = register_and_auth(&app, "memory_password").await;
+    let phone: AuthResponse =
+        test::read_body_json(login(&app, "memory_password", "plum-orbit-42").await).await;
+
+    let req = test::TestRequest::put()
+        .uri("/user/password")
+        .insert_header(bearer(&laptop))
+        .set_json(PasswordChangeDto {
+            current_password: String::from("wrong-password"),
+            new_password: String::from("quartz-lemon-17"),
+        })
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "invalid_current_password");
+
+    let req = test::TestRequest::put()
+        .uri("/user/password")
+        .insert_header(bearer(&laptop))
+        .set_json(PasswordChangeDto {
+            current_password: String::from("plum-orbit-42"),
+            new_password: String::from("quartz-lemon-17"),
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    // Only the session that changed the password survives.
+    for (auth, status) in [(&laptop, 200), (&phone, 401)] {
+        let req = test::TestRequest::get()
+            .uri("/auth/me")
+            .insert_header(bearer(auth))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), status);
+    }
+    let res = login(&app, "memory_password", "plum-orbit-42").await;
+    assert_eq!(res.status(), 401);
+
+    for name in ["memory_password", "memory_nobody"] {
+        let req = test::TestRequest::post()
+            .uri("/auth/forgot")
+            .set_json(ForgotPasswordDto {
+                name: String::from(name),
+            })
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), 202);
+    }
+
+    // Resets are sent off the request path.
+    let mut sent = String::new();
+    for _ in 0..100 {
+        sent = std::fs::read_to_string(&outbox).unwrap_or_default();
+        if !sent.is_empty() {
+            break;
+        }
+        actix_web::rt::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    std::fs::remove_file(&outbox).unwrap();
+    assert_eq!(sent.lines().count(), 1);
+    let token = sent
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("token="))
+        .unwrap();
+
+    let reset = PasswordResetDto {
+        token: String::from(token),
+        password: String::from("velvet-comet-93"),
+    };
+    let req = test::TestRequest::post()
+        .uri("/auth/reset")
+        .set_json(&reset)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(bearer(&laptop))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+    let res = login(&app, "memory_password", "velvet-comet-93").await;
+    assert_eq!(res.status(), 200);
+
+    let req = test::TestRequest::post()
+        .uri("/auth/reset")
+        .set_json(&reset)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "invalid_reset_token");
+}
+
+/// Just enough of an SMTP server to accept mail in plain text. Each message's
+/// DATA section is sent on the returned channel.
+async fn smtp_stand_in() -> (u16, tokio::sync::mpsc::UnboundedReceiver<String>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                write.write_all(b"220 stand-in ESMTP\r\n").await.unwrap();
+
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let verb = line.split(' ').next().unwrap_or("").to_uppercase();
+                    let reply: &[u8] = match verb.as_str() {
+                        "EHLO" | "HELO" => b"250 stand-in\r\n",
+                        "DATA" => {
+                            write.write_all(b"354 go ahead\r\n").await.unwrap();
+                            let mut data = String::new();
+                            while let Ok(Some(line)) = lines.next_line().await {
+                                if line == "." {
+                                    break;
+                                }
+                                data.push_str(&line);
+                                data.push_str("\r\n");
+                            }
+                            tx.send(data).unwrap();
+                            b"250 queued\r\n"
+                        }
+                        "QUIT" => {
+                            write.write_all(b"221 bye\r\n").await.unwrap();
+                            break;
+                        }
+                        _ => b"250 ok\r\n",
+                    };
+                    write.write_all(reply).await.unwrap();
+                }
+            });
+        }
+    });
+
+    (port, rx)
+}
+
+#[actix_web::test]
+async fn memory_email_verification_over_smtp() {
+    let (port, mut inbox) = smtp_stand_in().await;
+    let app = memory_app_with(Config {
+        mailer: MailTransport::Smtp(SmtpSettings {
+            host: String::from("127.0.0.1"),
+            port,
+            security: SmtpSecurity::None,
+            ..Default::default()
+        }),
+        mail_from: String::from("API <noreply@example.com>"),
+        public_url: String::from("https://api.example.com"),
+        ..Default::default()
+    })
+    .await;
+    let auth = register_and_auth(&app, "memory_mail").await;
+
+    let req = test::TestRequest::post()
+        .uri("/user/email/verification")
+        .insert_header(bearer(&auth))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "email_missing");
+
+    let req = test::TestRequest::put()
+        .uri("/user/email")
+        .insert_header(bearer(&auth))
+        .set_json(EmailDto {
+            email: String::from("Mail@Example.com"),
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 202);
+
+    let req = test::TestRequest::get()
+        .uri("/user/email")
+        .insert_header(bearer(&auth))
+        .to_request();
+    let email: UserEmail = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(email.email.as_deref(), Some("mail@example.com"));
+    assert!(!email.is_verified());
+
+    let message = inbox.recv().await.unwrap();
+    assert!(message.contains("To: mail@example.com\r\n"));
+    assert!(message.contains("Subject: Verify your email address\r\n"));
+
+    // Undo quoted-printable soft line breaks and escapes.
+    let body = message.replace("=\r\n", "").replace("=3D", "=");
+    let (_, link) = body
+        .split_once("https://api.example.com/user/email/verify?token=")
+        .unwrap();
+    let token = link.split_whitespace().next().unwrap();
+
+    let req = test::TestRequest::get()
+        .uri("/user/email/verify?token=not-a-token")
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 400);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "invalid_verification_token");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/user/email/verify?token={token}"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    let req = test::TestRequest::post()
+        .uri("/user/api-keys")
+        .insert_header(bearer(&auth))
+        .set_json(ApiKeyDto {
+            name: String::from("ci"),
+            scopes: vec![String::from(SCOPE_ITEMS_READ)],
+            expires_at: None,
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::post()
+        .uri("/user/email/verification")
+        .insert_header(bearer(&auth))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 409);
+}
+
+#[actix_web::test]
+async fn memory_profile_rename_and_delete() {
+    let storage = Storage::memory();
+    let state = AppState::new(&storage, &Config::default()).unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .configure(routes::configure),
+    )
+    .await;
+    let auth = register_and_auth(&app, "memory_profile").await;
+    register_and_auth(&app, "memory_taken").await;
+    let id = storage
+        .user_repo()
+        .fetch_by_name("memory_profile")
+        .await
+        .unwrap()
+        .id;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/user/{id}"))
+        .to_request();
+    let profile: UserProfile = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(profile.name, "memory_profile");
+
+    let rename = |name: &str| {
+        test::TestRequest::patch()
+            .uri("/user/me")
+            .insert_header(bearer(&auth))
+            .set_json(UserPatchDto {
+                name: Some(String::from(name)),
+            })
+            .to_request()
+    };
+    let res = test::call_service(&app, rename("memory_taken")).await;
+    assert_eq!(res.status(), 409);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "user_exists");
+
+    let renamed: UserProfile = test::call_and_read_body_json(&app, rename("memory_renamed")).await;
+    assert_eq!(renamed.id, id);
+    assert_eq!(renamed.name, "memory_renamed");
+
+    let req = test::TestRequest::post()
+        .uri("/items")
+        .insert_header(bearer(&auth))
+        .set_json(ItemDto {
+            name: String::from("widget"),
+            price: 1.0,
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::delete()
+        .uri("/user/me")
+        .insert_header(bearer(&auth))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/user/{id}"))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status(), 404);
+    let problem: ProblemDetails = test::read_body_json(res).await;
+    assert_eq!(problem.code, "user_not_found");
+
+    let req = test::TestRequest::get()
+        .uri("/auth/me")
+        .insert_header(bearer(&auth))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    // The name is free again and nothing of the old account carries over.
+    let auth = register_and_auth(&app, "memory_renamed").await;
+    let req = test::TestRequest::get()
+        .uri("/items")
+        .insert_header(bearer(&auth))
+        .to_request();
+    let items: Vec<ItemFetched> = test::call_and_read_body_json(&app, req).await;
+    assert!(items.is_empty());
+}
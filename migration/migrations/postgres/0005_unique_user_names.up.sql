@@ -0,0 +1,2 @@
+ALTER TABLE users
+    ADD CONSTRAINT users_name_key UNIQUE (name);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS user_mfa (
+    user_id INTEGER PRIMARY KEY,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    last_step BIGINT NOT NULL DEFAULT 0,
+
+    CONSTRAINT fk_user_mfa_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+
+CREATE TABLE IF NOT EXISTS mfa_recovery_codes (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    code_hash TEXT NOT NULL,
+    used BOOLEAN NOT NULL DEFAULT FALSE,
+
+    CONSTRAINT fk_mfa_recovery_codes_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
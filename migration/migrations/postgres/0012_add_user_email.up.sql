@@ -0,0 +1,6 @@
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS email TEXT,
+    ADD COLUMN IF NOT EXISTS email_verified_at BIGINT;
+
+-- Addresses are stored lowercased; any number of users may have none.
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_key ON users (email);
@@ -0,0 +1,47 @@
+ALTER TABLE items
+    DROP CONSTRAINT fk_items_user,
+    ADD CONSTRAINT fk_items_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
+
+ALTER TABLE refresh_tokens
+    DROP CONSTRAINT fk_refresh_tokens_user,
+    ADD CONSTRAINT fk_refresh_tokens_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
+
+ALTER TABLE user_mfa
+    DROP CONSTRAINT fk_user_mfa_user,
+    ADD CONSTRAINT fk_user_mfa_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
+
+ALTER TABLE mfa_recovery_codes
+    DROP CONSTRAINT fk_mfa_recovery_codes_user,
+    ADD CONSTRAINT fk_mfa_recovery_codes_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
+
+ALTER TABLE user_roles
+    DROP CONSTRAINT fk_user_roles_user,
+    ADD CONSTRAINT fk_user_roles_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
+
+ALTER TABLE api_keys
+    DROP CONSTRAINT fk_api_keys_user,
+    ADD CONSTRAINT fk_api_keys_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
+
+ALTER TABLE sessions
+    DROP CONSTRAINT fk_sessions_user,
+    ADD CONSTRAINT fk_sessions_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
+
+ALTER TABLE password_resets
+    DROP CONSTRAINT fk_password_resets_user,
+    ADD CONSTRAINT fk_password_resets_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id);
@@ -0,0 +1,4 @@
+-- Fails while two users share an address, one of them unverified.
+DROP INDEX IF EXISTS users_verified_email_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_key ON users (email);
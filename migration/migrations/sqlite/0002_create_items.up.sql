@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    price REAL NOT NULL,
+
+    CONSTRAINT fk_items_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
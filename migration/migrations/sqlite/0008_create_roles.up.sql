@@ -0,0 +1,48 @@
+CREATE TABLE IF NOT EXISTS roles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS permissions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role_id INTEGER NOT NULL,
+    permission_id INTEGER NOT NULL,
+
+    PRIMARY KEY (role_id, permission_id),
+    CONSTRAINT fk_role_permissions_role
+            FOREIGN KEY (role_id)
+            REFERENCES roles (id),
+    CONSTRAINT fk_role_permissions_permission
+            FOREIGN KEY (permission_id)
+            REFERENCES permissions (id)
+);
+
+CREATE TABLE IF NOT EXISTS user_roles (
+    user_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+
+    PRIMARY KEY (user_id, role_id),
+    CONSTRAINT fk_user_roles_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id),
+    CONSTRAINT fk_user_roles_role
+            FOREIGN KEY (role_id)
+            REFERENCES roles (id)
+);
+
+INSERT INTO roles (name) VALUES ('user'), ('admin');
+INSERT INTO permissions (name) VALUES ('users:read'), ('users:manage');
+
+INSERT INTO role_permissions (role_id, permission_id)
+SELECT roles.id, permissions.id
+FROM roles, permissions
+WHERE roles.name = 'admin';
+
+INSERT INTO user_roles (user_id, role_id)
+SELECT users.id, roles.id
+FROM users, roles
+WHERE roles.name = 'user';
@@ -0,0 +1,5 @@
+ALTER TABLE users ADD COLUMN email TEXT;
+ALTER TABLE users ADD COLUMN email_verified_at INTEGER;
+
+-- Addresses are stored lowercased; any number of users may have none.
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_key ON users (email);
@@ -0,0 +1,125 @@
+CREATE TABLE items_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    price REAL NOT NULL,
+
+    CONSTRAINT fk_items_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+INSERT INTO items_new SELECT * FROM items;
+DROP TABLE items;
+ALTER TABLE items_new RENAME TO items;
+
+CREATE TABLE refresh_tokens_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    family TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    used BOOLEAN NOT NULL DEFAULT FALSE,
+    revoked BOOLEAN NOT NULL DEFAULT FALSE,
+
+    CONSTRAINT fk_refresh_tokens_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+INSERT INTO refresh_tokens_new SELECT * FROM refresh_tokens;
+DROP TABLE refresh_tokens;
+ALTER TABLE refresh_tokens_new RENAME TO refresh_tokens;
+
+CREATE TABLE user_mfa_new (
+    user_id INTEGER PRIMARY KEY,
+    secret TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    last_step INTEGER NOT NULL DEFAULT 0,
+
+    CONSTRAINT fk_user_mfa_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+INSERT INTO user_mfa_new SELECT * FROM user_mfa;
+DROP TABLE user_mfa;
+ALTER TABLE user_mfa_new RENAME TO user_mfa;
+
+CREATE TABLE mfa_recovery_codes_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    code_hash TEXT NOT NULL,
+    used BOOLEAN NOT NULL DEFAULT FALSE,
+
+    CONSTRAINT fk_mfa_recovery_codes_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+INSERT INTO mfa_recovery_codes_new SELECT * FROM mfa_recovery_codes;
+DROP TABLE mfa_recovery_codes;
+ALTER TABLE mfa_recovery_codes_new RENAME TO mfa_recovery_codes;
+
+CREATE TABLE user_roles_new (
+    user_id INTEGER NOT NULL,
+    role_id INTEGER NOT NULL,
+
+    PRIMARY KEY (user_id, role_id),
+    CONSTRAINT fk_user_roles_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id),
+    CONSTRAINT fk_user_roles_role
+            FOREIGN KEY (role_id)
+            REFERENCES roles (id)
+);
+INSERT INTO user_roles_new SELECT * FROM user_roles;
+DROP TABLE user_roles;
+ALTER TABLE user_roles_new RENAME TO user_roles;
+
+CREATE TABLE api_keys_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER,
+    last_used_at INTEGER,
+
+    CONSTRAINT fk_api_keys_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+INSERT INTO api_keys_new SELECT * FROM api_keys;
+DROP TABLE api_keys;
+ALTER TABLE api_keys_new RENAME TO api_keys;
+
+CREATE TABLE sessions_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    family TEXT NOT NULL UNIQUE,
+    user_agent TEXT,
+    ip TEXT,
+    created_at INTEGER NOT NULL,
+    last_seen_at INTEGER NOT NULL,
+
+    CONSTRAINT fk_sessions_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+INSERT INTO sessions_new SELECT * FROM sessions;
+DROP TABLE sessions;
+ALTER TABLE sessions_new RENAME TO sessions;
+
+CREATE TABLE password_resets_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    expires_at INTEGER NOT NULL,
+    used BOOLEAN NOT NULL DEFAULT FALSE,
+
+    CONSTRAINT fk_password_resets_user
+            FOREIGN KEY (user_id)
+            REFERENCES users (id)
+);
+INSERT INTO password_resets_new SELECT * FROM password_resets;
+DROP TABLE password_resets;
+ALTER TABLE password_resets_new RENAME TO password_resets;
@@ -0,0 +1,7 @@
+-- Only a verified address is claimed, so nobody can reserve someone else's
+-- address by setting it first.
+DROP INDEX IF EXISTS users_email_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_verified_email_key
+    ON users (email)
+    WHERE email_verified_at IS NOT NULL;
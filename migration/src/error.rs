@@ -0,0 +1,13 @@
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("migration {version} ({name}) was edited after it was applied")]
+    ChecksumMismatch { version: i64, name: String },
+    #[error("migration {0} is applied but no longer exists")]
+    UnknownVersion(i64),
+    #[error("no migrations for the {0} backend")]
+    UnsupportedBackend(String),
+    #[error("unknown command `{0}`, expected one of: up, down, status, redo")]
+    UnknownCommand(String),
+}
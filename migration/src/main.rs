@@ -1,23 +1,43 @@
-mod item;
-mod user;
-
 use dotenvy::dotenv;
-use sqlx::postgres::PgPoolOptions;
+use migration::{
+    error::MigrationError,
+    runner::{self, Migrator},
+};
 use std::env;
 
 #[tokio::main]
-async fn main() -> Result<(), sqlx::Error> {
+async fn main() -> Result<(), MigrationError> {
     dotenv().ok();
 
+    let command = env::args().nth(1).unwrap_or(String::from("up"));
+
     let db_str = env::var("db_str").unwrap();
+    let mut conn = runner::connect(&db_str).await?;
 
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&db_str)
-        .await?;
+    let migrator = Migrator::for_connection(&conn)?;
 
-    user::create(&pool).await;
-    item::create(&pool).await;
+    match command.as_str() {
+        "up" => {
+            for migration in migrator.up_locked(&mut conn).await? {
+                println!("applied {}", migration.name);
+            }
+        }
+        "down" => match migrator.down_locked(&mut conn).await? {
+            Some(migration) => println!("reverted {}", migration.name),
+            None => println!("nothing to revert"),
+        },
+        "redo" => match migrator.redo_locked(&mut conn).await? {
+            Some(migration) => println!("redone {}", migration.name),
+            None => println!("nothing to redo"),
+        },
+        "status" => {
+            for status in migrator.status(&mut conn).await? {
+                let state = if status.applied { "applied" } else { "pending" };
+                println!("{state:<8} {}", status.name);
+            }
+        }
+        other => return Err(MigrationError::UnknownCommand(String::from(other))),
+    }
 
     Ok(())
 }
@@ -0,0 +1,117 @@
+use sha2::{Digest, Sha256};
+
+/// A schema change embedded from `migrations/<backend>/<name>.{up,down}.sql`.
+#[derive(Debug)]
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        hex::encode(Sha256::digest(self.up.as_bytes()))
+    }
+}
+
+macro_rules! migration {
+    ($backend:literal, $version:expr, $name:literal) => {
+        Migration {
+            version: $version,
+            name: $name,
+            up: include_str!(concat!("../migrations/", $backend, "/", $name, ".up.sql")),
+            down: include_str!(concat!("../migrations/", $backend, "/", $name, ".down.sql")),
+        }
+    };
+}
+
+/// Every migration, in the order it must be applied. Never edit an entry once
+/// it has shipped: add a new one instead.
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    migration!("postgres", 1, "0001_create_users"),
+    migration!("postgres", 2, "0002_create_items"),
+    migration!("postgres", 3, "0003_create_refresh_tokens"),
+    migration!("postgres", 4, "0004_create_revoked_tokens"),
+    migration!("postgres", 5, "0005_unique_user_names"),
+    migration!("postgres", 6, "0006_create_login_attempts"),
+    migration!("postgres", 7, "0007_create_user_mfa"),
+    migration!("postgres", 8, "0008_create_roles"),
+    migration!("postgres", 9, "0009_create_api_keys"),
+    migration!("postgres", 10, "0010_create_sessions"),
+    migration!("postgres", 11, "0011_create_password_resets"),
+    migration!("postgres", 12, "0012_add_user_email"),
+    migration!("postgres", 13, "0013_cascade_user_deletes"),
+    migration!("postgres", 14, "0014_unique_verified_emails"),
+];
+
+/// SQLite counterpart of `POSTGRES_MIGRATIONS`; versions must stay in step.
+#[cfg(feature = "sqlite")]
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    migration!("sqlite", 1, "0001_create_users"),
+    migration!("sqlite", 2, "0002_create_items"),
+    migration!("sqlite", 3, "0003_create_refresh_tokens"),
+    migration!("sqlite", 4, "0004_create_revoked_tokens"),
+    migration!("sqlite", 5, "0005_unique_user_names"),
+    migration!("sqlite", 6, "0006_create_login_attempts"),
+    migration!("sqlite", 7, "0007_create_user_mfa"),
+    migration!("sqlite", 8, "0008_create_roles"),
+    migration!("sqlite", 9, "0009_create_api_keys"),
+    migration!("sqlite", 10, "0010_create_sessions"),
+    migration!("sqlite", 11, "0011_create_password_resets"),
+    migration!("sqlite", 12, "0012_add_user_email"),
+    migration!("sqlite", 13, "0013_cascade_user_deletes"),
+    migration!("sqlite", 14, "0014_unique_verified_emails"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_strictly_increasing() {
+        assert!(
+            POSTGRES_MIGRATIONS
+                .windows(2)
+                .all(|w| w[0].version < w[1].version)
+        );
+    }
+
+    #[test]
+    fn names_match_versions() {
+        for migration in POSTGRES_MIGRATIONS {
+            let prefix = format!("{:04}_", migration.version);
+            assert!(migration.name.starts_with(&prefix), "{}", migration.name);
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_matches_postgres_versions() {
+        let versions = |migrations: &[Migration]| {
+            migrations
+                .iter()
+                .map(|m| (m.version, m.name))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(versions(SQLITE_MIGRATIONS), versions(POSTGRES_MIGRATIONS));
+    }
+
+    #[test]
+    fn checksum_tracks_up_script() {
+        let migration = Migration {
+            version: 1,
+            name: "0001_test",
+            up: "SELECT 1;",
+            down: "",
+        };
+        let edited = Migration {
+            up: "SELECT 2;",
+            ..migration
+        };
+
+        assert_eq!(migration.checksum().len(), 64);
+        assert_ne!(migration.checksum(), edited.checksum());
+    }
+}
@@ -0,0 +1,415 @@
+use sqlx::{AnyConnection, Connection, FromRow};
+
+#[cfg(feature = "sqlite")]
+use crate::migrations::SQLITE_MIGRATIONS;
+use crate::{
+    error::MigrationError,
+    migrations::{Migration, POSTGRES_MIGRATIONS},
+};
+
+const POSTGRES: &str = "PostgreSQL";
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied: bool,
+}
+
+/// Advisory lock key shared by every process that migrates this database.
+const MIGRATION_LOCK_KEY: i64 = 0x6d69_6772_6174_6530;
+
+pub struct Migrator {
+    migrations: &'static [Migration],
+}
+
+/// Opens a connection for whichever backend `db_str` points at.
+pub async fn connect(db_str: &str) -> Result<AnyConnection, MigrationError> {
+    sqlx::any::install_default_drivers();
+    Ok(AnyConnection::connect(db_str).await?)
+}
+
+impl Migrator {
+    pub fn new(migrations: &'static [Migration]) -> Self {
+        Self { migrations }
+    }
+
+    /// Picks the embedded migration set matching the connection's backend.
+    pub fn for_connection(conn: &AnyConnection) -> Result<Self, MigrationError> {
+        match conn.backend_name() {
+            POSTGRES => Ok(Self::new(POSTGRES_MIGRATIONS)),
+            #[cfg(feature = "sqlite")]
+            "SQLite" => Ok(Self::new(SQLITE_MIGRATIONS)),
+            other => Err(MigrationError::UnsupportedBackend(String::from(other))),
+        }
+    }
+
+    async fn ensure_table(&self, conn: &mut AnyConnection) -> Result<(), MigrationError> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version BIGINT PRIMARY KEY,
+                name TEXT NOT NULL,
+                checksum TEXT NOT NULL,
+                applied_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"#,
+        )
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn applied(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Vec<AppliedMigration>, MigrationError> {
+        self.ensure_table(conn).await?;
+
+        let applied = sqlx::query_as(
+            "SELECT version, name, checksum FROM schema_migrations ORDER BY version",
+        )
+        .fetch_all(conn)
+        .await?;
+
+        Ok(applied)
+    }
+
+    /// Refuses to go on when history no longer matches the embedded migrations.
+    pub fn verify(&self, applied: &[AppliedMigration]) -> Result<(), MigrationError> {
+        for row in applied {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|m| m.version == row.version)
+                .ok_or(MigrationError::UnknownVersion(row.version))?;
+
+            if migration.checksum() != row.checksum {
+                return Err(MigrationError::ChecksumMismatch {
+                    version: row.version,
+                    name: row.name.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn checked(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Vec<AppliedMigration>, MigrationError> {
+        let applied = self.applied(conn).await?;
+        self.verify(&applied)?;
+        Ok(applied)
+    }
+
+    async fn apply(
+        &self,
+        conn: &mut AnyConnection,
+        migration: &Migration,
+    ) -> Result<(), MigrationError> {
+        let mut tx = conn.begin().await?;
+
+        sqlx::raw_sql(migration.up).execute(&mut *tx).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .bind(migration.checksum())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn revert(
+        &self,
+        conn: &mut AnyConnection,
+        migration: &Migration,
+    ) -> Result<(), MigrationError> {
+        let mut tx = conn.begin().await?;
+
+        sqlx::raw_sql(migration.down).execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM schema_migrations WHERE version = $1")
+            .bind(migration.version)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    fn find(&self, version: i64) -> &'static Migration {
+        // `verify` already guaranteed every applied version is known.
+        self.migrations
+            .iter()
+            .find(|m| m.version == version)
+            .unwrap()
+    }
+
+    /// Applies every pending migration, each in its own transaction.
+    pub async fn up(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Vec<&'static Migration>, MigrationError> {
+        let applied = self.checked(conn).await?;
+
+        let pending: Vec<_> = self
+            .migrations
+            .iter()
+            .filter(|m| !applied.iter().any(|a| a.version == m.version))
+            .collect();
+
+        for migration in &pending {
+            self.apply(conn, migration).await?;
+        }
+
+        Ok(pending)
+    }
+
+    /// Runs `op` while holding a Postgres advisory lock, so replicas starting
+    /// together and manual `down`/`redo` runs never migrate at the same time.
+    /// SQLite is single-node, so there is nothing to coordinate.
+    async fn locked<T>(
+        &self,
+        conn: &mut AnyConnection,
+        op: impl AsyncFnOnce(&mut AnyConnection) -> Result<T, MigrationError>,
+    ) -> Result<T, MigrationError> {
+        if conn.backend_name() != POSTGRES {
+            return op(conn).await;
+        }
+
+        sqlx::query("SELECT pg_advisory_lock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await?;
+
+        let result = op(conn).await;
+
+        sqlx::query("SELECT pg_advisory_unlock($1)")
+            .bind(MIGRATION_LOCK_KEY)
+            .execute(&mut *conn)
+            .await?;
+
+        result
+    }
+
+    /// Same as `up`, but under the migration lock so replicas starting
+    /// together apply each migration exactly once.
+    pub async fn up_locked(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Vec<&'static Migration>, MigrationError> {
+        self.locked(conn, async |conn| self.up(conn).await).await
+    }
+
+    /// Same as `down`, but under the migration lock.
+    pub async fn down_locked(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Option<&'static Migration>, MigrationError> {
+        self.locked(conn, async |conn| self.down(conn).await).await
+    }
+
+    /// Same as `redo`, but under the migration lock.
+    pub async fn redo_locked(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Option<&'static Migration>, MigrationError> {
+        self.locked(conn, async |conn| self.redo(conn).await).await
+    }
+
+    /// Reverts the most recently applied migration.
+    pub async fn down(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Option<&'static Migration>, MigrationError> {
+        let applied = self.checked(conn).await?;
+
+        let Some(last) = applied.last() else {
+            return Ok(None);
+        };
+
+        let migration = self.find(last.version);
+        self.revert(conn, migration).await?;
+
+        Ok(Some(migration))
+    }
+
+    /// Reverts and re-applies the most recently applied migration.
+    pub async fn redo(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Option<&'static Migration>, MigrationError> {
+        let migration = self.down(conn).await?;
+
+        if let Some(migration) = migration {
+            self.apply(conn, migration).await?;
+        }
+
+        Ok(migration)
+    }
+
+    pub async fn status(
+        &self,
+        conn: &mut AnyConnection,
+    ) -> Result<Vec<MigrationStatus>, MigrationError> {
+        let applied = self.checked(conn).await?;
+
+        Ok(self
+            .migrations
+            .iter()
+            .map(|m| MigrationStatus {
+                version: m.version,
+                name: m.name,
+                applied: applied.iter().any(|a| a.version == m.version),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dotenvy::dotenv;
+    use std::env;
+
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "0001_create_widgets",
+            up: "CREATE TABLE widgets (id SERIAL PRIMARY KEY);",
+            down: "DROP TABLE widgets;",
+        },
+        Migration {
+            version: 2,
+            name: "0002_add_widget_name",
+            up: "ALTER TABLE widgets ADD COLUMN name TEXT;",
+            down: "ALTER TABLE widgets DROP COLUMN name;",
+        },
+    ];
+
+    fn applied(version: i64, checksum: String) -> AppliedMigration {
+        AppliedMigration {
+            version,
+            name: String::from("applied"),
+            checksum,
+        }
+    }
+
+    #[test]
+    fn verify_accepts_matching_history() {
+        let migrator = Migrator::new(TEST_MIGRATIONS);
+        let history = vec![applied(1, TEST_MIGRATIONS[0].checksum())];
+
+        assert!(migrator.verify(&history).is_ok());
+    }
+
+    #[test]
+    fn verify_rejects_edited_migration() {
+        let migrator = Migrator::new(TEST_MIGRATIONS);
+        let history = vec![applied(1, String::from("edited"))];
+
+        assert!(matches!(
+            migrator.verify(&history),
+            Err(MigrationError::ChecksumMismatch { version: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_unknown_migration() {
+        let migrator = Migrator::new(TEST_MIGRATIONS);
+        let history = vec![applied(3, String::new())];
+
+        assert!(matches!(
+            migrator.verify(&history),
+            Err(MigrationError::UnknownVersion(3))
+        ));
+    }
+
+    async fn scratch_connection() -> AnyConnection {
+        dotenv().ok();
+        let db_str = env::var("db_str").unwrap();
+
+        let mut conn = connect(&db_str).await.unwrap();
+        sqlx::raw_sql(
+            "DROP SCHEMA IF EXISTS migration_test CASCADE;
+             CREATE SCHEMA migration_test;
+             SET search_path TO migration_test;",
+        )
+        .execute(&mut conn)
+        .await
+        .unwrap();
+
+        conn
+    }
+
+    async fn up_down_redo(conn: &mut AnyConnection) {
+        let migrator = Migrator::new(TEST_MIGRATIONS);
+
+        assert_eq!(migrator.up(conn).await.unwrap().len(), 2);
+        assert!(migrator.up_locked(conn).await.unwrap().is_empty());
+
+        let reverted = migrator.down(conn).await.unwrap().unwrap();
+        assert_eq!(reverted.version, 2);
+
+        let status = migrator.status(conn).await.unwrap();
+        assert!(status[0].applied);
+        assert!(!status[1].applied);
+
+        let redone = migrator.redo_locked(conn).await.unwrap().unwrap();
+        assert_eq!(redone.version, 1);
+        assert_eq!(migrator.applied(conn).await.unwrap().len(), 1);
+
+        sqlx::query("UPDATE schema_migrations SET checksum = 'edited'")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        assert!(matches!(
+            migrator.up(conn).await,
+            Err(MigrationError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "db_test"]
+    async fn postgres_up_down_redo() {
+        let mut conn = scratch_connection().await;
+
+        up_down_redo(&mut conn).await;
+
+        sqlx::raw_sql("DROP SCHEMA migration_test CASCADE")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn sqlite_up_down_redo() {
+        let mut conn = connect("sqlite::memory:").await.unwrap();
+
+        up_down_redo(&mut conn).await;
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn sqlite_embedded_migrations_apply() {
+        let mut conn = connect("sqlite::memory:").await.unwrap();
+        let migrator = Migrator::for_connection(&conn).unwrap();
+
+        assert_eq!(
+            migrator.up(&mut conn).await.unwrap().len(),
+            SQLITE_MIGRATIONS.len()
+        );
+        assert!(migrator.down(&mut conn).await.unwrap().is_some());
+    }
+}